@@ -0,0 +1,38 @@
+use super::CanvasClient;
+use crate::extensions::account_domains::AccountDomain;
+
+pub struct AccountDomainSearch<'a> {
+    client: &'a CanvasClient,
+    search: crate::extensions::account_domains::AccountDomainSearch<'a>,
+}
+
+impl<'a> AccountDomainSearch<'a> {
+    pub fn set_name(&mut self, name: Option<String>) {
+        self.search.set_name(name);
+    }
+
+    pub fn set_domain(&mut self, domain: Option<String>) {
+        self.search.set_domain(domain);
+    }
+
+    pub fn search(self) -> Result<Vec<AccountDomain>, crate::Error> {
+        self.client.block_on(self.search.search())
+    }
+}
+
+/// The synchronous counterpart of [`crate::AccountDomainsExt`].
+pub trait AccountDomainsExt {
+    /// Returns a list of up to 5 matching account domains.
+    ///
+    /// See [`crate::AccountDomainsExt::search_account_domains`].
+    fn search_account_domains(&self) -> AccountDomainSearch<'_>;
+}
+
+impl AccountDomainsExt for CanvasClient {
+    fn search_account_domains(&self) -> AccountDomainSearch<'_> {
+        AccountDomainSearch {
+            client: self,
+            search: crate::AccountDomainsExt::search_account_domains(&self.inner),
+        }
+    }
+}
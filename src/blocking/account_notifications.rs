@@ -0,0 +1,266 @@
+use super::{CanvasClient, PageIter};
+use crate::{
+    extensions::account_notifications::{
+        AccountNotification, IncludePastNotifications, NotificationQuery, SubAccountNotifications,
+    },
+    AccountId, NotificationId,
+};
+
+/// The synchronous counterpart of [`crate::AccountNotificationsExt`].
+pub trait AccountNotificationsExt {
+    /// See [`crate::AccountNotificationsExt::get_global_notifications_for_account`].
+    fn get_global_notifications_for_account(
+        &self,
+        account_id: AccountId,
+        include_past: IncludePastNotifications,
+    ) -> Result<Vec<AccountNotification>, crate::Error>;
+
+    /// See [`crate::AccountNotificationsExt::get_global_notifications_for_account_stream`].
+    fn get_global_notifications_for_account_iter(
+        &self,
+        account_id: AccountId,
+        include_past: IncludePastNotifications,
+    ) -> PageIter<'_, AccountNotification>;
+
+    /// See [`crate::AccountNotificationsExt::get_notification_for_account`].
+    fn get_notification_for_account(
+        &self,
+        account_id: AccountId,
+        notification_id: NotificationId,
+    ) -> Result<AccountNotification, crate::Error>;
+
+    /// See [`crate::AccountNotificationsExt::close_notification_for_account`].
+    fn close_notification_for_account(
+        &self,
+        account_id: AccountId,
+        notification_id: NotificationId,
+    ) -> Result<AccountNotification, crate::Error>;
+
+    /// See [`crate::AccountNotificationsExt::create_global_notification`].
+    fn create_global_notification(
+        &self,
+        account_id: AccountId,
+        notification: &AccountNotification,
+    ) -> Result<AccountNotification, crate::Error>;
+
+    /// See [`crate::AccountNotificationsExt::update_global_notification`].
+    fn update_global_notification(
+        &self,
+        account_id: AccountId,
+        notification_id: NotificationId,
+        new_notification: &AccountNotification,
+    ) -> Result<AccountNotification, crate::Error>;
+
+    /// See [`crate::AccountNotificationsExt::get_all_notifications_for_account`].
+    fn get_all_notifications_for_account(
+        &self,
+        account_id: AccountId,
+        query: &NotificationQuery,
+    ) -> Result<Vec<AccountNotification>, crate::Error>;
+
+    /// See [`crate::AccountNotificationsExt::get_all_notifications_for_account_stream`].
+    fn get_all_notifications_for_account_iter(
+        &self,
+        account_id: AccountId,
+        query: &NotificationQuery,
+    ) -> PageIter<'_, AccountNotification>;
+
+    /// See [`crate::AccountNotificationsExt::expire_global_notification`].
+    fn expire_global_notification(
+        &self,
+        account_id: AccountId,
+        notification: &AccountNotification,
+    ) -> Result<AccountNotification, crate::Error>;
+
+    /// See [`crate::AccountNotificationsExt::get_notifications_for_account_tree`].
+    fn get_notifications_for_account_tree(
+        &self,
+        account_id: AccountId,
+        query: &NotificationQuery,
+    ) -> Result<Vec<SubAccountNotifications>, crate::Error>;
+}
+
+impl AccountNotificationsExt for CanvasClient {
+    fn get_global_notifications_for_account(
+        &self,
+        account_id: AccountId,
+        include_past: IncludePastNotifications,
+    ) -> Result<Vec<AccountNotification>, crate::Error> {
+        self.block_on(
+            crate::AccountNotificationsExt::get_global_notifications_for_account(
+                &self.inner,
+                account_id,
+                include_past,
+            ),
+        )
+    }
+
+    fn get_global_notifications_for_account_iter(
+        &self,
+        account_id: AccountId,
+        include_past: IncludePastNotifications,
+    ) -> PageIter<'_, AccountNotification> {
+        self.iter(
+            crate::AccountNotificationsExt::get_global_notifications_for_account_stream(
+                &self.inner,
+                account_id,
+                include_past,
+            ),
+        )
+    }
+
+    fn get_notification_for_account(
+        &self,
+        account_id: AccountId,
+        notification_id: NotificationId,
+    ) -> Result<AccountNotification, crate::Error> {
+        self.block_on(
+            crate::AccountNotificationsExt::get_notification_for_account(
+                &self.inner,
+                account_id,
+                notification_id,
+            ),
+        )
+    }
+
+    fn close_notification_for_account(
+        &self,
+        account_id: AccountId,
+        notification_id: NotificationId,
+    ) -> Result<AccountNotification, crate::Error> {
+        self.block_on(
+            crate::AccountNotificationsExt::close_notification_for_account(
+                &self.inner,
+                account_id,
+                notification_id,
+            ),
+        )
+    }
+
+    fn create_global_notification(
+        &self,
+        account_id: AccountId,
+        notification: &AccountNotification,
+    ) -> Result<AccountNotification, crate::Error> {
+        self.block_on(crate::AccountNotificationsExt::create_global_notification(
+            &self.inner,
+            account_id,
+            notification,
+        ))
+    }
+
+    fn update_global_notification(
+        &self,
+        account_id: AccountId,
+        notification_id: NotificationId,
+        new_notification: &AccountNotification,
+    ) -> Result<AccountNotification, crate::Error> {
+        self.block_on(crate::AccountNotificationsExt::update_global_notification(
+            &self.inner,
+            account_id,
+            notification_id,
+            new_notification,
+        ))
+    }
+
+    fn get_all_notifications_for_account(
+        &self,
+        account_id: AccountId,
+        query: &NotificationQuery,
+    ) -> Result<Vec<AccountNotification>, crate::Error> {
+        self.block_on(
+            crate::AccountNotificationsExt::get_all_notifications_for_account(
+                &self.inner,
+                account_id,
+                query,
+            ),
+        )
+    }
+
+    fn get_all_notifications_for_account_iter(
+        &self,
+        account_id: AccountId,
+        query: &NotificationQuery,
+    ) -> PageIter<'_, AccountNotification> {
+        self.iter(
+            crate::AccountNotificationsExt::get_all_notifications_for_account_stream(
+                &self.inner,
+                account_id,
+                query,
+            ),
+        )
+    }
+
+    fn expire_global_notification(
+        &self,
+        account_id: AccountId,
+        notification: &AccountNotification,
+    ) -> Result<AccountNotification, crate::Error> {
+        self.block_on(crate::AccountNotificationsExt::expire_global_notification(
+            &self.inner,
+            account_id,
+            notification,
+        ))
+    }
+
+    fn get_notifications_for_account_tree(
+        &self,
+        account_id: AccountId,
+        query: &NotificationQuery,
+    ) -> Result<Vec<SubAccountNotifications>, crate::Error> {
+        self.block_on(
+            crate::AccountNotificationsExt::get_notifications_for_account_tree(
+                &self.inner,
+                account_id,
+                query,
+            ),
+        )
+    }
+}
+
+/// The synchronous counterpart of
+/// [`NotificationSync`](crate::extensions::account_notifications::sync::NotificationSync).
+#[cfg(feature = "notification_sync")]
+pub struct NotificationSync<'a> {
+    client: &'a CanvasClient,
+    sync: crate::extensions::account_notifications::sync::NotificationSync<'a>,
+}
+
+#[cfg(feature = "notification_sync")]
+impl<'a> NotificationSync<'a> {
+    /// See [`crate::extensions::account_notifications::sync::NotificationSync::new`].
+    pub fn new(client: &'a CanvasClient) -> Self {
+        Self {
+            client,
+            sync: crate::extensions::account_notifications::sync::NotificationSync::new(
+                &client.inner,
+            ),
+        }
+    }
+
+    /// See [`crate::extensions::account_notifications::sync::NotificationSync::set_matching`].
+    pub fn set_matching(
+        mut self,
+        matching: crate::extensions::account_notifications::sync::Matching,
+    ) -> Self {
+        self.sync = self.sync.set_matching(matching);
+
+        self
+    }
+
+    /// See [`crate::extensions::account_notifications::sync::NotificationSync::plan`].
+    pub fn plan(
+        &self,
+        desired: &crate::extensions::account_notifications::sync::DesiredState,
+    ) -> Result<crate::extensions::account_notifications::sync::SyncPlan, crate::Error> {
+        self.client.block_on(self.sync.plan(desired))
+    }
+
+    /// See [`crate::extensions::account_notifications::sync::NotificationSync::apply`].
+    pub fn apply(
+        &self,
+        plan: crate::extensions::account_notifications::sync::SyncPlan,
+    ) -> Result<crate::extensions::account_notifications::sync::SyncReport, crate::Error> {
+        self.client.block_on(self.sync.apply(plan))
+    }
+}
@@ -0,0 +1,362 @@
+use std::{
+    io::Write,
+    path::{Path, PathBuf},
+    pin::Pin,
+    task::{Context, Poll},
+};
+
+use tokio::io::AsyncWrite;
+
+use super::{CanvasClient, PageIter};
+use crate::{
+    extensions::account_reports::{
+        parameters::TypedReport,
+        retention::RetentionPolicy,
+        rows::{ReportRow, ReportRows},
+        CreateReportForm, Report, ReportPollConfig, ReportResponse,
+    },
+    AccountId, ReportId,
+};
+
+/// The synchronous counterpart of [`crate::AccountReportsExt`].
+pub trait AccountReportsExt {
+    /// See [`crate::AccountReportsExt::get_available_reports_by_account`].
+    fn get_available_reports_by_account(
+        &self,
+        account_id: AccountId,
+    ) -> Result<Vec<ReportResponse>, crate::Error>;
+
+    /// See [`crate::AccountReportsExt::get_available_reports_by_account_stream`].
+    fn get_available_reports_by_account_iter(
+        &self,
+        account_id: AccountId,
+    ) -> PageIter<'_, ReportResponse>;
+
+    /// See [`crate::AccountReportsExt::create_report`].
+    fn create_report(
+        &self,
+        account_id: AccountId,
+        report_type: String,
+        parameters: CreateReportForm,
+    ) -> Result<Report, crate::Error>;
+
+    /// See [`crate::AccountReportsExt::create_validated_report`].
+    fn create_validated_report(
+        &self,
+        account_id: AccountId,
+        report_type: String,
+        parameters: CreateReportForm,
+    ) -> Result<Report, crate::Error>;
+
+    /// See [`crate::AccountReportsExt::create_typed_report`].
+    fn create_typed_report<R>(
+        &self,
+        account_id: AccountId,
+        report: R,
+    ) -> Result<Report, crate::Error>
+    where
+        R: TypedReport + Send;
+
+    /// See [`crate::AccountReportsExt::get_reports_by_type`].
+    fn get_reports_by_type(
+        &self,
+        account_id: AccountId,
+        report_type: String,
+    ) -> Result<Vec<Report>, crate::Error>;
+
+    /// See [`crate::AccountReportsExt::get_reports_by_type_stream`].
+    fn get_reports_by_type_iter(
+        &self,
+        account_id: AccountId,
+        report_type: String,
+    ) -> PageIter<'_, Report>;
+
+    /// See [`crate::AccountReportsExt::get_report_by_id`].
+    fn get_report_by_id(
+        &self,
+        account_id: AccountId,
+        report_type: String,
+        report_id: ReportId,
+    ) -> Result<Report, crate::Error>;
+
+    /// See [`crate::AccountReportsExt::delete_report_by_id`].
+    fn delete_report_by_id(
+        &self,
+        account_id: AccountId,
+        report_type: String,
+        report_id: ReportId,
+    ) -> Result<Report, crate::Error>;
+
+    /// See [`crate::AccountReportsExt::apply_report_retention`].
+    fn apply_report_retention(
+        &self,
+        account_id: AccountId,
+        policy: &RetentionPolicy,
+    ) -> Result<Vec<Report>, crate::Error>;
+
+    /// See [`crate::AccountReportsExt::wait_for_report`].
+    fn wait_for_report(
+        &self,
+        account_id: AccountId,
+        report: Report,
+        poll: &ReportPollConfig,
+    ) -> Result<Report, crate::Error>;
+
+    /// See [`crate::AccountReportsExt::run_report_to_completion`].
+    fn run_report_to_completion(
+        &self,
+        account_id: AccountId,
+        report_type: String,
+        parameters: CreateReportForm,
+        poll: &ReportPollConfig,
+    ) -> Result<Report, crate::Error>;
+
+    /// See [`crate::AccountReportsExt::download_report`].
+    fn download_report<W>(&self, report: &Report, writer: &mut W) -> Result<u64, crate::Error>
+    where
+        W: Write + Send + ?Sized;
+
+    /// See [`crate::AccountReportsExt::download_report_to_path`].
+    fn download_report_to_path(&self, report: &Report, path: &Path) -> Result<u64, crate::Error>;
+
+    /// See [`crate::AccountReportsExt::extract_report`].
+    fn extract_report(&self, report: &Report, dir: &Path) -> Result<Vec<PathBuf>, crate::Error>;
+
+    /// See [`crate::AccountReportsExt::run_report_rows`].
+    fn run_report_rows<T: ReportRow>(
+        &self,
+        account_id: AccountId,
+        report_type: String,
+        parameters: CreateReportForm,
+        poll: &ReportPollConfig,
+        dir: &Path,
+    ) -> Result<ReportRows<T>, crate::Error>;
+}
+
+impl AccountReportsExt for CanvasClient {
+    fn get_available_reports_by_account(
+        &self,
+        account_id: AccountId,
+    ) -> Result<Vec<ReportResponse>, crate::Error> {
+        self.block_on(crate::AccountReportsExt::get_available_reports_by_account(
+            &self.inner,
+            account_id,
+        ))
+    }
+
+    fn get_available_reports_by_account_iter(
+        &self,
+        account_id: AccountId,
+    ) -> PageIter<'_, ReportResponse> {
+        self.iter(
+            crate::AccountReportsExt::get_available_reports_by_account_stream(
+                &self.inner,
+                account_id,
+            ),
+        )
+    }
+
+    fn create_report(
+        &self,
+        account_id: AccountId,
+        report_type: String,
+        parameters: CreateReportForm,
+    ) -> Result<Report, crate::Error> {
+        self.block_on(crate::AccountReportsExt::create_report(
+            &self.inner,
+            account_id,
+            report_type,
+            parameters,
+        ))
+    }
+
+    fn create_validated_report(
+        &self,
+        account_id: AccountId,
+        report_type: String,
+        parameters: CreateReportForm,
+    ) -> Result<Report, crate::Error> {
+        self.block_on(crate::AccountReportsExt::create_validated_report(
+            &self.inner,
+            account_id,
+            report_type,
+            parameters,
+        ))
+    }
+
+    fn create_typed_report<R>(
+        &self,
+        account_id: AccountId,
+        report: R,
+    ) -> Result<Report, crate::Error>
+    where
+        R: TypedReport + Send,
+    {
+        self.block_on(crate::AccountReportsExt::create_typed_report(
+            &self.inner,
+            account_id,
+            report,
+        ))
+    }
+
+    fn get_reports_by_type(
+        &self,
+        account_id: AccountId,
+        report_type: String,
+    ) -> Result<Vec<Report>, crate::Error> {
+        self.block_on(crate::AccountReportsExt::get_reports_by_type(
+            &self.inner,
+            account_id,
+            report_type,
+        ))
+    }
+
+    fn get_reports_by_type_iter(
+        &self,
+        account_id: AccountId,
+        report_type: String,
+    ) -> PageIter<'_, Report> {
+        self.iter(crate::AccountReportsExt::get_reports_by_type_stream(
+            &self.inner,
+            account_id,
+            report_type,
+        ))
+    }
+
+    fn get_report_by_id(
+        &self,
+        account_id: AccountId,
+        report_type: String,
+        report_id: ReportId,
+    ) -> Result<Report, crate::Error> {
+        self.block_on(crate::AccountReportsExt::get_report_by_id(
+            &self.inner,
+            account_id,
+            report_type,
+            report_id,
+        ))
+    }
+
+    fn delete_report_by_id(
+        &self,
+        account_id: AccountId,
+        report_type: String,
+        report_id: ReportId,
+    ) -> Result<Report, crate::Error> {
+        self.block_on(crate::AccountReportsExt::delete_report_by_id(
+            &self.inner,
+            account_id,
+            report_type,
+            report_id,
+        ))
+    }
+
+    fn apply_report_retention(
+        &self,
+        account_id: AccountId,
+        policy: &RetentionPolicy,
+    ) -> Result<Vec<Report>, crate::Error> {
+        self.block_on(crate::AccountReportsExt::apply_report_retention(
+            &self.inner,
+            account_id,
+            policy,
+        ))
+    }
+
+    fn wait_for_report(
+        &self,
+        account_id: AccountId,
+        report: Report,
+        poll: &ReportPollConfig,
+    ) -> Result<Report, crate::Error> {
+        self.block_on(crate::AccountReportsExt::wait_for_report(
+            &self.inner,
+            account_id,
+            report,
+            poll,
+        ))
+    }
+
+    fn run_report_to_completion(
+        &self,
+        account_id: AccountId,
+        report_type: String,
+        parameters: CreateReportForm,
+        poll: &ReportPollConfig,
+    ) -> Result<Report, crate::Error> {
+        self.block_on(crate::AccountReportsExt::run_report_to_completion(
+            &self.inner,
+            account_id,
+            report_type,
+            parameters,
+            poll,
+        ))
+    }
+
+    fn download_report<W>(&self, report: &Report, writer: &mut W) -> Result<u64, crate::Error>
+    where
+        W: Write + Send + ?Sized,
+    {
+        self.block_on(crate::AccountReportsExt::download_report(
+            &self.inner,
+            report,
+            &mut BlockingWriter(writer),
+        ))
+    }
+
+    fn download_report_to_path(&self, report: &Report, path: &Path) -> Result<u64, crate::Error> {
+        self.block_on(crate::AccountReportsExt::download_report_to_path(
+            &self.inner,
+            report,
+            path,
+        ))
+    }
+
+    fn extract_report(&self, report: &Report, dir: &Path) -> Result<Vec<PathBuf>, crate::Error> {
+        self.block_on(crate::AccountReportsExt::extract_report(
+            &self.inner,
+            report,
+            dir,
+        ))
+    }
+
+    fn run_report_rows<T: ReportRow>(
+        &self,
+        account_id: AccountId,
+        report_type: String,
+        parameters: CreateReportForm,
+        poll: &ReportPollConfig,
+        dir: &Path,
+    ) -> Result<ReportRows<T>, crate::Error> {
+        self.block_on(crate::AccountReportsExt::run_report_rows(
+            &self.inner,
+            account_id,
+            report_type,
+            parameters,
+            poll,
+            dir,
+        ))
+    }
+}
+
+/// Adapts a [`Write`] to an [`AsyncWrite`]. Writes block, which is fine as
+/// the blocking client owns the thread its runtime runs on.
+struct BlockingWriter<'a, W: ?Sized>(&'a mut W);
+
+impl<W: Write + ?Sized> AsyncWrite for BlockingWriter<'_, W> {
+    fn poll_write(
+        self: Pin<&mut Self>,
+        _cx: &mut Context<'_>,
+        buf: &[u8],
+    ) -> Poll<std::io::Result<usize>> {
+        Poll::Ready(self.get_mut().0.write(buf))
+    }
+
+    fn poll_flush(self: Pin<&mut Self>, _cx: &mut Context<'_>) -> Poll<std::io::Result<()>> {
+        Poll::Ready(self.get_mut().0.flush())
+    }
+
+    fn poll_shutdown(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<std::io::Result<()>> {
+        self.poll_flush(cx)
+    }
+}
@@ -0,0 +1,52 @@
+use super::{CanvasClient, PageIter};
+use crate::{extensions::accounts::Account, AccountId};
+
+/// The synchronous counterpart of [`crate::AccountsExt`].
+pub trait AccountsExt {
+    /// See [`crate::AccountsExt::get_account`].
+    fn get_account(&self, account_id: AccountId) -> Result<Account, crate::Error>;
+
+    /// See [`crate::AccountsExt::get_sub_accounts`].
+    fn get_sub_accounts(
+        &self,
+        account_id: AccountId,
+        recursive: bool,
+    ) -> Result<Vec<Account>, crate::Error>;
+
+    /// See [`crate::AccountsExt::get_sub_accounts_stream`].
+    fn get_sub_accounts_iter(
+        &self,
+        account_id: AccountId,
+        recursive: bool,
+    ) -> PageIter<'_, Account>;
+}
+
+impl AccountsExt for CanvasClient {
+    fn get_account(&self, account_id: AccountId) -> Result<Account, crate::Error> {
+        self.block_on(crate::AccountsExt::get_account(&self.inner, account_id))
+    }
+
+    fn get_sub_accounts(
+        &self,
+        account_id: AccountId,
+        recursive: bool,
+    ) -> Result<Vec<Account>, crate::Error> {
+        self.block_on(crate::AccountsExt::get_sub_accounts(
+            &self.inner,
+            account_id,
+            recursive,
+        ))
+    }
+
+    fn get_sub_accounts_iter(
+        &self,
+        account_id: AccountId,
+        recursive: bool,
+    ) -> PageIter<'_, Account> {
+        self.iter(crate::AccountsExt::get_sub_accounts_stream(
+            &self.inner,
+            account_id,
+            recursive,
+        ))
+    }
+}
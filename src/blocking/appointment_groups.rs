@@ -0,0 +1,255 @@
+use super::{CanvasClient, PageIter};
+use crate::{
+    extensions::{
+        appointment_groups::{
+            AppointmentGroup, AppointmentGroupForm, AppointmentGroupInclude, AppointmentGroupQuery,
+            ParticipantGroup, ParticipantUser, RegistrationStatus, ReservationForm,
+        },
+        calendar_events::CalendarEvent,
+    },
+    AppointmentGroupId, CalendarEventId,
+};
+
+/// The synchronous counterpart of [`crate::AppointmentGroupsExt`].
+pub trait AppointmentGroupsExt {
+    /// See [`crate::AppointmentGroupsExt::list_appointment_groups`].
+    fn list_appointment_groups(
+        &self,
+        query: &AppointmentGroupQuery,
+    ) -> Result<Vec<AppointmentGroup>, crate::Error>;
+
+    /// See [`crate::AppointmentGroupsExt::list_appointment_groups_stream`].
+    fn list_appointment_groups_iter(
+        &self,
+        query: &AppointmentGroupQuery,
+    ) -> PageIter<'_, AppointmentGroup>;
+
+    /// See [`crate::AppointmentGroupsExt::get_appointment_group`].
+    fn get_appointment_group(
+        &self,
+        appointment_group_id: AppointmentGroupId,
+        include: &[AppointmentGroupInclude],
+    ) -> Result<AppointmentGroup, crate::Error>;
+
+    /// See [`crate::AppointmentGroupsExt::create_appointment_group`].
+    fn create_appointment_group(
+        &self,
+        appointment_group: &AppointmentGroupForm,
+    ) -> Result<AppointmentGroup, crate::Error>;
+
+    /// See [`crate::AppointmentGroupsExt::update_appointment_group`].
+    fn update_appointment_group(
+        &self,
+        appointment_group_id: AppointmentGroupId,
+        appointment_group: &AppointmentGroupForm,
+    ) -> Result<AppointmentGroup, crate::Error>;
+
+    /// See [`crate::AppointmentGroupsExt::delete_appointment_group`].
+    fn delete_appointment_group(
+        &self,
+        appointment_group_id: AppointmentGroupId,
+        cancel_reason: Option<&str>,
+    ) -> Result<AppointmentGroup, crate::Error>;
+
+    /// See [`crate::AppointmentGroupsExt::list_appointment_group_users`].
+    fn list_appointment_group_users(
+        &self,
+        appointment_group_id: AppointmentGroupId,
+        registration_status: RegistrationStatus,
+    ) -> Result<Vec<ParticipantUser>, crate::Error>;
+
+    /// See [`crate::AppointmentGroupsExt::list_appointment_group_users_stream`].
+    fn list_appointment_group_users_iter(
+        &self,
+        appointment_group_id: AppointmentGroupId,
+        registration_status: RegistrationStatus,
+    ) -> PageIter<'_, ParticipantUser>;
+
+    /// See [`crate::AppointmentGroupsExt::list_appointment_group_groups`].
+    fn list_appointment_group_groups(
+        &self,
+        appointment_group_id: AppointmentGroupId,
+        registration_status: RegistrationStatus,
+    ) -> Result<Vec<ParticipantGroup>, crate::Error>;
+
+    /// See [`crate::AppointmentGroupsExt::list_appointment_group_groups_stream`].
+    fn list_appointment_group_groups_iter(
+        &self,
+        appointment_group_id: AppointmentGroupId,
+        registration_status: RegistrationStatus,
+    ) -> PageIter<'_, ParticipantGroup>;
+
+    /// See [`crate::AppointmentGroupsExt::next_appointment`].
+    fn next_appointment(
+        &self,
+        appointment_group_ids: &[AppointmentGroupId],
+    ) -> Result<Option<CalendarEvent>, crate::Error>;
+
+    /// See [`crate::AppointmentGroupsExt::reserve_time_slot`].
+    fn reserve_time_slot(
+        &self,
+        time_slot_id: CalendarEventId,
+        reservation: &ReservationForm,
+    ) -> Result<CalendarEvent, crate::Error>;
+
+    /// See [`crate::AppointmentGroupsExt::unreserve_time_slot`].
+    fn unreserve_time_slot(
+        &self,
+        reservation_id: CalendarEventId,
+        cancel_reason: Option<&str>,
+    ) -> Result<CalendarEvent, crate::Error>;
+}
+
+impl AppointmentGroupsExt for CanvasClient {
+    fn list_appointment_groups(
+        &self,
+        query: &AppointmentGroupQuery,
+    ) -> Result<Vec<AppointmentGroup>, crate::Error> {
+        self.block_on(crate::AppointmentGroupsExt::list_appointment_groups(
+            &self.inner,
+            query,
+        ))
+    }
+
+    fn list_appointment_groups_iter(
+        &self,
+        query: &AppointmentGroupQuery,
+    ) -> PageIter<'_, AppointmentGroup> {
+        self.iter(crate::AppointmentGroupsExt::list_appointment_groups_stream(
+            &self.inner,
+            query,
+        ))
+    }
+
+    fn get_appointment_group(
+        &self,
+        appointment_group_id: AppointmentGroupId,
+        include: &[AppointmentGroupInclude],
+    ) -> Result<AppointmentGroup, crate::Error> {
+        self.block_on(crate::AppointmentGroupsExt::get_appointment_group(
+            &self.inner,
+            appointment_group_id,
+            include,
+        ))
+    }
+
+    fn create_appointment_group(
+        &self,
+        appointment_group: &AppointmentGroupForm,
+    ) -> Result<AppointmentGroup, crate::Error> {
+        self.block_on(crate::AppointmentGroupsExt::create_appointment_group(
+            &self.inner,
+            appointment_group,
+        ))
+    }
+
+    fn update_appointment_group(
+        &self,
+        appointment_group_id: AppointmentGroupId,
+        appointment_group: &AppointmentGroupForm,
+    ) -> Result<AppointmentGroup, crate::Error> {
+        self.block_on(crate::AppointmentGroupsExt::update_appointment_group(
+            &self.inner,
+            appointment_group_id,
+            appointment_group,
+        ))
+    }
+
+    fn delete_appointment_group(
+        &self,
+        appointment_group_id: AppointmentGroupId,
+        cancel_reason: Option<&str>,
+    ) -> Result<AppointmentGroup, crate::Error> {
+        self.block_on(crate::AppointmentGroupsExt::delete_appointment_group(
+            &self.inner,
+            appointment_group_id,
+            cancel_reason,
+        ))
+    }
+
+    fn list_appointment_group_users(
+        &self,
+        appointment_group_id: AppointmentGroupId,
+        registration_status: RegistrationStatus,
+    ) -> Result<Vec<ParticipantUser>, crate::Error> {
+        self.block_on(crate::AppointmentGroupsExt::list_appointment_group_users(
+            &self.inner,
+            appointment_group_id,
+            registration_status,
+        ))
+    }
+
+    fn list_appointment_group_users_iter(
+        &self,
+        appointment_group_id: AppointmentGroupId,
+        registration_status: RegistrationStatus,
+    ) -> PageIter<'_, ParticipantUser> {
+        self.iter(
+            crate::AppointmentGroupsExt::list_appointment_group_users_stream(
+                &self.inner,
+                appointment_group_id,
+                registration_status,
+            ),
+        )
+    }
+
+    fn list_appointment_group_groups(
+        &self,
+        appointment_group_id: AppointmentGroupId,
+        registration_status: RegistrationStatus,
+    ) -> Result<Vec<ParticipantGroup>, crate::Error> {
+        self.block_on(crate::AppointmentGroupsExt::list_appointment_group_groups(
+            &self.inner,
+            appointment_group_id,
+            registration_status,
+        ))
+    }
+
+    fn list_appointment_group_groups_iter(
+        &self,
+        appointment_group_id: AppointmentGroupId,
+        registration_status: RegistrationStatus,
+    ) -> PageIter<'_, ParticipantGroup> {
+        self.iter(
+            crate::AppointmentGroupsExt::list_appointment_group_groups_stream(
+                &self.inner,
+                appointment_group_id,
+                registration_status,
+            ),
+        )
+    }
+
+    fn next_appointment(
+        &self,
+        appointment_group_ids: &[AppointmentGroupId],
+    ) -> Result<Option<CalendarEvent>, crate::Error> {
+        self.block_on(crate::AppointmentGroupsExt::next_appointment(
+            &self.inner,
+            appointment_group_ids,
+        ))
+    }
+
+    fn reserve_time_slot(
+        &self,
+        time_slot_id: CalendarEventId,
+        reservation: &ReservationForm,
+    ) -> Result<CalendarEvent, crate::Error> {
+        self.block_on(crate::AppointmentGroupsExt::reserve_time_slot(
+            &self.inner,
+            time_slot_id,
+            reservation,
+        ))
+    }
+
+    fn unreserve_time_slot(
+        &self,
+        reservation_id: CalendarEventId,
+        cancel_reason: Option<&str>,
+    ) -> Result<CalendarEvent, crate::Error> {
+        self.block_on(crate::AppointmentGroupsExt::unreserve_time_slot(
+            &self.inner,
+            reservation_id,
+            cancel_reason,
+        ))
+    }
+}
@@ -0,0 +1,213 @@
+use super::{CanvasClient, PageIter};
+#[cfg(feature = "ical")]
+use crate::extensions::calendar_events::{ical::IcsCalendar, CalendarEvent};
+use crate::{
+    extensions::calendar::{AccountCalendar, AccountVisibility, Visibility},
+    AccountId,
+};
+
+/// The synchronous counterpart of [`crate::CalendarExt`].
+pub trait CalendarExt {
+    /// See [`crate::CalendarExt::all_calendars`].
+    fn all_calendars(&self) -> Result<Vec<AccountCalendar>, crate::Error>;
+
+    /// See [`crate::CalendarExt::all_calendars_stream`].
+    fn all_calendars_iter(&self) -> PageIter<'_, AccountCalendar>;
+
+    /// See [`crate::CalendarExt::search_calendars`].
+    fn search_calendars(&self, search_term: &str) -> Result<Vec<AccountCalendar>, crate::Error>;
+
+    /// See [`crate::CalendarExt::search_calendars_stream`].
+    fn search_calendars_iter(&self, search_term: &str) -> PageIter<'_, AccountCalendar>;
+
+    /// See [`crate::CalendarExt::calendar_by_account_id`].
+    fn calendar_by_account_id(
+        &self,
+        account_id: AccountId,
+    ) -> Result<AccountCalendar, crate::Error>;
+
+    /// See [`crate::CalendarExt::set_account_calendar_visible`].
+    fn set_account_calendar_visible(
+        &self,
+        account_id: AccountId,
+        visibility: Visibility,
+    ) -> Result<AccountCalendar, crate::Error>;
+
+    /// See [`crate::CalendarExt::set_many_account_calendars_visible`].
+    fn set_many_account_calendars_visible(
+        &self,
+        account_id: AccountId,
+        account_calendars: &[AccountVisibility],
+    ) -> Result<AccountCalendar, crate::Error>;
+
+    /// See [`crate::CalendarExt::all_account_calendars`].
+    fn all_account_calendars(
+        &self,
+        account_id: AccountId,
+        filter: Visibility,
+    ) -> Result<Vec<AccountCalendar>, crate::Error>;
+
+    /// See [`crate::CalendarExt::all_account_calendars_stream`].
+    fn all_account_calendars_iter(
+        &self,
+        account_id: AccountId,
+        filter: Visibility,
+    ) -> PageIter<'_, AccountCalendar>;
+
+    /// See [`crate::CalendarExt::search_all_account_calendars`].
+    fn search_all_account_calendars(
+        &self,
+        account_id: AccountId,
+        search_term: &str,
+        filter: Visibility,
+    ) -> Result<Vec<AccountCalendar>, crate::Error>;
+
+    /// See [`crate::CalendarExt::search_all_account_calendars_stream`].
+    fn search_all_account_calendars_iter(
+        &self,
+        account_id: AccountId,
+        search_term: &str,
+        filter: Visibility,
+    ) -> PageIter<'_, AccountCalendar>;
+
+    /// See [`crate::CalendarExt::count_account_visible_calendars`].
+    fn count_account_visible_calendars(&self, account_id: AccountId) -> Result<u64, crate::Error>;
+
+    /// See [`crate::CalendarExt::import_ics`].
+    #[cfg(feature = "ical")]
+    fn import_ics(
+        &self,
+        account_id: AccountId,
+        calendar: &IcsCalendar,
+    ) -> Result<Vec<CalendarEvent>, crate::Error>;
+}
+
+impl CalendarExt for CanvasClient {
+    fn all_calendars(&self) -> Result<Vec<AccountCalendar>, crate::Error> {
+        self.block_on(crate::CalendarExt::all_calendars(&self.inner))
+    }
+
+    fn all_calendars_iter(&self) -> PageIter<'_, AccountCalendar> {
+        self.iter(crate::CalendarExt::all_calendars_stream(&self.inner))
+    }
+
+    fn search_calendars(&self, search_term: &str) -> Result<Vec<AccountCalendar>, crate::Error> {
+        self.block_on(crate::CalendarExt::search_calendars(
+            &self.inner,
+            search_term,
+        ))
+    }
+
+    fn search_calendars_iter(&self, search_term: &str) -> PageIter<'_, AccountCalendar> {
+        self.iter(crate::CalendarExt::search_calendars_stream(
+            &self.inner,
+            search_term,
+        ))
+    }
+
+    fn calendar_by_account_id(
+        &self,
+        account_id: AccountId,
+    ) -> Result<AccountCalendar, crate::Error> {
+        self.block_on(crate::CalendarExt::calendar_by_account_id(
+            &self.inner,
+            account_id,
+        ))
+    }
+
+    fn set_account_calendar_visible(
+        &self,
+        account_id: AccountId,
+        visibility: Visibility,
+    ) -> Result<AccountCalendar, crate::Error> {
+        self.block_on(crate::CalendarExt::set_account_calendar_visible(
+            &self.inner,
+            account_id,
+            visibility,
+        ))
+    }
+
+    fn set_many_account_calendars_visible(
+        &self,
+        account_id: AccountId,
+        account_calendars: &[AccountVisibility],
+    ) -> Result<AccountCalendar, crate::Error> {
+        self.block_on(crate::CalendarExt::set_many_account_calendars_visible(
+            &self.inner,
+            account_id,
+            account_calendars,
+        ))
+    }
+
+    fn all_account_calendars(
+        &self,
+        account_id: AccountId,
+        filter: Visibility,
+    ) -> Result<Vec<AccountCalendar>, crate::Error> {
+        self.block_on(crate::CalendarExt::all_account_calendars(
+            &self.inner,
+            account_id,
+            filter,
+        ))
+    }
+
+    fn all_account_calendars_iter(
+        &self,
+        account_id: AccountId,
+        filter: Visibility,
+    ) -> PageIter<'_, AccountCalendar> {
+        self.iter(crate::CalendarExt::all_account_calendars_stream(
+            &self.inner,
+            account_id,
+            filter,
+        ))
+    }
+
+    fn search_all_account_calendars(
+        &self,
+        account_id: AccountId,
+        search_term: &str,
+        filter: Visibility,
+    ) -> Result<Vec<AccountCalendar>, crate::Error> {
+        self.block_on(crate::CalendarExt::search_all_account_calendars(
+            &self.inner,
+            account_id,
+            search_term,
+            filter,
+        ))
+    }
+
+    fn search_all_account_calendars_iter(
+        &self,
+        account_id: AccountId,
+        search_term: &str,
+        filter: Visibility,
+    ) -> PageIter<'_, AccountCalendar> {
+        self.iter(crate::CalendarExt::search_all_account_calendars_stream(
+            &self.inner,
+            account_id,
+            search_term,
+            filter,
+        ))
+    }
+
+    fn count_account_visible_calendars(&self, account_id: AccountId) -> Result<u64, crate::Error> {
+        self.block_on(crate::CalendarExt::count_account_visible_calendars(
+            &self.inner,
+            account_id,
+        ))
+    }
+
+    #[cfg(feature = "ical")]
+    fn import_ics(
+        &self,
+        account_id: AccountId,
+        calendar: &IcsCalendar,
+    ) -> Result<Vec<CalendarEvent>, crate::Error> {
+        self.block_on(crate::CalendarExt::import_ics(
+            &self.inner,
+            account_id,
+            calendar,
+        ))
+    }
+}
@@ -0,0 +1,106 @@
+use super::{CanvasClient, PageIter};
+use crate::{
+    extensions::calendar_events::{CalendarEvent, CalendarEventForm, CalendarEventQuery, Which},
+    CalendarEventId,
+};
+
+/// The synchronous counterpart of [`crate::CalendarEventsExt`].
+pub trait CalendarEventsExt {
+    /// See [`crate::CalendarEventsExt::list_calendar_events`].
+    fn list_calendar_events(
+        &self,
+        query: &CalendarEventQuery,
+    ) -> Result<Vec<CalendarEvent>, crate::Error>;
+
+    /// See [`crate::CalendarEventsExt::list_calendar_events_stream`].
+    fn list_calendar_events_iter(&self, query: &CalendarEventQuery) -> PageIter<'_, CalendarEvent>;
+
+    /// See [`crate::CalendarEventsExt::get_calendar_event`].
+    fn get_calendar_event(&self, event_id: CalendarEventId) -> Result<CalendarEvent, crate::Error>;
+
+    /// See [`crate::CalendarEventsExt::create_calendar_event`].
+    fn create_calendar_event(
+        &self,
+        event: &CalendarEventForm,
+    ) -> Result<CalendarEvent, crate::Error>;
+
+    /// See [`crate::CalendarEventsExt::update_calendar_event`].
+    fn update_calendar_event(
+        &self,
+        event_id: CalendarEventId,
+        event: &CalendarEventForm,
+        which: Option<Which>,
+    ) -> Result<Vec<CalendarEvent>, crate::Error>;
+
+    /// See [`crate::CalendarEventsExt::delete_calendar_event`].
+    fn delete_calendar_event(
+        &self,
+        event_id: CalendarEventId,
+        cancel_reason: Option<&str>,
+        which: Option<Which>,
+    ) -> Result<Vec<CalendarEvent>, crate::Error>;
+}
+
+impl CalendarEventsExt for CanvasClient {
+    fn list_calendar_events(
+        &self,
+        query: &CalendarEventQuery,
+    ) -> Result<Vec<CalendarEvent>, crate::Error> {
+        self.block_on(crate::CalendarEventsExt::list_calendar_events(
+            &self.inner,
+            query,
+        ))
+    }
+
+    fn list_calendar_events_iter(&self, query: &CalendarEventQuery) -> PageIter<'_, CalendarEvent> {
+        self.iter(crate::CalendarEventsExt::list_calendar_events_stream(
+            &self.inner,
+            query,
+        ))
+    }
+
+    fn get_calendar_event(&self, event_id: CalendarEventId) -> Result<CalendarEvent, crate::Error> {
+        self.block_on(crate::CalendarEventsExt::get_calendar_event(
+            &self.inner,
+            event_id,
+        ))
+    }
+
+    fn create_calendar_event(
+        &self,
+        event: &CalendarEventForm,
+    ) -> Result<CalendarEvent, crate::Error> {
+        self.block_on(crate::CalendarEventsExt::create_calendar_event(
+            &self.inner,
+            event,
+        ))
+    }
+
+    fn update_calendar_event(
+        &self,
+        event_id: CalendarEventId,
+        event: &CalendarEventForm,
+        which: Option<Which>,
+    ) -> Result<Vec<CalendarEvent>, crate::Error> {
+        self.block_on(crate::CalendarEventsExt::update_calendar_event(
+            &self.inner,
+            event_id,
+            event,
+            which,
+        ))
+    }
+
+    fn delete_calendar_event(
+        &self,
+        event_id: CalendarEventId,
+        cancel_reason: Option<&str>,
+        which: Option<Which>,
+    ) -> Result<Vec<CalendarEvent>, crate::Error> {
+        self.block_on(crate::CalendarEventsExt::delete_calendar_event(
+            &self.inner,
+            event_id,
+            cancel_reason,
+            which,
+        ))
+    }
+}
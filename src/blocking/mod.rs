@@ -0,0 +1,167 @@
+//! A synchronous [`CanvasClient`] for programs that do not use an async runtime.
+//!
+//! Each `Ext` trait of the crate has a counterpart in this module with the same
+//! methods, which block the current thread until the request completes instead
+//! of returning a future. Methods that stream a paginated endpoint return a
+//! [`PageIter`], which fetches each page as it is iterated.
+//!
+//! Like `reqwest::blocking`, the client drives an async client on an internal
+//! single-threaded runtime, so rate limiting, retries, OAuth2 and cassettes
+//! behave exactly as they do for [`crate::CanvasClient`]. Only the `rt`
+//! feature of `tokio` is required. The async client is built for, and only
+//! ever used on, that runtime, as pooled connections are bound to the runtime
+//! that opened them.
+//!
+//! The blocking client must not be used from within an async runtime, as
+//! blocking the runtime's thread would panic.
+//!
+//! # Example
+//! ```rs
+//! use canvas_api::blocking::{AccountDomainsExt, CanvasClient};
+//!
+//! let client = CanvasClient::builder(canvas_token)
+//!     .set_api_url("https://canvas.example.edu/api")
+//!     .build_blocking()?;
+//!
+//! let mut search = client.search_account_domains();
+//! search.set_name(Some(String::from("utah")));
+//! let account_domains = search.search()?;
+//! ```
+
+use std::{future::Future, sync::Arc};
+
+use futures::StreamExt;
+use tokio::runtime::Runtime;
+
+use crate::{CanvasClientBuilder, PageStream, UserId};
+
+#[cfg(feature = "account_domains")]
+mod account_domains;
+#[cfg(feature = "account_notifications")]
+mod account_notifications;
+#[cfg(feature = "account_reports")]
+mod account_reports;
+#[cfg(feature = "accounts")]
+mod accounts;
+#[cfg(feature = "appointment_groups")]
+mod appointment_groups;
+#[cfg(feature = "calendar")]
+mod calendar;
+#[cfg(feature = "calendar_events")]
+mod calendar_events;
+
+#[cfg(feature = "account_domains")]
+pub use account_domains::{AccountDomainSearch, AccountDomainsExt};
+#[cfg(feature = "account_notifications")]
+pub use account_notifications::AccountNotificationsExt;
+#[cfg(feature = "notification_sync")]
+pub use account_notifications::NotificationSync;
+#[cfg(feature = "account_reports")]
+pub use account_reports::AccountReportsExt;
+#[cfg(feature = "accounts")]
+pub use accounts::AccountsExt;
+#[cfg(feature = "appointment_groups")]
+pub use appointment_groups::AppointmentGroupsExt;
+#[cfg(feature = "calendar")]
+pub use calendar::CalendarExt;
+#[cfg(feature = "calendar_events")]
+pub use calendar_events::CalendarEventsExt;
+
+/// Represents the synchronous canvas client that implements API functionality.
+///
+/// Cloning a client is cheap, and the clone shares the connection pool, rate
+/// limit quota, credentials and runtime of the original.
+#[derive(Clone)]
+pub struct CanvasClient {
+    /// The async client every request is made through.
+    inner: crate::CanvasClient,
+    /// The runtime the requests of `inner` are driven on.
+    runtime: Arc<Runtime>,
+}
+
+/// An iterator over the items of a paginated endpoint.
+///
+/// Pages are requested lazily as the iterator is advanced, so only a single
+/// page of items is held in memory at a time.
+pub struct PageIter<'a, T> {
+    stream: PageStream<'a, T>,
+    runtime: &'a Runtime,
+}
+
+impl CanvasClient {
+    /// Creates a new [`CanvasClientBuilder`] to configure a client. Finish
+    /// the builder with [`CanvasClientBuilder::build_blocking`].
+    ///
+    /// This is the same as [`CanvasClientBuilder::new()`].
+    pub fn builder(canvas_token: String) -> CanvasClientBuilder {
+        CanvasClientBuilder::new(canvas_token)
+    }
+
+    /// Returns a handle to this client that makes every request as the user
+    /// with the given ID.
+    ///
+    /// See [`crate::CanvasClient::as_user`].
+    pub fn as_user(&self, user_id: impl Into<UserId>) -> CanvasClient {
+        CanvasClient {
+            inner: self.inner.as_user(user_id),
+            runtime: Arc::clone(&self.runtime),
+        }
+    }
+
+    /// The ID of the user this client masquerades as, if any.
+    pub fn as_user_id(&self) -> Option<&UserId> {
+        self.inner.as_user_id()
+    }
+
+    /// Revokes the access token the client authorizes requests with.
+    ///
+    /// See [`crate::CanvasClient::revoke_token`].
+    pub fn revoke_token(&self, expire_sessions: bool) -> Result<(), crate::Error> {
+        self.block_on(self.inner.revoke_token(expire_sessions))
+    }
+
+    fn block_on<F: Future>(&self, future: F) -> F::Output {
+        self.runtime.block_on(future)
+    }
+
+    #[cfg(any(
+        feature = "account_notifications",
+        feature = "account_reports",
+        feature = "accounts",
+        feature = "appointment_groups",
+        feature = "calendar",
+        feature = "calendar_events"
+    ))]
+    fn iter<'a, T>(&'a self, stream: PageStream<'a, T>) -> PageIter<'a, T> {
+        PageIter {
+            stream,
+            runtime: &self.runtime,
+        }
+    }
+}
+
+impl CanvasClientBuilder {
+    /// Builds a synchronous [`CanvasClient`], returning an error if the client
+    /// could not be built.
+    pub fn build_blocking(self) -> Result<CanvasClient, crate::Error> {
+        let runtime = tokio::runtime::Builder::new_current_thread()
+            .enable_all()
+            .build()
+            .map_err(|e| crate::Error::Runtime { source: e })?;
+        // the client is built inside the runtime its connections are opened on
+        let inner = runtime.block_on(async { self.build() })?;
+
+        Ok(CanvasClient {
+            inner,
+            runtime: Arc::new(runtime),
+        })
+    }
+}
+
+impl<'a, T> Iterator for PageIter<'a, T> {
+    type Item = Result<T, crate::Error>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.runtime.block_on(self.stream.next())
+    }
+}
@@ -1,16 +1,78 @@
+use futures::stream::BoxStream;
+#[cfg(any(
+    feature = "account_notifications",
+    feature = "account_reports",
+    feature = "accounts",
+    feature = "appointment_groups",
+    feature = "calendar",
+    feature = "calendar_events"
+))]
+use futures::{stream, StreamExt, TryStreamExt};
+#[cfg(any(
+    feature = "account_notifications",
+    feature = "account_reports",
+    feature = "accounts",
+    feature = "appointment_groups",
+    feature = "calendar",
+    feature = "calendar_events"
+))]
+use reqwest::header::LINK;
 use reqwest::{
     header::{HeaderMap, HeaderValue, AUTHORIZATION},
-    Client, RequestBuilder,
+    Client, Request, RequestBuilder, Response,
+};
+
+use std::sync::Arc;
+
+use crate::{
+    cassette::Cassette,
+    oauth::{OAuthClient, OAuthSession, OAuthToken, TokenStore},
+    rate_limit::{RateLimitConfig, RateLimiter},
+    retry::RetryPolicy,
+    UserId,
 };
 
 const BASE_API_URL: &str = "https://canvas.instructure.com/api";
 
+/// A stream of items from a paginated endpoint.
+///
+/// Pages are requested lazily as the stream is polled, so only a single page
+/// of items is held in memory at a time.
+pub type PageStream<'a, T> = BoxStream<'a, Result<T, crate::Error>>;
+
 /// Represents the main canvas client that implements API functionality.
+///
+/// Cloning a client is cheap, and the clone shares the connection pool, rate
+/// limit quota and credentials of the original.
+#[derive(Clone)]
 pub struct CanvasClient {
     /// The HTTP client to make requests with.
     http_client: Client,
     /// The base API url for each request.
     api_url: String,
+    /// The number of items to request per page on paginated endpoints.
+    #[cfg(any(
+        feature = "account_notifications",
+        feature = "account_reports",
+        feature = "accounts",
+        feature = "appointment_groups",
+        feature = "calendar",
+        feature = "calendar_events"
+    ))]
+    per_page: Option<u32>,
+    /// Throttles requests to stay within the Canvas rate limit. `None` if
+    /// rate limiting is disabled.
+    rate_limiter: Option<Arc<RateLimiter>>,
+    /// Determines which failed requests are retried.
+    retry_policy: RetryPolicy,
+    /// The OAuth2 credentials to authorize requests with. `None` if requests
+    /// are authorized with a static access token.
+    oauth: Option<Arc<OAuthSession>>,
+    /// The ID of the user to masquerade as. `None` if requests are made as the
+    /// authenticated user.
+    as_user_id: Option<UserId>,
+    /// Records or replays every request. `None` if requests are sent as normal.
+    cassette: Option<Arc<Cassette>>,
 }
 
 pub struct CanvasClientBuilder {
@@ -19,10 +81,134 @@ pub struct CanvasClientBuilder {
 }
 
 struct CanvasClientConfig {
-    /// The user token for making requests.
-    canvas_token: String,
+    /// The credentials for making requests.
+    credentials: Credentials,
     /// The url for API requests.
     api_url: String,
+    /// The number of items to request per page on paginated endpoints.
+    #[cfg(any(
+        feature = "account_notifications",
+        feature = "account_reports",
+        feature = "accounts",
+        feature = "appointment_groups",
+        feature = "calendar",
+        feature = "calendar_events"
+    ))]
+    per_page: Option<u32>,
+    /// The rate limiting configuration. `None` if rate limiting is disabled.
+    rate_limit: Option<RateLimitConfig>,
+    /// Determines which failed requests are retried.
+    retry_policy: RetryPolicy,
+    /// Records or replays every request. `None` if requests are sent as normal.
+    cassette: Option<Cassette>,
+}
+
+/// How a [`CanvasClient`] authorizes its requests.
+enum Credentials {
+    /// A static access token, such as a personal access token.
+    Token(String),
+    /// An OAuth2 token that is refreshed once it expires.
+    OAuth {
+        client: OAuthClient,
+        store: Arc<dyn TokenStore>,
+    },
+}
+
+/// The pagination links Canvas returns in the `Link` header of a paginated
+/// response.
+///
+/// [See docs](https://canvas.instructure.com/doc/api/file.pagination.html).
+#[cfg(any(
+    feature = "account_notifications",
+    feature = "account_reports",
+    feature = "accounts",
+    feature = "appointment_groups",
+    feature = "calendar",
+    feature = "calendar_events"
+))]
+#[derive(Debug, Default)]
+pub(crate) struct PageLinks {
+    /// The url of the page that was just returned.
+    current: Option<String>,
+    /// The url of the next page. `None` if this is the last page.
+    next: Option<String>,
+    /// The url of the last page. Canvas omits this when counting the total
+    /// number of pages would be too expensive.
+    last: Option<String>,
+}
+
+#[cfg(any(
+    feature = "account_notifications",
+    feature = "account_reports",
+    feature = "accounts",
+    feature = "appointment_groups",
+    feature = "calendar",
+    feature = "calendar_events"
+))]
+impl PageLinks {
+    /// Parses the RFC 5988 `Link` headers of a response.
+    ///
+    /// Each header is a comma separated list of entries in the form
+    /// `<https://...>; rel="next"`. Unknown relations are ignored.
+    pub(crate) fn from_headers(headers: &HeaderMap) -> Self {
+        let mut links = Self::default();
+
+        for value in headers.get_all(LINK) {
+            let Ok(value) = value.to_str() else {
+                continue;
+            };
+
+            let mut rest = value;
+            while let Some(start) = rest.find('<') {
+                let Some(end) = rest[start..].find('>').map(|end| start + end) else {
+                    break;
+                };
+                let url = &rest[start + 1..end];
+
+                // the parameters of this entry run until the start of the next url
+                let params_end = rest[end..].find('<').map_or(rest.len(), |next| end + next);
+                let params = &rest[end + 1..params_end];
+
+                for param in params.split(';') {
+                    let Some((key, relations)) = param.split_once('=') else {
+                        continue;
+                    };
+                    if !key.trim().eq_ignore_ascii_case("rel") {
+                        continue;
+                    }
+
+                    let relations = relations
+                        .trim()
+                        .trim_end_matches(',')
+                        .trim()
+                        .trim_matches('"');
+                    for relation in relations.split_whitespace() {
+                        let slot = match relation {
+                            "current" => &mut links.current,
+                            "next" => &mut links.next,
+                            "last" => &mut links.last,
+                            _ => continue,
+                        };
+                        *slot = Some(url.to_string());
+                    }
+                }
+
+                rest = &rest[params_end..];
+            }
+        }
+
+        links
+    }
+
+    /// The url of the next page to request, if there is one.
+    pub(crate) fn next_page(&self) -> Option<&str> {
+        // some Canvas versions still emit a `next` link on the last page
+        if self.current.is_some() && self.current == self.last {
+            return None;
+        }
+
+        self.next.as_deref()
+    }
 }
 
 impl CanvasClient {
@@ -33,30 +219,345 @@ impl CanvasClient {
         CanvasClientBuilder::new(canvas_token)
     }
 
+    /// Returns a handle to this client that makes every request as the user
+    /// with the given ID, by passing the `as_user_id` parameter. The user can
+    /// also be identified by an alternate identifier such as [`UserId::sis`].
+    ///
+    /// The handle shares the connection pool, rate limit quota and credentials
+    /// of this client, and every `Ext` trait method can be called on it.
+    /// Requires the `Become other users` permission.
+    ///
+    /// # Example
+    /// ```rs
+    /// let notifications = client
+    ///     .as_user(1234)
+    ///     .get_global_notifications_for_account(AccountId::new(1), IncludePastNotifications::Exclude)
+    ///     .await?;
+    /// ```
+    ///
+    /// [See docs](https://canvas.instructure.com/doc/api/file.masquerading.html).
+    pub fn as_user(&self, user_id: impl Into<UserId>) -> CanvasClient {
+        CanvasClient {
+            as_user_id: Some(user_id.into()),
+            ..self.clone()
+        }
+    }
+
+    /// The ID of the user this client masquerades as, if any.
+    pub fn as_user_id(&self) -> Option<&UserId> {
+        self.as_user_id.as_ref()
+    }
+
+    /// Revokes the access token the client authorizes requests with.
+    ///
+    /// If the client is authenticated through OAuth2, the token is also
+    /// removed from its [`TokenStore`]. If `expire_sessions` is true, the web
+    /// sessions of the user are also ended.
+    ///
+    /// The client cannot make further requests until a new token is stored.
+    ///
+    /// [See docs](https://canvas.instructure.com/doc/api/file.oauth_endpoints.html#delete-login-oauth2-token).
+    pub async fn revoke_token(&self, expire_sessions: bool) -> Result<(), crate::Error> {
+        if let Some(oauth) = &self.oauth {
+            return oauth.revoke(expire_sessions).await;
+        }
+
+        let mut request = self
+            .http_client
+            .delete(format!("{}/login/oauth2/token", self.base_url()));
+        if expire_sessions {
+            request = request.query(&[("expire_sessions", 1)]);
+        }
+        self.execute(request).await?;
+
+        Ok(())
+    }
+
+    /// The url of the Canvas instance, without the `/api` suffix of the API url.
+    pub(crate) fn base_url(&self) -> &str {
+        let api_url = self.api_url.trim_end_matches('/');
+
+        api_url.strip_suffix("/api").unwrap_or(api_url)
+    }
+
+    #[cfg(any(
+        feature = "account_domains",
+        feature = "account_notifications",
+        feature = "account_reports",
+        feature = "accounts",
+        feature = "appointment_groups",
+        feature = "calendar",
+        feature = "calendar_events"
+    ))]
     pub(crate) fn make_query(&self, path: &str) -> RequestBuilder {
         self.http_client.get(format!("{}/{path}", self.api_url))
     }
 
+    #[cfg(any(
+        feature = "account_notifications",
+        feature = "appointment_groups",
+        feature = "calendar",
+        feature = "calendar_events"
+    ))]
     pub(crate) fn make_put(&self, path: &str) -> RequestBuilder {
         self.http_client.put(format!("{}/{path}", self.api_url))
     }
 
+    #[cfg(any(
+        feature = "account_notifications",
+        feature = "account_reports",
+        feature = "appointment_groups",
+        feature = "calendar_events"
+    ))]
     pub(crate) fn make_delete(&self, path: &str) -> RequestBuilder {
         self.http_client.delete(format!("{}/{path}", self.api_url))
     }
 
+    #[cfg(any(
+        feature = "account_notifications",
+        feature = "account_reports",
+        feature = "appointment_groups",
+        feature = "calendar_events"
+    ))]
     pub(crate) fn make_post(&self, path: &str) -> RequestBuilder {
         self.http_client.post(format!("{}/{path}", self.api_url))
     }
+
+    /// Makes a request to download a file from an absolute `url`, such as the
+    /// url of a [`File`](crate::file::File).
+    #[cfg(feature = "account_reports")]
+    pub(crate) fn make_download(&self, url: &str) -> RequestBuilder {
+        self.http_client.get(url)
+    }
+
+    /// Sends a request, returning an error if Canvas responded with an
+    /// unsuccessful status code.
+    ///
+    /// Failed requests are retried according to the [`RetryPolicy`] of the
+    /// client. If the client is authenticated through OAuth2, a request that
+    /// fails as unauthorized is retried once after refreshing the access token.
+    pub(crate) async fn execute(&self, request: RequestBuilder) -> Result<Response, crate::Error> {
+        let mut request = request.build()?;
+
+        if let Some(as_user_id) = &self.as_user_id {
+            // the `next` links of paginated endpoints already carry the parameter
+            let masquerading = request
+                .url()
+                .query_pairs()
+                .any(|(key, _)| key == "as_user_id");
+            if !masquerading {
+                request
+                    .url_mut()
+                    .query_pairs_mut()
+                    .append_pair("as_user_id", &as_user_id.to_string());
+            }
+        }
+
+        let Some(oauth) = &self.oauth else {
+            return self.execute_with_retries(request).await;
+        };
+
+        let token = oauth.token().await?;
+        request
+            .headers_mut()
+            .insert(AUTHORIZATION, token.authorization()?);
+
+        let Some(retry) = request.try_clone() else {
+            return self.execute_with_retries(request).await;
+        };
+        match self.execute_with_retries(request).await {
+            Err(crate::Error::Unauthorized { .. }) if token.refresh_token().is_some() => {
+                self.execute_with_refreshed_token(oauth, &token, retry)
+                    .await
+            }
+            result => result,
+        }
+    }
+
+    /// Refreshes the expired `token`, and retries `request` with the new token.
+    async fn execute_with_refreshed_token(
+        &self,
+        oauth: &OAuthSession,
+        token: &OAuthToken,
+        mut request: Request,
+    ) -> Result<Response, crate::Error> {
+        let token = oauth.refresh(token).await?;
+        request
+            .headers_mut()
+            .insert(AUTHORIZATION, token.authorization()?);
+
+        self.execute_with_retries(request).await
+    }
+
+    /// Sends a request, retrying it according to the [`RetryPolicy`] of the client.
+    async fn execute_with_retries(&self, request: Request) -> Result<Response, crate::Error> {
+        let method = request.method().clone();
+
+        let mut attempt = 1;
+        loop {
+            // requests with a streaming body cannot be cloned, and so are only attempted once
+            let Some(attempt_request) = request.try_clone() else {
+                return self.execute_once(request).await;
+            };
+
+            match self.execute_once(attempt_request).await {
+                Err(e) if self.retry_policy.should_retry(&method, attempt, &e) => {
+                    tokio::time::sleep(self.retry_policy.delay(attempt)).await;
+                    attempt += 1;
+                }
+                result => return result,
+            }
+        }
+    }
+
+    /// Sends a single attempt of a request.
+    async fn execute_once(&self, request: Request) -> Result<Response, crate::Error> {
+        let method = request.method().clone();
+
+        let response = match &self.cassette {
+            Some(cassette) if cassette.is_replaying() => cassette.replay_response(&request).await?,
+            Some(cassette) => match request.try_clone() {
+                Some(recorded_request) => {
+                    let response = self.send_request(request).await?;
+                    cassette
+                        .record_response(&recorded_request, response)
+                        .await?
+                }
+                None => self.send_request(request).await?,
+            },
+            None => self.send_request(request).await?,
+        };
+        if !response.status().is_success() {
+            return Err(crate::Error::from_response(method, response).await);
+        }
+
+        Ok(response)
+    }
+
+    /// Sends a request over the network, within the rate limit of the client.
+    async fn send_request(&self, request: Request) -> Result<Response, crate::Error> {
+        let response = match &self.rate_limiter {
+            Some(rate_limiter) => {
+                let permit = rate_limiter.acquire().await;
+                let response = self.http_client.execute(request).await?;
+                rate_limiter.record(permit, response.status(), response.headers());
+
+                response
+            }
+            None => self.http_client.execute(request).await?,
+        };
+
+        Ok(response)
+    }
+
+    /// Sends a request and deserializes the JSON body of the response.
+    #[cfg(any(
+        feature = "account_domains",
+        feature = "account_notifications",
+        feature = "account_reports",
+        feature = "accounts",
+        feature = "appointment_groups",
+        feature = "calendar",
+        feature = "calendar_events"
+    ))]
+    pub(crate) async fn send<T>(&self, request: RequestBuilder) -> Result<T, crate::Error>
+    where
+        T: serde::de::DeserializeOwned,
+    {
+        Ok(self.execute(request).await?.json().await?)
+    }
+
+    /// Returns a [`PageStream`] over the items of a paginated endpoint,
+    /// following the `next` links Canvas returns in the `Link` header.
+    ///
+    /// `items` extracts the list of items from a single deserialized page, as
+    /// some endpoints wrap their results in an object.
+    ///
+    /// The first page is only requested once the stream is first polled, and
+    /// each following page once the items of the previous page are consumed.
+    /// Collect the stream with [`TryStreamExt::try_collect`] to fetch every
+    /// page at once.
+    #[cfg(any(
+        feature = "account_notifications",
+        feature = "account_reports",
+        feature = "accounts",
+        feature = "appointment_groups",
+        feature = "calendar",
+        feature = "calendar_events"
+    ))]
+    pub(crate) fn paginate_stream<P, T>(
+        &self,
+        request: RequestBuilder,
+        items: fn(P) -> Vec<T>,
+    ) -> PageStream<'_, T>
+    where
+        P: serde::de::DeserializeOwned + Send + 'static,
+        T: Send + 'static,
+    {
+        let request = match self.per_page {
+            Some(per_page) => request.query(&[("per_page", per_page)]),
+            None => request,
+        };
+
+        stream::try_unfold(Some(request), move |request| async move {
+            let Some(request) = request else {
+                return Ok::<_, crate::Error>(None);
+            };
+
+            let response = self.execute(request).await?;
+            let links = PageLinks::from_headers(response.headers());
+            let page = items(response.json().await?);
+
+            // the next link already carries every query parameter of the original request
+            let next = links.next_page().map(|next| self.http_client.get(next));
+
+            Ok(Some((page, next)))
+        })
+        .map_ok(|page| stream::iter(page.into_iter().map(Ok)))
+        .try_flatten()
+        .boxed()
+    }
 }
 
 impl CanvasClientBuilder {
     /// Creates a new [`CanvasClientBuilder`] to configure a [`CanvasClient`].
     pub fn new(canvas_token: String) -> Self {
+        Self::with_credentials(Credentials::Token(canvas_token))
+    }
+
+    /// Creates a new [`CanvasClientBuilder`] to configure a [`CanvasClient`]
+    /// that authorizes requests with an OAuth2 token.
+    ///
+    /// The token is loaded from `token_store` for each request. Once the token
+    /// expires, or a request fails as unauthorized, it is refreshed through
+    /// `oauth_client` and the new token is saved back to `token_store`.
+    ///
+    /// Note that the API url is not derived from `oauth_client`, and should be
+    /// set with [`CanvasClientBuilder::set_api_url`].
+    pub fn new_oauth(oauth_client: OAuthClient, token_store: impl TokenStore + 'static) -> Self {
+        Self::with_credentials(Credentials::OAuth {
+            client: oauth_client,
+            store: Arc::new(token_store),
+        })
+    }
+
+    fn with_credentials(credentials: Credentials) -> Self {
         Self {
             config: CanvasClientConfig {
-                canvas_token,
+                credentials,
                 api_url: BASE_API_URL.to_string(),
+                #[cfg(any(
+                    feature = "account_notifications",
+                    feature = "account_reports",
+                    feature = "accounts",
+                    feature = "appointment_groups",
+                    feature = "calendar",
+                    feature = "calendar_events"
+                ))]
+                per_page: None,
+                rate_limit: Some(RateLimitConfig::default()),
+                retry_policy: RetryPolicy::default(),
+                cassette: None,
             },
         }
     }
@@ -68,21 +569,98 @@ impl CanvasClientBuilder {
         self
     }
 
+    /// Sets the number of items to request per page on paginated endpoints.
+    ///
+    /// Canvas defaults to 10 items per page if not specified, and may cap
+    /// this value (usually at 100) on a per-endpoint basis.
+    #[cfg(any(
+        feature = "account_notifications",
+        feature = "account_reports",
+        feature = "accounts",
+        feature = "appointment_groups",
+        feature = "calendar",
+        feature = "calendar_events"
+    ))]
+    pub fn set_per_page(mut self, per_page: u32) -> CanvasClientBuilder {
+        self.config.per_page = Some(per_page);
+
+        self
+    }
+
+    /// Sets how the client throttles itself to stay within the Canvas rate
+    /// limit, or disables rate limiting if `None`.
+    ///
+    /// Rate limiting is enabled with [`RateLimitConfig::default`] if not
+    /// specified. The quota is shared by every request made through the
+    /// client, including requests made concurrently from multiple tasks.
+    pub fn set_rate_limit(mut self, rate_limit: Option<RateLimitConfig>) -> CanvasClientBuilder {
+        self.config.rate_limit = rate_limit;
+
+        self
+    }
+
+    /// Sets which failed requests are retried, and how long to wait between
+    /// attempts.
+    ///
+    /// Defaults to [`RetryPolicy::default`] if not specified. Use
+    /// [`RetryPolicy::never`] to disable retries.
+    pub fn set_retry_policy(mut self, retry_policy: RetryPolicy) -> CanvasClientBuilder {
+        self.config.retry_policy = retry_policy;
+
+        self
+    }
+
+    /// Records every request made through the client to a cassette, or
+    /// replays them from one without touching the network.
+    ///
+    /// See [`Cassette`] for details.
+    pub fn set_cassette(mut self, cassette: Cassette) -> CanvasClientBuilder {
+        self.config.cassette = Some(cassette);
+
+        self
+    }
+
     /// Builds the [`CanvasClient`], returning an error if the client could not be built.
     pub fn build(self) -> Result<CanvasClient, crate::Error> {
         let mut default_client_headers = HeaderMap::new();
 
-        default_client_headers.insert(
-            AUTHORIZATION,
-            HeaderValue::from_str(&format!("Bearer {}", self.config.canvas_token))
-                .map_err(|e| crate::Error::CreatingHeader { header: e })?,
-        );
+        let oauth = match self.config.credentials {
+            Credentials::Token(canvas_token) => {
+                default_client_headers.insert(
+                    AUTHORIZATION,
+                    HeaderValue::from_str(&format!("Bearer {canvas_token}"))
+                        .map_err(|e| crate::Error::CreatingHeader { header: e })?,
+                );
+
+                None
+            }
+            Credentials::OAuth { client, store } => {
+                Some(Arc::new(OAuthSession::new(client, store)))
+            }
+        };
 
         Ok(CanvasClient {
             http_client: Client::builder()
                 .default_headers(default_client_headers)
                 .build()?,
             api_url: self.config.api_url,
+            #[cfg(any(
+                feature = "account_notifications",
+                feature = "account_reports",
+                feature = "accounts",
+                feature = "appointment_groups",
+                feature = "calendar",
+                feature = "calendar_events"
+            ))]
+            per_page: self.config.per_page,
+            rate_limiter: self
+                .config
+                .rate_limit
+                .map(|rate_limit| Arc::new(RateLimiter::new(rate_limit))),
+            retry_policy: self.config.retry_policy,
+            oauth,
+            as_user_id: None,
+            cassette: self.config.cassette.map(Arc::new),
         })
     }
 }
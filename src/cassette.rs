@@ -0,0 +1,594 @@
+//! Record and replay the HTTP traffic of a [`CanvasClient`](crate::CanvasClient).
+//!
+//! In record mode, every request made through the client and the response
+//! Canvas sent back is saved to a JSON cassette file. In replay mode, requests
+//! are answered from a previously recorded cassette without touching the
+//! network, which makes it possible to capture the behaviour of a real Canvas
+//! instance once and reuse it in offline regression tests.
+//!
+//! Access tokens are never recorded, and the values of personally identifying
+//! fields are replaced with [`REDACTED`] before the cassette is written.
+//!
+//! # Example
+//! ```rs
+//! // capture a session against a real instance
+//! let client = CanvasClient::builder(token)
+//!     .set_api_url("https://canvas.example.edu/api")
+//!     .set_cassette(Cassette::record("tests/cassettes/calendars.json"))
+//!     .build()?;
+//! client.all_calendars().await?;
+//!
+//! // and replay it later
+//! let client = CanvasClient::builder(String::new())
+//!     .set_cassette(Cassette::replay("tests/cassettes/calendars.json")?)
+//!     .build()?;
+//! client.all_calendars().await?;
+//! ```
+
+use std::path::{Path, PathBuf};
+
+use reqwest::{
+    header::{HeaderName, HeaderValue, CONTENT_TYPE},
+    Request, Response, ResponseBuilderExt,
+};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+/// The value scrubbed fields are replaced with.
+pub const REDACTED: &str = "[REDACTED]";
+
+/// The fields scrubbed from query strings, form bodies and JSON bodies by default.
+const DEFAULT_SCRUBBED_KEYS: &[&str] = &[
+    "access_token",
+    "refresh_token",
+    "client_secret",
+    "code",
+    "email",
+    "primary_email",
+    "login_id",
+    "sis_user_id",
+    "sis_login_id",
+    "integration_id",
+    "sortable_name",
+    "short_name",
+    "avatar_url",
+];
+
+/// The response headers that are never recorded, as they are either sensitive
+/// or no longer accurate once the body is scrubbed.
+const UNRECORDED_HEADERS: &[&str] = &["set-cookie", "x-session-id", "content-length"];
+
+/// A recording of the requests made through a [`CanvasClient`](crate::CanvasClient).
+///
+/// Attach a cassette to a client with
+/// [`CanvasClientBuilder::set_cassette`](crate::CanvasClientBuilder::set_cassette).
+pub struct Cassette {
+    /// Whether the cassette is being recorded or replayed.
+    mode: CassetteMode,
+    /// The file the cassette is saved to or loaded from.
+    path: PathBuf,
+    /// The names of the fields whose values are scrubbed.
+    scrubbed_keys: Vec<String>,
+    /// The recorded interactions. Held across writes to the cassette file so
+    /// that concurrent requests do not overwrite each other.
+    state: tokio::sync::Mutex<CassetteState>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum CassetteMode {
+    Record,
+    Replay,
+}
+
+#[derive(Default)]
+struct CassetteState {
+    interactions: Vec<Interaction>,
+    /// Whether each interaction has already been replayed.
+    replayed: Vec<bool>,
+}
+
+#[derive(Debug, Default, Serialize, Deserialize)]
+struct CassetteFile {
+    interactions: Vec<Interaction>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct Interaction {
+    request: RecordedRequest,
+    response: RecordedResponse,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct RecordedRequest {
+    method: String,
+    /// The path and query of the request, without the host, so that a
+    /// cassette can be replayed against any API url.
+    path: String,
+    /// The media type of the body, without parameters such as the charset.
+    /// `None` if the request had no `Content-Type` header, but never missing
+    /// from the cassette.
+    #[serde(deserialize_with = "Option::deserialize")]
+    content_type: Option<String>,
+    body: Option<RecordedBody>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct RecordedResponse {
+    status: u16,
+    headers: Vec<(String, String)>,
+    body: RecordedBody,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(untagged)]
+enum RecordedBody {
+    Text(String),
+    Binary(Vec<u8>),
+}
+
+impl Cassette {
+    /// Creates a cassette that records every interaction to `path`,
+    /// overwriting it if it exists.
+    ///
+    /// The file is rewritten after each interaction.
+    pub fn record(path: impl Into<PathBuf>) -> Self {
+        Self::new(CassetteMode::Record, path.into(), CassetteState::default())
+    }
+
+    /// Loads a previously recorded cassette from `path` to replay.
+    ///
+    /// Each request is answered with the first recorded interaction with the
+    /// same method, path, query and body that has not been replayed yet. A
+    /// request without a matching interaction fails with [`crate::Error::Cassette`].
+    ///
+    /// Bodies are compared according to their content type: form bodies field
+    /// by field, JSON bodies as JSON values (so the order of object keys does
+    /// not matter), and any other body byte for byte.
+    pub fn replay(path: impl Into<PathBuf>) -> Result<Self, crate::Error> {
+        let path = path.into();
+
+        let contents = std::fs::read_to_string(&path).map_err(|e| cassette_error(&path, e))?;
+        let file: CassetteFile =
+            serde_json::from_str(&contents).map_err(|e| cassette_error(&path, e))?;
+
+        let state = CassetteState {
+            replayed: vec![false; file.interactions.len()],
+            interactions: file.interactions,
+        };
+
+        Ok(Self::new(CassetteMode::Replay, path, state))
+    }
+
+    fn new(mode: CassetteMode, path: PathBuf, state: CassetteState) -> Self {
+        Self {
+            mode,
+            path,
+            scrubbed_keys: DEFAULT_SCRUBBED_KEYS
+                .iter()
+                .map(|key| key.to_string())
+                .collect(),
+            state: tokio::sync::Mutex::new(state),
+        }
+    }
+
+    /// Adds fields whose values are scrubbed from query strings, form bodies
+    /// and JSON bodies, in addition to the defaults (such as `email` and
+    /// `sis_user_id`).
+    ///
+    /// Keys are matched against the innermost name of a form field, so `email`
+    /// also scrubs `user[email]`.
+    pub fn add_scrubbed_keys<I, S>(mut self, keys: I) -> Self
+    where
+        I: IntoIterator<Item = S>,
+        S: Into<String>,
+    {
+        self.scrubbed_keys.extend(keys.into_iter().map(Into::into));
+
+        self
+    }
+
+    /// Whether requests are answered from the cassette instead of the network.
+    pub(crate) fn is_replaying(&self) -> bool {
+        self.mode == CassetteMode::Replay
+    }
+
+    /// Answers `request` with the matching recorded response.
+    pub(crate) async fn replay_response(
+        &self,
+        request: &Request,
+    ) -> Result<Response, crate::Error> {
+        let recorded = self.record_request(request);
+
+        let mut state = self.state.lock().await;
+        let CassetteState {
+            interactions,
+            replayed,
+        } = &mut *state;
+
+        let index = interactions
+            .iter()
+            .zip(replayed.iter())
+            .position(|(interaction, replayed)| !replayed && interaction.request.matches(&recorded))
+            .ok_or_else(|| crate::Error::Cassette {
+                message: format!(
+                    "no unplayed interaction in {} matches {} {}",
+                    self.path.display(),
+                    recorded.method,
+                    recorded.path
+                ),
+            })?;
+        replayed[index] = true;
+
+        interactions[index].response.to_response(request)
+    }
+
+    /// Records `response` as the answer to `request`, returning an equivalent
+    /// response as the body of the original is consumed.
+    pub(crate) async fn record_response(
+        &self,
+        request: &Request,
+        response: Response,
+    ) -> Result<Response, crate::Error> {
+        let status = response.status().as_u16();
+        let headers = response
+            .headers()
+            .iter()
+            .filter(|(name, _)| !UNRECORDED_HEADERS.contains(&name.as_str()))
+            .filter_map(|(name, value)| Some((name.to_string(), value.to_str().ok()?.to_string())))
+            .collect();
+        let body = response.bytes().await?.to_vec();
+
+        let body = match String::from_utf8(body) {
+            Ok(body) => RecordedBody::Text(body),
+            Err(e) => RecordedBody::Binary(e.into_bytes()),
+        };
+        let recorded = RecordedResponse {
+            status,
+            headers,
+            body,
+        };
+        let replayed = recorded.to_response(request)?;
+
+        let interaction = Interaction {
+            request: self.record_request(request),
+            response: self.scrub_response(recorded),
+        };
+
+        let mut state = self.state.lock().await;
+        state.interactions.push(interaction);
+
+        let file = CassetteFile {
+            interactions: state.interactions.clone(),
+        };
+        let contents =
+            serde_json::to_string_pretty(&file).map_err(|e| cassette_error(&self.path, e))?;
+        tokio::fs::write(&self.path, contents)
+            .await
+            .map_err(|e| cassette_error(&self.path, e))?;
+
+        Ok(replayed)
+    }
+
+    fn record_request(&self, request: &Request) -> RecordedRequest {
+        let url = request.url();
+        let mut path = url.path().to_string();
+        if url.query().is_some() {
+            let query = self.scrub_pairs(url::form_urlencoded::parse(
+                url.query().unwrap_or_default().as_bytes(),
+            ));
+            path = format!("{path}?{query}");
+        }
+
+        let content_type = request
+            .headers()
+            .get(CONTENT_TYPE)
+            .and_then(|content_type| content_type.to_str().ok())
+            .map(media_type);
+        let body = request
+            .body()
+            .and_then(|body| body.as_bytes())
+            .map(|body| self.record_body(content_type.as_deref(), body));
+
+        RecordedRequest {
+            method: request.method().to_string(),
+            path,
+            content_type,
+            body,
+        }
+    }
+
+    /// Records a request body, scrubbing form and JSON bodies.
+    fn record_body(&self, content_type: Option<&str>, body: &[u8]) -> RecordedBody {
+        match BodyKind::of(content_type) {
+            BodyKind::Form => {
+                return RecordedBody::Text(self.scrub_pairs(url::form_urlencoded::parse(body)))
+            }
+            BodyKind::Json => {
+                if let Ok(mut value) = serde_json::from_slice::<Value>(body) {
+                    self.scrub_value(&mut value);
+                    return RecordedBody::Text(value.to_string());
+                }
+            }
+            BodyKind::Raw => {}
+        }
+
+        match String::from_utf8(body.to_vec()) {
+            Ok(body) => RecordedBody::Text(body),
+            Err(e) => RecordedBody::Binary(e.into_bytes()),
+        }
+    }
+
+    /// Re-encodes form encoded pairs with the values of scrubbed keys replaced.
+    fn scrub_pairs<'a>(
+        &self,
+        pairs: impl Iterator<Item = (std::borrow::Cow<'a, str>, std::borrow::Cow<'a, str>)>,
+    ) -> String {
+        let mut serializer = url::form_urlencoded::Serializer::new(String::new());
+
+        for (key, value) in pairs {
+            if self.is_scrubbed(&key) {
+                serializer.append_pair(&key, REDACTED);
+            } else {
+                serializer.append_pair(&key, &value);
+            }
+        }
+
+        serializer.finish()
+    }
+
+    fn scrub_response(&self, mut response: RecordedResponse) -> RecordedResponse {
+        if let RecordedBody::Text(body) = &response.body {
+            if let Ok(mut value) = serde_json::from_str::<Value>(body) {
+                self.scrub_value(&mut value);
+                response.body = RecordedBody::Text(value.to_string());
+            }
+        }
+
+        response
+    }
+
+    fn scrub_value(&self, value: &mut Value) {
+        match value {
+            Value::Object(fields) => {
+                for (key, field) in fields {
+                    if self.is_scrubbed(key) && !field.is_null() {
+                        *field = Value::String(REDACTED.to_string());
+                    } else {
+                        self.scrub_value(field);
+                    }
+                }
+            }
+            Value::Array(items) => items.iter_mut().for_each(|item| self.scrub_value(item)),
+            _ => {}
+        }
+    }
+
+    /// Whether the value of `key` should be scrubbed. Form keys such as
+    /// `user[email]` are matched by their innermost name.
+    fn is_scrubbed(&self, key: &str) -> bool {
+        let name = key
+            .trim_end_matches("[]")
+            .rsplit('[')
+            .next()
+            .unwrap_or(key)
+            .trim_end_matches(']');
+
+        self.scrubbed_keys.iter().any(|scrubbed| scrubbed == name)
+    }
+}
+
+/// How the body of a request is compared when replaying.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum BodyKind {
+    Form,
+    Json,
+    Raw,
+}
+
+impl BodyKind {
+    fn of(content_type: Option<&str>) -> Self {
+        match content_type {
+            Some("application/x-www-form-urlencoded") => Self::Form,
+            Some(content_type)
+                if content_type == "application/json" || content_type.ends_with("+json") =>
+            {
+                Self::Json
+            }
+            _ => Self::Raw,
+        }
+    }
+}
+
+impl RecordedRequest {
+    /// Whether `request` is a replay of this recorded request.
+    fn matches(&self, request: &RecordedRequest) -> bool {
+        if self.method != request.method
+            || self.path != request.path
+            || self.content_type != request.content_type
+        {
+            return false;
+        }
+
+        let kind = BodyKind::of(request.content_type.as_deref());
+        match (&self.body, &request.body) {
+            (Some(recorded), Some(body)) => match kind {
+                BodyKind::Form => url::form_urlencoded::parse(recorded.as_bytes())
+                    .eq(url::form_urlencoded::parse(body.as_bytes())),
+                BodyKind::Json => {
+                    match (
+                        serde_json::from_slice::<Value>(recorded.as_bytes()),
+                        serde_json::from_slice::<Value>(body.as_bytes()),
+                    ) {
+                        (Ok(recorded_value), Ok(value)) => recorded_value == value,
+                        _ => recorded == body,
+                    }
+                }
+                BodyKind::Raw => recorded == body,
+            },
+            (recorded, body) => recorded == body,
+        }
+    }
+}
+
+impl RecordedBody {
+    fn as_bytes(&self) -> &[u8] {
+        match self {
+            Self::Text(body) => body.as_bytes(),
+            Self::Binary(body) => body,
+        }
+    }
+}
+
+impl RecordedResponse {
+    fn to_response(&self, request: &Request) -> Result<Response, crate::Error> {
+        let mut builder = http::Response::builder()
+            .status(self.status)
+            .url(request.url().clone());
+        for (name, value) in &self.headers {
+            let (Ok(name), Ok(value)) = (
+                HeaderName::from_bytes(name.as_bytes()),
+                HeaderValue::from_str(value),
+            ) else {
+                continue;
+            };
+            builder = builder.header(name, value);
+        }
+
+        let response =
+            builder
+                .body(self.body.as_bytes().to_vec())
+                .map_err(|e| crate::Error::Cassette {
+                    message: format!("invalid recorded response: {e}"),
+                })?;
+
+        Ok(Response::from(response))
+    }
+}
+
+/// The media type of a `Content-Type` header, without parameters such as the
+/// charset.
+fn media_type(content_type: &str) -> String {
+    content_type
+        .split(';')
+        .next()
+        .unwrap_or_default()
+        .trim()
+        .to_ascii_lowercase()
+}
+
+fn cassette_error(path: &Path, error: impl std::fmt::Display) -> crate::Error {
+    crate::Error::Cassette {
+        message: format!("{}: {error}", path.display()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use reqwest::{Client, Request};
+    use serde_json::json;
+
+    use super::*;
+
+    fn cassette() -> Cassette {
+        Cassette::new(
+            CassetteMode::Replay,
+            PathBuf::from("cassette.json"),
+            CassetteState::default(),
+        )
+    }
+
+    fn post(build: impl FnOnce(reqwest::RequestBuilder) -> reqwest::RequestBuilder) -> Request {
+        build(Client::new().post("https://canvas.example.edu/api/v1/things"))
+            .build()
+            .unwrap()
+    }
+
+    fn matches(recorded: &Request, replayed: &Request) -> bool {
+        let cassette = cassette();
+
+        cassette
+            .record_request(recorded)
+            .matches(&cassette.record_request(replayed))
+    }
+
+    #[test]
+    fn matches_form_bodies_field_by_field() {
+        let recorded = post(|request| request.form(&[("name", "a b"), ("roles[]", "1")]));
+        let encoded_differently = post(|request| {
+            request
+                .header(CONTENT_TYPE, "application/x-www-form-urlencoded")
+                .body("name=a%20b&roles%5B%5D=1")
+        });
+        let reordered = post(|request| request.form(&[("roles[]", "1"), ("name", "a b")]));
+
+        assert!(matches(&recorded, &encoded_differently));
+        assert!(!matches(&recorded, &reordered));
+    }
+
+    #[test]
+    fn matches_json_bodies_as_values() {
+        let recorded = post(|request| request.json(&json!({ "a": 1, "b": [true, null] })));
+        let reordered = post(|request| {
+            request
+                .header(CONTENT_TYPE, "application/json; charset=utf-8")
+                .body(r#"{ "b": [true, null], "a": 1 }"#)
+        });
+        let different = post(|request| request.json(&json!({ "a": 2, "b": [true, null] })));
+
+        assert!(matches(&recorded, &reordered));
+        assert!(!matches(&recorded, &different));
+    }
+
+    #[test]
+    fn matches_other_bodies_byte_for_byte() {
+        let body = |body: &'static [u8]| {
+            post(move |request| request.header(CONTENT_TYPE, "text/csv").body(body))
+        };
+
+        assert!(matches(&body(b"a,b\n1,2\n"), &body(b"a,b\n1,2\n")));
+        // the same fields, but not the same bytes
+        assert!(!matches(&body(b"a=1&b=2"), &body(b"b=2&a=1")));
+        assert!(!matches(&body(&[0xff, 0x00]), &body(&[0xff, 0x01])));
+    }
+
+    #[test]
+    fn does_not_match_bodies_of_another_content_type() {
+        let form = post(|request| request.form(&[("a", "1")]));
+        let raw = post(|request| request.header(CONTENT_TYPE, "text/plain").body("a=1"));
+
+        assert!(!matches(&form, &raw));
+    }
+
+    #[test]
+    fn scrubs_form_and_json_bodies() {
+        let cassette = cassette();
+
+        let form = cassette.record_request(&post(|request| {
+            request.form(&[("user[email]", "a@example.edu"), ("name", "A")])
+        }));
+        let json = cassette.record_request(&post(|request| {
+            request.json(&json!({ "user": { "email": "a@example.edu", "name": "A" } }))
+        }));
+
+        assert_eq!(
+            form.body,
+            Some(RecordedBody::Text(String::from(
+                "user%5Bemail%5D=%5BREDACTED%5D&name=A"
+            )))
+        );
+        let json: Value = serde_json::from_slice(json.body.unwrap().as_bytes()).unwrap();
+        assert_eq!(json, json!({ "user": { "email": REDACTED, "name": "A" } }));
+    }
+
+    #[test]
+    fn requires_content_types_of_recorded_requests() {
+        let request = json!({ "method": "GET", "path": "/api/v1/things", "body": null });
+
+        assert!(serde_json::from_value::<RecordedRequest>(request.clone()).is_err());
+
+        let mut request = request;
+        request["content_type"] = Value::Null;
+        let recorded: RecordedRequest = serde_json::from_value(request).unwrap();
+        assert_eq!(recorded.content_type, None);
+    }
+}
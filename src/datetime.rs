@@ -0,0 +1,262 @@
+//! The date/time type timestamps are parsed into.
+//!
+//! Canvas sends and accepts timestamps in ISO 8601 format, such as
+//! `2013-08-28T23:59:00-06:00`. With the `time` feature (the default), they
+//! are represented by [`time::OffsetDateTime`]. With the `chrono` feature, they
+//! are represented by [`chrono::DateTime<FixedOffset>`](chrono::DateTime).
+//!
+//! At least one of the two features must be enabled. As [`DateTime`] can only
+//! be one of the types, `chrono` is used if both are, so that enabling it
+//! alongside the default features is enough:
+//!
+//! ```toml
+//! canvas-api = { version = "0.1", features = ["chrono"] }
+//! ```
+//!
+//! Disable the default features to leave out the `time` dependency.
+
+#[cfg(not(any(feature = "time", feature = "chrono")))]
+compile_error!("either the `time` or the `chrono` feature must be enabled");
+
+use serde::{de, ser, Deserialize, Deserializer, Serializer};
+
+/// A timestamp with a UTC offset.
+#[cfg(feature = "chrono")]
+pub type DateTime = chrono::DateTime<chrono::FixedOffset>;
+
+/// A timestamp with a UTC offset.
+#[cfg(all(feature = "time", not(feature = "chrono")))]
+pub type DateTime = time::OffsetDateTime;
+
+/// Formats a timestamp as RFC 3339, the ISO 8601 profile Canvas uses.
+///
+/// RFC 3339 offsets have no seconds, so a timestamp whose offset does is
+/// formatted in UTC instead. Fails if the year is not between 0 and 9999.
+#[cfg(feature = "chrono")]
+pub(crate) fn format(datetime: &DateTime) -> Result<String, String> {
+    use chrono::{Datelike, Offset, Utc};
+
+    let datetime = if datetime.offset().local_minus_utc() % 60 == 0 {
+        *datetime
+    } else {
+        datetime.with_timezone(&Utc.fix())
+    };
+    if !(0..=9999).contains(&datetime.year()) {
+        return Err(format!("{datetime} is not representable as RFC 3339"));
+    }
+
+    Ok(datetime.to_rfc3339())
+}
+
+/// Formats a timestamp as RFC 3339, the ISO 8601 profile Canvas uses.
+///
+/// RFC 3339 offsets have no seconds, so a timestamp whose offset does is
+/// formatted in UTC instead. Fails if the year is not between 0 and 9999.
+#[cfg(all(feature = "time", not(feature = "chrono")))]
+pub(crate) fn format(datetime: &DateTime) -> Result<String, String> {
+    let datetime = if datetime.offset().seconds_past_minute() == 0 {
+        *datetime
+    } else {
+        datetime.to_offset(time::UtcOffset::UTC)
+    };
+
+    datetime
+        .format(&time::format_description::well_known::Rfc3339)
+        .map_err(|e| format!("{datetime} is not representable as RFC 3339: {e}"))
+}
+
+/// Parses an RFC 3339 timestamp.
+#[cfg(feature = "chrono")]
+pub(crate) fn parse(s: &str) -> Result<DateTime, String> {
+    DateTime::parse_from_rfc3339(s).map_err(|e| e.to_string())
+}
+
+/// Parses an RFC 3339 timestamp.
+#[cfg(all(feature = "time", not(feature = "chrono")))]
+pub(crate) fn parse(s: &str) -> Result<DateTime, String> {
+    DateTime::parse(s, &time::format_description::well_known::Rfc3339).map_err(|e| e.to_string())
+}
+
+/// The current time, in UTC.
+#[cfg(any(
+    feature = "account_notifications",
+    feature = "account_reports",
+    feature = "ical"
+))]
+#[cfg(feature = "chrono")]
+pub(crate) fn now() -> DateTime {
+    chrono::DateTime::<chrono::Utc>::from(std::time::SystemTime::now()).fixed_offset()
+}
+
+/// The current time, in UTC.
+#[cfg(any(
+    feature = "account_notifications",
+    feature = "account_reports",
+    feature = "ical"
+))]
+#[cfg(all(feature = "time", not(feature = "chrono")))]
+pub(crate) fn now() -> DateTime {
+    DateTime::now_utc()
+}
+
+/// The number of whole seconds from `start` to `end`, negative if `end` is
+/// before `start`.
+#[cfg(any(feature = "account_reports", feature = "appointment_groups"))]
+#[cfg(feature = "chrono")]
+pub(crate) fn seconds_between(start: &DateTime, end: &DateTime) -> i64 {
+    (*end - *start).num_seconds()
+}
+
+/// The number of whole seconds from `start` to `end`, negative if `end` is
+/// before `start`.
+#[cfg(any(feature = "account_reports", feature = "appointment_groups"))]
+#[cfg(all(feature = "time", not(feature = "chrono")))]
+pub(crate) fn seconds_between(start: &DateTime, end: &DateTime) -> i64 {
+    (*end - *start).whole_seconds()
+}
+
+/// The timestamp `seconds` after `datetime`, or before it if negative.
+#[cfg(any(test, feature = "appointment_groups"))]
+#[cfg(feature = "chrono")]
+pub(crate) fn add_seconds(datetime: &DateTime, seconds: i64) -> DateTime {
+    *datetime + chrono::Duration::seconds(seconds)
+}
+
+/// The timestamp `seconds` after `datetime`, or before it if negative.
+#[cfg(any(test, feature = "appointment_groups"))]
+#[cfg(all(feature = "time", not(feature = "chrono")))]
+pub(crate) fn add_seconds(datetime: &DateTime, seconds: i64) -> DateTime {
+    *datetime + time::Duration::seconds(seconds)
+}
+
+/// The number of seconds since the Unix epoch.
+#[cfg(all(feature = "ical", feature = "chrono"))]
+pub(crate) fn unix_timestamp(datetime: &DateTime) -> i64 {
+    datetime.timestamp()
+}
+
+/// The number of seconds since the Unix epoch.
+#[cfg(all(feature = "ical", feature = "time", not(feature = "chrono")))]
+pub(crate) fn unix_timestamp(datetime: &DateTime) -> i64 {
+    datetime.unix_timestamp()
+}
+
+/// The timestamp `seconds` after the Unix epoch, in UTC. `None` if it is out
+/// of range.
+#[cfg(all(feature = "ical", feature = "chrono"))]
+pub(crate) fn from_unix_timestamp(seconds: i64) -> Option<DateTime> {
+    chrono::DateTime::from_timestamp(seconds, 0).map(|datetime| datetime.fixed_offset())
+}
+
+/// The timestamp `seconds` after the Unix epoch, in UTC. `None` if it is out
+/// of range.
+#[cfg(all(feature = "ical", feature = "time", not(feature = "chrono")))]
+pub(crate) fn from_unix_timestamp(seconds: i64) -> Option<DateTime> {
+    DateTime::from_unix_timestamp(seconds).ok()
+}
+
+/// Serializes a [`DateTime`] field as an RFC 3339 string, for use with
+/// `#[serde(with = "crate::datetime::rfc3339")]`.
+///
+/// Form bodies and query strings are encoded with the same format.
+pub(crate) mod rfc3339 {
+    use super::*;
+
+    pub(crate) fn serialize<S>(datetime: &DateTime, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        serializer.serialize_str(&format(datetime).map_err(ser::Error::custom)?)
+    }
+
+    pub(crate) fn deserialize<'de, D>(deserializer: D) -> Result<DateTime, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        let s = String::deserialize(deserializer)?;
+
+        parse(&s).map_err(de::Error::custom)
+    }
+
+    /// The same as [`rfc3339`](super::rfc3339), for `Option<DateTime>` fields.
+    #[cfg(any(
+        feature = "account_notifications",
+        feature = "account_reports",
+        feature = "appointment_groups",
+        feature = "calendar_events"
+    ))]
+    pub(crate) mod option {
+        use super::*;
+
+        pub(crate) fn serialize<S>(
+            datetime: &Option<DateTime>,
+            serializer: S,
+        ) -> Result<S::Ok, S::Error>
+        where
+            S: Serializer,
+        {
+            match datetime {
+                Some(datetime) => {
+                    serializer.serialize_some(&format(datetime).map_err(ser::Error::custom)?)
+                }
+                None => serializer.serialize_none(),
+            }
+        }
+
+        #[cfg(any(
+            feature = "account_reports",
+            feature = "appointment_groups",
+            feature = "calendar_events"
+        ))]
+        pub(crate) fn deserialize<'de, D>(deserializer: D) -> Result<Option<DateTime>, D::Error>
+        where
+            D: Deserializer<'de>,
+        {
+            Option::<String>::deserialize(deserializer)?
+                .map(|s| parse(&s).map_err(de::Error::custom))
+                .transpose()
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[cfg(feature = "chrono")]
+    fn with_offset(s: &str, offset_seconds: i32) -> DateTime {
+        parse(s)
+            .unwrap()
+            .with_timezone(&chrono::FixedOffset::east_opt(offset_seconds).unwrap())
+    }
+
+    #[cfg(all(feature = "time", not(feature = "chrono")))]
+    fn with_offset(s: &str, offset_seconds: i32) -> DateTime {
+        parse(s)
+            .unwrap()
+            .to_offset(time::UtcOffset::from_whole_seconds(offset_seconds).unwrap())
+    }
+
+    #[test]
+    fn formats_offsets_in_whole_minutes() {
+        let datetime = with_offset("2024-06-01T12:00:00Z", -6 * 3600);
+
+        assert_eq!(format(&datetime).unwrap(), "2024-06-01T06:00:00-06:00");
+    }
+
+    #[test]
+    fn formats_offsets_with_seconds_in_utc() {
+        let datetime = with_offset("2024-06-01T12:00:00Z", 3600 + 30);
+
+        let formatted = format(&datetime).unwrap();
+        assert_eq!(parse(&formatted).unwrap(), datetime);
+        assert!(formatted.ends_with('Z') || formatted.ends_with("+00:00"));
+    }
+
+    #[test]
+    fn rejects_years_outside_rfc3339() {
+        let datetime = add_seconds(&parse("0000-01-01T00:00:00Z").unwrap(), -1);
+
+        assert!(format(&datetime).is_err());
+    }
+}
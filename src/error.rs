@@ -1,11 +1,364 @@
-use reqwest::header::InvalidHeaderValue;
+use std::{collections::HashMap, fmt};
+
+use reqwest::{header::InvalidHeaderValue, Method, Response, StatusCode};
+use serde_json::Value;
 use thiserror::Error;
 
 #[derive(Error, Debug)]
+#[non_exhaustive]
 pub enum Error {
     #[error("Failed to create a header for an http request {header}")]
     CreatingHeader { header: InvalidHeaderValue },
 
+    /// The access token was missing, invalid or expired (`401`).
+    #[error("Unauthorized: {response}")]
+    Unauthorized { response: Box<ApiErrorResponse> },
+
+    /// The user is not permitted to perform the request (`403`).
+    #[error("Forbidden: {response}")]
+    Forbidden { response: Box<ApiErrorResponse> },
+
+    /// The requested resource does not exist or is not visible to the user
+    /// (`404`).
+    #[error("Not found: {response}")]
+    NotFound { response: Box<ApiErrorResponse> },
+
+    /// Canvas rejected the parameters of the request (`422`).
+    ///
+    /// `errors` maps each rejected field to its error messages.
+    #[error("Unprocessable entity: {response}")]
+    Unprocessable {
+        errors: HashMap<String, Vec<String>>,
+        response: Box<ApiErrorResponse>,
+    },
+
+    /// The request was throttled because the rate limit quota was exhausted
+    /// (`403 Rate Limit Exceeded`, or `429`).
+    #[error("Throttled: {response}")]
+    Throttled { response: Box<ApiErrorResponse> },
+
+    /// Any other unsuccessful response from Canvas.
+    #[error("Request failed: {response}")]
+    Api { response: Box<ApiErrorResponse> },
+
+    /// The client is authenticated through OAuth2, but the [`TokenStore`](crate::oauth::TokenStore)
+    /// holds no token.
+    #[error("No OAuth2 token is stored; complete the authorization code flow first")]
+    MissingToken,
+
+    /// The OAuth2 access token expired, but no refresh token was issued with it.
+    #[error("The OAuth2 access token cannot be refreshed without a refresh token")]
+    MissingRefreshToken,
+
+    /// A [`TokenStore`](crate::oauth::TokenStore) failed to load or store a token.
+    #[error("Failed to access the OAuth2 token store: {source}")]
+    TokenStore {
+        source: Box<dyn std::error::Error + Send + Sync>,
+    },
+
+    /// A request could not be recorded to or replayed from a
+    /// [`Cassette`](crate::cassette::Cassette).
+    #[error("Cassette error: {message}")]
+    Cassette { message: String },
+
+    /// The runtime of a [`blocking::CanvasClient`](crate::blocking::CanvasClient)
+    /// could not be started.
+    #[cfg(feature = "blocking")]
+    #[error("Failed to start the blocking client runtime: {source}")]
+    Runtime { source: std::io::Error },
+
+    /// A report finished with the `error` status, or was aborted or deleted
+    /// before it completed.
+    #[cfg(feature = "account_reports")]
+    #[error("Report {} of type {} finished with status {:?}", report.id(), report.report(), report.status())]
+    ReportFailed {
+        report: Box<crate::extensions::account_reports::Report>,
+    },
+
+    /// A report did not finish before the polling timeout elapsed.
+    #[cfg(feature = "account_reports")]
+    #[error("Report {} of type {} did not finish in time (progress {}%)", report.id(), report.report(), report.progress())]
+    ReportTimedOut {
+        report: Box<crate::extensions::account_reports::Report>,
+    },
+
+    /// A completed report has no file to download.
+    #[cfg(feature = "account_reports")]
+    #[error("Report {} of type {} has no file to download", report.id(), report.report())]
+    MissingReportFile {
+        report: Box<crate::extensions::account_reports::Report>,
+    },
+
+    /// A downloaded report could not be unpacked.
+    #[cfg(feature = "account_reports")]
+    #[error("Failed to unpack the report archive: {source}")]
+    ReportArchive { source: zip::result::ZipError },
+
+    /// A report did not produce the CSV file rows were requested from.
+    #[cfg(feature = "account_reports")]
+    #[error("The report did not produce {file_name}")]
+    MissingReportCsv { file_name: String },
+
+    /// The parameters of a report were rejected by
+    /// [`ReportResponse::validate`](crate::extensions::account_reports::ReportResponse::validate).
+    #[cfg(feature = "account_reports")]
+    #[error(
+        "Invalid parameters for report {report_type}: {}",
+        problems.iter().map(ToString::to_string).collect::<Vec<_>>().join(", ")
+    )]
+    InvalidReportParameters {
+        report_type: String,
+        problems: Vec<crate::extensions::account_reports::parameters::ReportParameterProblem>,
+    },
+
+    /// A row of a report CSV file could not be parsed.
+    #[cfg(feature = "account_reports")]
+    #[error("Failed to parse a report CSV file: {source}")]
+    Csv { source: csv::Error },
+
+    /// An account notification does not end after it starts.
+    #[cfg(feature = "account_notifications")]
+    #[error("The notification ends at {end_at}, which is not after it starts at {start_at}")]
+    InvalidNotificationWindow {
+        start_at: crate::DateTime,
+        end_at: crate::DateTime,
+    },
+
+    /// An account notification has no ID, as it was not returned by Canvas.
+    #[cfg(feature = "account_notifications")]
+    #[error("The notification \"{subject}\" has no ID, so it has not been created in Canvas")]
+    UnsavedNotification { subject: String },
+
+    /// Time slots cannot be split into the given length, which must be a
+    /// whole number of seconds.
+    #[cfg(feature = "appointment_groups")]
+    #[error("Time slots must last a whole, non-zero number of seconds, not {length:?}")]
+    InvalidTimeSlotLength { length: std::time::Duration },
+
+    /// The body of a request could not be encoded as a form.
+    #[error("Failed to encode a form body: {source}")]
+    EncodingForm { source: serde_json::Error },
+
+    /// A notification sync could not read its desired state or state file, or
+    /// the notifications it manages are ambiguous.
+    #[cfg(feature = "notification_sync")]
+    #[error("Notification sync error: {message}")]
+    NotificationSync { message: String },
+
+    /// An iCalendar file could not be parsed.
+    #[cfg(feature = "ical")]
+    #[error("Invalid iCalendar data on line {line}: {message}")]
+    Ics { line: usize, message: String },
+
+    /// Calendar events could not be exported as an iCalendar file.
+    #[cfg(feature = "ical")]
+    #[error("Cannot export iCalendar data: {message}")]
+    IcsExport { message: String },
+
+    /// Reading or writing a local file failed.
+    #[error("IO error: {source}")]
+    Io { source: std::io::Error },
+
+    /// A string could not be parsed as a Canvas ID.
+    #[error("Invalid Canvas ID {id:?}")]
+    InvalidId { id: String },
+
+    #[error("Failed to build a url: {source}")]
+    InvalidUrl { source: url::ParseError },
+
     #[error(transparent)]
     Reqwest(#[from] reqwest::Error),
 }
+
+impl Error {
+    /// The response Canvas returned, if this error was caused by an unsuccessful
+    /// status code.
+    pub fn response(&self) -> Option<&ApiErrorResponse> {
+        match self {
+            Self::Unauthorized { response }
+            | Self::Forbidden { response }
+            | Self::NotFound { response }
+            | Self::Unprocessable { response, .. }
+            | Self::Throttled { response }
+            | Self::Api { response } => Some(response),
+            Self::CreatingHeader { .. }
+            | Self::MissingToken
+            | Self::MissingRefreshToken
+            | Self::TokenStore { .. }
+            | Self::Cassette { .. }
+            | Self::InvalidId { .. }
+            | Self::EncodingForm { .. }
+            | Self::Io { .. }
+            | Self::InvalidUrl { .. }
+            | Self::Reqwest(_) => None,
+            #[cfg(feature = "blocking")]
+            Self::Runtime { .. } => None,
+            #[cfg(feature = "account_notifications")]
+            Self::InvalidNotificationWindow { .. } | Self::UnsavedNotification { .. } => None,
+            #[cfg(feature = "appointment_groups")]
+            Self::InvalidTimeSlotLength { .. } => None,
+            #[cfg(feature = "notification_sync")]
+            Self::NotificationSync { .. } => None,
+            #[cfg(feature = "ical")]
+            Self::Ics { .. } | Self::IcsExport { .. } => None,
+            #[cfg(feature = "account_reports")]
+            Self::ReportFailed { .. }
+            | Self::ReportTimedOut { .. }
+            | Self::MissingReportFile { .. }
+            | Self::ReportArchive { .. }
+            | Self::MissingReportCsv { .. }
+            | Self::InvalidReportParameters { .. }
+            | Self::Csv { .. } => None,
+        }
+    }
+
+    /// Builds the error for an unsuccessful response to a `method` request,
+    /// consuming its body.
+    pub(crate) async fn from_response(method: Method, response: Response) -> Self {
+        let status = response.status();
+        let path = response.url().path().to_string();
+        let body = match response.text().await {
+            Ok(body) => body,
+            Err(e) => return Self::Reqwest(e),
+        };
+
+        let (messages, errors) = parse_error_body(&body);
+        let response = Box::new(ApiErrorResponse {
+            method,
+            status,
+            path,
+            body,
+            messages,
+        });
+
+        match status {
+            StatusCode::UNAUTHORIZED => Self::Unauthorized { response },
+            StatusCode::TOO_MANY_REQUESTS => Self::Throttled { response },
+            // Canvas reports an exhausted rate limit quota as a 403
+            StatusCode::FORBIDDEN if response.body.contains("Rate Limit Exceeded") => {
+                Self::Throttled { response }
+            }
+            StatusCode::FORBIDDEN => Self::Forbidden { response },
+            StatusCode::NOT_FOUND => Self::NotFound { response },
+            StatusCode::UNPROCESSABLE_ENTITY => Self::Unprocessable { errors, response },
+            _ => Self::Api { response },
+        }
+    }
+}
+
+/// The details of an unsuccessful response from Canvas.
+#[derive(Debug)]
+pub struct ApiErrorResponse {
+    /// The method of the failed request.
+    method: Method,
+    /// The status code Canvas responded with.
+    status: StatusCode,
+    /// The path of the failed request.
+    path: String,
+    /// The raw body of the response.
+    body: String,
+    /// The error messages parsed from the body.
+    messages: Vec<String>,
+}
+
+impl ApiErrorResponse {
+    /// The method of the failed request.
+    pub fn method(&self) -> &Method {
+        &self.method
+    }
+
+    /// The status code Canvas responded with.
+    pub fn status(&self) -> StatusCode {
+        self.status
+    }
+
+    /// The path of the failed request.
+    pub fn path(&self) -> &str {
+        &self.path
+    }
+
+    /// The raw body of the response.
+    pub fn body(&self) -> &str {
+        &self.body
+    }
+
+    /// The error messages parsed from the body. Messages about a specific
+    /// field are prefixed with the field name, e.g. `name: can't be blank`.
+    ///
+    /// Empty if the body was not in one of the formats Canvas uses for errors.
+    pub fn messages(&self) -> &[String] {
+        &self.messages
+    }
+}
+
+impl fmt::Display for ApiErrorResponse {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} {} returned {}", self.method, self.path, self.status)?;
+
+        if !self.messages.is_empty() {
+            write!(f, ": {}", self.messages.join("; "))?;
+        }
+
+        Ok(())
+    }
+}
+
+/// Parses the error messages out of a Canvas error body.
+///
+/// Canvas uses a few different formats for errors:
+/// - `{"errors":[{"message":"..."}]}` for general errors,
+/// - `{"errors":{"field":[{"message":"..."}]}}` for validation errors,
+/// - `{"message":"..."}` for some authentication errors,
+/// - `{"error":"...","error_description":"..."}` for OAuth2 errors.
+///
+/// Returns every message, and the messages of each field.
+fn parse_error_body(body: &str) -> (Vec<String>, HashMap<String, Vec<String>>) {
+    let mut messages = Vec::new();
+    let mut errors = HashMap::new();
+
+    let Ok(value) = serde_json::from_str::<Value>(body) else {
+        return (messages, errors);
+    };
+
+    for key in ["message", "error_description"] {
+        if let Some(message) = value.get(key).and_then(Value::as_str) {
+            messages.push(message.to_string());
+        }
+    }
+
+    match value.get("errors") {
+        Some(Value::Array(items)) => messages.extend(items.iter().filter_map(error_message)),
+        Some(Value::Object(fields)) => {
+            for (field, items) in fields {
+                let field_messages: Vec<String> = match items {
+                    Value::Array(items) => items.iter().filter_map(error_message).collect(),
+                    item => error_message(item).into_iter().collect(),
+                };
+
+                messages.extend(
+                    field_messages
+                        .iter()
+                        .map(|message| format!("{field}: {message}")),
+                );
+                errors.insert(field.clone(), field_messages);
+            }
+        }
+        Some(item) => messages.extend(error_message(item)),
+        None => {}
+    }
+
+    (messages, errors)
+}
+
+/// Extracts the message of a single error, which is either a string or an
+/// object with a `message` field.
+fn error_message(value: &Value) -> Option<String> {
+    match value {
+        Value::String(message) => Some(message.clone()),
+        Value::Object(error) => error
+            .get("message")
+            .and_then(Value::as_str)
+            .map(str::to_string),
+        _ => None,
+    }
+}
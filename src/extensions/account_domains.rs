@@ -48,12 +48,12 @@ impl<'a> AccountDomainSearch<'a> {
     pub async fn search(self) -> Result<Vec<AccountDomain>, crate::Error> {
         let res: Vec<AccountDomain> = self
             .client
-            .make_query("v1/accounts/search")
-            .query(&[("domain", self.domain)])
-            .query(&[("name", self.name)])
-            .send()
-            .await?
-            .json()
+            .send(
+                self.client
+                    .make_query("v1/accounts/search")
+                    .query(&[("domain", self.domain)])
+                    .query(&[("name", self.name)]),
+            )
             .await?;
 
         Ok(res)
@@ -75,11 +75,11 @@ pub trait AccountDomainsExt {
     ///     .search()
     ///     .await?;
     /// ```
-    fn search_account_domains(&self) -> AccountDomainSearch;
+    fn search_account_domains(&self) -> AccountDomainSearch<'_>;
 }
 
 impl AccountDomainsExt for CanvasClient {
-    fn search_account_domains(&self) -> AccountDomainSearch {
+    fn search_account_domains(&self) -> AccountDomainSearch<'_> {
         AccountDomainSearch {
             client: self,
             domain: None,
@@ -1,10 +1,22 @@
+use std::collections::HashSet;
+
 use async_trait::async_trait;
+use futures::TryStreamExt;
 use serde::{Deserialize, Serialize};
 
-use crate::CanvasClient;
+use crate::{
+    form::NestedForm, AccountId, AccountsExt, CanvasClient, DateTime, NotificationId, PageStream,
+    RoleId,
+};
+
+#[cfg(feature = "notification_sync")]
+pub mod sync;
 
-#[derive(Debug, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct AccountNotification {
+    /// The ID of the notification, or [`None`] if it has not been created.
+    #[serde(default, skip_serializing)]
+    id: Option<NotificationId>,
     /// The subject of the notifications
     #[serde(rename(serialize = "account_notification[subject]"))]
     subject: String,
@@ -14,13 +26,19 @@ pub struct AccountNotification {
     ///  When to send out the notification.
     ///
     /// For example, `2013-08-28T23:59:00-06:00`
-    #[serde(rename(serialize = "account_notification[start_at]"))]
-    start_at: String,
+    #[serde(
+        rename(serialize = "account_notification[start_at]"),
+        with = "crate::datetime::rfc3339"
+    )]
+    start_at: DateTime,
     /// When to expire the notification.
     ///
     /// For example, `2013-08-29T23:59:00-06:00`
-    #[serde(rename(serialize = "account_notification[end_at]"))]
-    end_at: String,
+    #[serde(
+        rename(serialize = "account_notification[end_at]"),
+        with = "crate::datetime::rfc3339"
+    )]
+    end_at: DateTime,
     /// The icon to display with the message.
     ///
     /// Defaults to `warning`.
@@ -29,11 +47,19 @@ pub struct AccountNotification {
     /// The roles to send the notification to.
     ///
     /// If [`None`], defaults to all roles.
-    #[serde(skip_serializing)]
-    role_ids: Option<Vec<u64>>,
+    #[serde(
+        rename(serialize = "account_notification_roles", deserialize = "role_ids"),
+        default,
+        skip_serializing_if = "Option::is_none"
+    )]
+    role_ids: Option<Vec<RoleId>>,
+    /// Whether the current user has closed the notification. Only returned
+    /// when listing with [`NotificationQuery::set_show_is_closed`].
+    #[serde(default, skip_serializing)]
+    closed: Option<bool>,
 }
 
-#[derive(Debug, Serialize, Deserialize)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
 #[serde(rename_all = "lowercase")]
 pub enum NotificationIcon {
     Warning,
@@ -44,6 +70,21 @@ pub enum NotificationIcon {
 }
 
 impl AccountNotification {
+    /// Creates a new [`AccountNotificationBuilder`] for a notification shown
+    /// from `start_at` until `end_at`.
+    pub fn builder(
+        subject: impl Into<String>,
+        message: impl Into<String>,
+        start_at: DateTime,
+        end_at: DateTime,
+    ) -> AccountNotificationBuilder {
+        AccountNotificationBuilder::new(subject, message, start_at, end_at)
+    }
+
+    /// The ID of the notification, or [`None`] if it has not been created.
+    pub fn id(&self) -> Option<&NotificationId> {
+        self.id.as_ref()
+    }
     /// The subject of the notifications
     pub fn subject(&self) -> &str {
         &self.subject
@@ -55,14 +96,14 @@ impl AccountNotification {
     ///  When to send out the notification.
     ///
     /// For example, `2013-08-28T23:59:00-06:00`
-    pub fn start_at(&self) -> &str {
-        &self.start_at
+    pub fn start_at(&self) -> DateTime {
+        self.start_at
     }
     /// When to expire the notification.
     ///
     /// For example, `2013-08-29T23:59:00-06:00`
-    pub fn end_at(&self) -> &str {
-        &self.end_at
+    pub fn end_at(&self) -> DateTime {
+        self.end_at
     }
     /// The icon to display with the message.
     ///
@@ -73,9 +114,75 @@ impl AccountNotification {
     /// The roles to send the notification to.
     ///
     /// If [`None`], defaults to all roles.
-    pub fn role_ids(&self) -> Option<&Vec<u64>> {
+    pub fn role_ids(&self) -> Option<&Vec<RoleId>> {
         self.role_ids.as_ref()
     }
+    /// Whether the current user has closed the notification. Only returned
+    /// when listing with [`NotificationQuery::set_show_is_closed`].
+    pub fn closed(&self) -> Option<bool> {
+        self.closed
+    }
+}
+
+/// Configures an [`AccountNotification`] to create or update with
+/// [`AccountNotificationsExt`].
+#[derive(Debug, Clone)]
+pub struct AccountNotificationBuilder {
+    notification: AccountNotification,
+}
+
+impl AccountNotificationBuilder {
+    /// Creates a new [`AccountNotificationBuilder`] for a notification shown
+    /// from `start_at` until `end_at`.
+    pub fn new(
+        subject: impl Into<String>,
+        message: impl Into<String>,
+        start_at: DateTime,
+        end_at: DateTime,
+    ) -> Self {
+        Self {
+            notification: AccountNotification {
+                id: None,
+                subject: subject.into(),
+                message: message.into(),
+                start_at,
+                end_at,
+                icon: NotificationIcon::Warning,
+                role_ids: None,
+                closed: None,
+            },
+        }
+    }
+
+    /// Sets the icon to display with the message. Defaults to
+    /// [`NotificationIcon::Warning`].
+    pub fn set_icon(mut self, icon: NotificationIcon) -> Self {
+        self.notification.icon = icon;
+
+        self
+    }
+
+    /// Sets the roles to send the notification to. Defaults to all roles.
+    pub fn set_role_ids(mut self, role_ids: Option<Vec<RoleId>>) -> Self {
+        self.notification.role_ids = role_ids;
+
+        self
+    }
+
+    /// Builds the [`AccountNotification`].
+    ///
+    /// Fails with [`crate::Error::InvalidNotificationWindow`] if the
+    /// notification does not end after it starts.
+    pub fn build(self) -> Result<AccountNotification, crate::Error> {
+        let AccountNotification {
+            start_at, end_at, ..
+        } = self.notification;
+        if end_at <= start_at {
+            return Err(crate::Error::InvalidNotificationWindow { start_at, end_at });
+        }
+
+        Ok(self.notification)
+    }
 }
 
 pub enum IncludePastNotifications {
@@ -95,6 +202,84 @@ impl Serialize for IncludePastNotifications {
     }
 }
 
+/// Which notifications [`AccountNotificationsExt::get_all_notifications_for_account`]
+/// lists.
+#[derive(Debug, Clone, Serialize)]
+pub struct NotificationQuery {
+    /// Whether to include notifications that have ended or were closed by the
+    /// current user.
+    include_past: bool,
+    /// Whether to include notifications for every role, not just those of the
+    /// current user.
+    include_all: bool,
+    /// Whether to mark the notifications closed by the current user.
+    show_is_closed: bool,
+}
+
+impl Default for NotificationQuery {
+    fn default() -> Self {
+        Self {
+            include_past: false,
+            include_all: true,
+            show_is_closed: false,
+        }
+    }
+}
+
+impl NotificationQuery {
+    /// Creates a new [`NotificationQuery`] for the current notifications of
+    /// every role.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Sets whether to include notifications that have ended or were closed
+    /// by the current user. Defaults to false.
+    pub fn set_include_past(mut self, include_past: bool) -> Self {
+        self.include_past = include_past;
+
+        self
+    }
+
+    /// Sets whether to include notifications for every role, not just those
+    /// of the current user. Defaults to true, which requires the
+    /// `manage_alerts` permission.
+    pub fn set_include_all(mut self, include_all: bool) -> Self {
+        self.include_all = include_all;
+
+        self
+    }
+
+    /// Sets whether to mark the notifications closed by the current user with
+    /// [`AccountNotification::closed`]. Defaults to false.
+    pub fn set_show_is_closed(mut self, show_is_closed: bool) -> Self {
+        self.show_is_closed = show_is_closed;
+
+        self
+    }
+}
+
+/// The notifications of one account, collected by
+/// [`AccountNotificationsExt::get_notifications_for_account_tree`].
+#[derive(Debug, Clone)]
+pub struct SubAccountNotifications {
+    /// The ID of the account.
+    account_id: AccountId,
+    /// The notifications of the account.
+    notifications: Vec<AccountNotification>,
+}
+
+impl SubAccountNotifications {
+    /// The ID of the account.
+    pub fn account_id(&self) -> &AccountId {
+        &self.account_id
+    }
+    /// The notifications of the account.
+    pub fn notifications(&self) -> &[AccountNotification] {
+        &self.notifications
+    }
+}
+
 #[async_trait]
 pub trait AccountNotificationsExt {
     /// Returns a list of all global notifications in the account for the
@@ -104,17 +289,27 @@ pub trait AccountNotificationsExt {
     /// returned, unless a include_past parameter is passed in as true.
     async fn get_global_notifications_for_account(
         &self,
-        account_id: u64,
+        account_id: AccountId,
         include_past: IncludePastNotifications,
     ) -> Result<GetGlobalNotificationsForAccountResponse, crate::Error>;
 
+    /// Streams all global notifications in the account for the current user,
+    /// fetching each page as it is consumed.
+    ///
+    /// See [`AccountNotificationsExt::get_global_notifications_for_account`].
+    fn get_global_notifications_for_account_stream(
+        &self,
+        account_id: AccountId,
+        include_past: IncludePastNotifications,
+    ) -> PageStream<'_, AccountNotification>;
+
     /// Returns a global notification for the current user.
     ///
     /// A notification that has been closed by the user will not be returned.
     async fn get_notification_for_account(
         &self,
-        account_id: u64,
-        notification_id: u64,
+        account_id: AccountId,
+        notification_id: NotificationId,
     ) -> Result<GetNotificationForAccountByIdResponse, crate::Error>;
 
     /// If the current user no long wants to see this notification it can be excused with this call.
@@ -122,28 +317,79 @@ pub trait AccountNotificationsExt {
     /// Returns the notification before it was closed.
     async fn close_notification_for_account(
         &self,
-        account_id: u64,
-        notification_id: u64,
+        account_id: AccountId,
+        notification_id: NotificationId,
     ) -> Result<CloseNotificationForAccountResponse, crate::Error>;
 
     /// Create and return a new global notification for an account.
     ///
-    /// Note that the [`AccountNotification::role_ids`] field will be ignored.
+    /// The notification is configured with [`AccountNotification::builder`].
     async fn create_global_notification(
         &self,
-        account_id: u64,
+        account_id: AccountId,
         notification: &AccountNotification,
     ) -> Result<CreateGlobalNotificationResponse, crate::Error>;
 
     // Update global notification for an account.
-    ///
-    /// Note that the [`AccountNotification::role_ids`] field will be ignored.
     async fn update_global_notification(
         &self,
-        account_id: u64,
-        notification_id: u64,
+        account_id: AccountId,
+        notification_id: NotificationId,
         new_notification: &AccountNotification,
     ) -> Result<UpdateGlobalNotificationResponse, crate::Error>;
+
+    /// Returns the notifications of an account as an admin sees them, including
+    /// those for other roles with [`NotificationQuery::set_include_all`].
+    ///
+    /// [See docs](https://canvas.instructure.com/doc/api/account_notifications.html#method.account_notifications.user_index).
+    async fn get_all_notifications_for_account(
+        &self,
+        account_id: AccountId,
+        query: &NotificationQuery,
+    ) -> Result<Vec<AccountNotification>, crate::Error>;
+
+    /// Streams the notifications of an account as an admin sees them, fetching
+    /// each page as it is consumed.
+    ///
+    /// See [`AccountNotificationsExt::get_all_notifications_for_account`].
+    fn get_all_notifications_for_account_stream(
+        &self,
+        account_id: AccountId,
+        query: &NotificationQuery,
+    ) -> PageStream<'_, AccountNotification>;
+
+    /// Expires a global notification by ending it now, so that it is no
+    /// longer shown to anyone. A notification that has not started yet is
+    /// also started now, as a notification cannot end before it starts.
+    ///
+    /// Canvas has no API for admins to delete notifications, so an expired
+    /// notification remains listed with [`NotificationQuery::set_include_past`].
+    /// To hide a notification for the current user only, use
+    /// [`AccountNotificationsExt::close_notification_for_account`] instead.
+    ///
+    /// `notification` must have been returned by Canvas, such as by
+    /// [`AccountNotificationsExt::get_all_notifications_for_account`], and
+    /// fails with [`crate::Error::UnsavedNotification`] otherwise.
+    ///
+    /// Returns the expired notification.
+    async fn expire_global_notification(
+        &self,
+        account_id: AccountId,
+        notification: &AccountNotification,
+    ) -> Result<AccountNotification, crate::Error>;
+
+    /// Collects the notifications of an account and every one of its
+    /// sub-accounts, such as to audit which notifications are shown across an
+    /// institution.
+    ///
+    /// Canvas lists the notifications of parent accounts alongside those of a
+    /// sub-account, so each notification is only returned under the first
+    /// account it was listed for, starting with `account_id`.
+    async fn get_notifications_for_account_tree(
+        &self,
+        account_id: AccountId,
+        query: &NotificationQuery,
+    ) -> Result<Vec<SubAccountNotifications>, crate::Error>;
 }
 
 // https://canvas.instructure.com/doc/api/account_notifications.html#method.account_notifications.user_index
@@ -165,32 +411,40 @@ type UpdateGlobalNotificationResponse = AccountNotification;
 impl AccountNotificationsExt for CanvasClient {
     async fn get_global_notifications_for_account(
         &self,
-        account_id: u64,
+        account_id: AccountId,
         include_past: IncludePastNotifications,
     ) -> Result<GetGlobalNotificationsForAccountResponse, crate::Error> {
-        let notifications = self
-            .make_query(&format!("v1/accounts/{account_id}/account_notifications"))
-            .query(&[("include_past", include_past)])
-            .send()
-            .await?
-            .json()
-            .await?;
+        self.get_global_notifications_for_account_stream(account_id, include_past)
+            .try_collect()
+            .await
+    }
 
-        Ok(notifications)
+    fn get_global_notifications_for_account_stream(
+        &self,
+        account_id: AccountId,
+        include_past: IncludePastNotifications,
+    ) -> PageStream<'_, AccountNotification> {
+        self.paginate_stream(
+            self.make_query(&format!(
+                "v1/accounts/{}/account_notifications",
+                account_id.path_segment()
+            ))
+            .query(&[("include_past", include_past)]),
+            |page: GetGlobalNotificationsForAccountResponse| page,
+        )
     }
 
     async fn get_notification_for_account(
         &self,
-        account_id: u64,
-        notification_id: u64,
+        account_id: AccountId,
+        notification_id: NotificationId,
     ) -> Result<GetNotificationForAccountByIdResponse, crate::Error> {
         let notification = self
-            .make_query(&format!(
-                "v1/accounts/{account_id}/account_notifications/{notification_id}"
-            ))
-            .send()
-            .await?
-            .json()
+            .send(self.make_query(&format!(
+                "v1/accounts/{}/account_notifications/{}",
+                account_id.path_segment(),
+                notification_id.path_segment()
+            )))
             .await?;
 
         Ok(notification)
@@ -198,16 +452,15 @@ impl AccountNotificationsExt for CanvasClient {
 
     async fn close_notification_for_account(
         &self,
-        account_id: u64,
-        notification_id: u64,
+        account_id: AccountId,
+        notification_id: NotificationId,
     ) -> Result<CloseNotificationForAccountResponse, crate::Error> {
         let notification = self
-            .make_delete(&format!(
-                "v1/accounts/{account_id}/account_notifications/{notification_id}"
-            ))
-            .send()
-            .await?
-            .json()
+            .send(self.make_delete(&format!(
+                "v1/accounts/{}/account_notifications/{}",
+                account_id.path_segment(),
+                notification_id.path_segment()
+            )))
             .await?;
 
         Ok(notification)
@@ -215,15 +468,17 @@ impl AccountNotificationsExt for CanvasClient {
 
     async fn create_global_notification(
         &self,
-        account_id: u64,
+        account_id: AccountId,
         notification: &AccountNotification,
     ) -> Result<CreateGlobalNotificationResponse, crate::Error> {
         let notification = self
-            .make_post(&format!("v1/accounts/{account_id}/account_notifications"))
-            .form(notification)
-            .send()
-            .await?
-            .json()
+            .send(
+                self.make_post(&format!(
+                    "v1/accounts/{}/account_notifications",
+                    account_id.path_segment()
+                ))
+                .nested_form(notification)?,
+            )
             .await?;
 
         Ok(notification)
@@ -231,20 +486,125 @@ impl AccountNotificationsExt for CanvasClient {
 
     async fn update_global_notification(
         &self,
-        account_id: u64,
-        notification_id: u64,
+        account_id: AccountId,
+        notification_id: NotificationId,
         new_notification: &AccountNotification,
     ) -> Result<UpdateGlobalNotificationResponse, crate::Error> {
         let notification = self
-            .make_put(&format!(
-                "v1/accounts/{account_id}/account_notifications/{notification_id}"
-            ))
-            .form(new_notification)
-            .send()
-            .await?
-            .json()
+            .send(
+                self.make_put(&format!(
+                    "v1/accounts/{}/account_notifications/{}",
+                    account_id.path_segment(),
+                    notification_id.path_segment()
+                ))
+                .nested_form(new_notification)?,
+            )
             .await?;
 
         Ok(notification)
     }
+
+    async fn get_all_notifications_for_account(
+        &self,
+        account_id: AccountId,
+        query: &NotificationQuery,
+    ) -> Result<Vec<AccountNotification>, crate::Error> {
+        self.get_all_notifications_for_account_stream(account_id, query)
+            .try_collect()
+            .await
+    }
+
+    fn get_all_notifications_for_account_stream(
+        &self,
+        account_id: AccountId,
+        query: &NotificationQuery,
+    ) -> PageStream<'_, AccountNotification> {
+        self.paginate_stream(
+            self.make_query(&format!(
+                "v1/accounts/{}/account_notifications",
+                account_id.path_segment()
+            ))
+            .query(query),
+            |page: Vec<AccountNotification>| page,
+        )
+    }
+
+    async fn expire_global_notification(
+        &self,
+        account_id: AccountId,
+        notification: &AccountNotification,
+    ) -> Result<AccountNotification, crate::Error> {
+        #[derive(Serialize)]
+        struct ExpireNotification {
+            #[serde(
+                rename = "account_notification[start_at]",
+                with = "crate::datetime::rfc3339::option",
+                skip_serializing_if = "Option::is_none"
+            )]
+            start_at: Option<DateTime>,
+            #[serde(
+                rename = "account_notification[end_at]",
+                with = "crate::datetime::rfc3339"
+            )]
+            end_at: DateTime,
+        }
+
+        let Some(notification_id) = notification.id() else {
+            return Err(crate::Error::UnsavedNotification {
+                subject: notification.subject.clone(),
+            });
+        };
+
+        let now = crate::datetime::now();
+        let expired = self
+            .send(
+                self.make_put(&format!(
+                    "v1/accounts/{}/account_notifications/{}",
+                    account_id.path_segment(),
+                    notification_id.path_segment()
+                ))
+                .nested_form(&ExpireNotification {
+                    start_at: (notification.start_at > now).then_some(now),
+                    end_at: now,
+                })?,
+            )
+            .await?;
+
+        Ok(expired)
+    }
+
+    async fn get_notifications_for_account_tree(
+        &self,
+        account_id: AccountId,
+        query: &NotificationQuery,
+    ) -> Result<Vec<SubAccountNotifications>, crate::Error> {
+        let mut account_ids = vec![account_id.clone()];
+        account_ids.extend(
+            self.get_sub_accounts(account_id, true)
+                .await?
+                .into_iter()
+                .map(|account| account.id().clone()),
+        );
+
+        let mut seen = HashSet::new();
+        let mut tree = Vec::with_capacity(account_ids.len());
+        for account_id in account_ids {
+            let notifications = self
+                .get_all_notifications_for_account(account_id.clone(), query)
+                .await?
+                .into_iter()
+                .filter(|notification| match notification.id() {
+                    Some(id) => seen.insert(id.clone()),
+                    None => true,
+                })
+                .collect();
+
+            tree.push(SubAccountNotifications {
+                account_id,
+                notifications,
+            });
+        }
+
+        Ok(tree)
+    }
 }
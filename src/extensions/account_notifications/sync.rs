@@ -0,0 +1,673 @@
+//! Reconciles the notifications of accounts with a desired state, such as a
+//! schedule of maintenance banners kept in a YAML or TOML file.
+//!
+//! Each notification in the desired state has a stable `key`, which is used
+//! to find the notification it manages in Canvas. With
+//! [`Matching::SubjectTag`], the key is appended to the subject of the
+//! notification as `[key]`. With [`Matching::StateFile`], the ID of each
+//! managed notification is kept in a JSON file instead, leaving the subject
+//! untouched.
+//!
+//! Syncing is split into [`NotificationSync::plan`], which only reads from
+//! Canvas, and [`NotificationSync::apply`], which makes the planned changes.
+//! Both the [`SyncPlan`] and the [`SyncReport`] serialize to a
+//! machine-readable summary of the changes.
+//!
+//! # Example
+//! ```yaml
+//! accounts:
+//!   - account_id: 1
+//!     notifications:
+//!       - key: maintenance-2024-06
+//!         subject: Scheduled maintenance
+//!         message: Canvas will be unavailable on Saturday morning.
+//!         start_at: 2024-06-01T00:00:00Z
+//!         end_at: 2024-06-08T12:00:00Z
+//!         icon: calendar
+//! ```
+//!
+//! ```rs
+//! let desired = DesiredState::from_path(Path::new("notifications.yaml"))?;
+//! let sync = NotificationSync::new(&client);
+//! let plan = sync.plan(&desired).await?;
+//! println!("{}", serde_json::to_string_pretty(&plan)?);
+//! let report = sync.apply(plan).await?;
+//! ```
+
+use std::{
+    collections::{BTreeMap, HashSet},
+    path::{Path, PathBuf},
+};
+
+use serde::{Deserialize, Serialize};
+
+use super::{AccountNotification, AccountNotificationsExt, NotificationIcon, NotificationQuery};
+use crate::{AccountId, CanvasClient, DateTime, NotificationId, RoleId};
+
+/// The notifications each account should have.
+#[derive(Debug, Clone, Deserialize)]
+pub struct DesiredState {
+    /// The accounts whose notifications are managed.
+    #[serde(default)]
+    accounts: Vec<DesiredAccount>,
+}
+
+/// The notifications one account should have.
+#[derive(Debug, Clone, Deserialize)]
+pub struct DesiredAccount {
+    /// The ID of the account.
+    account_id: AccountId,
+    /// The notifications of the account.
+    #[serde(default)]
+    notifications: Vec<DesiredNotification>,
+}
+
+/// A notification an account should have.
+#[derive(Debug, Clone, Deserialize)]
+pub struct DesiredNotification {
+    /// The stable key the notification is matched by.
+    key: String,
+    /// The subject of the notification.
+    subject: String,
+    /// The message of the notification.
+    message: String,
+    /// When to start showing the notification.
+    #[serde(with = "crate::datetime::rfc3339")]
+    start_at: DateTime,
+    /// When to stop showing the notification.
+    #[serde(with = "crate::datetime::rfc3339")]
+    end_at: DateTime,
+    /// The icon to display with the message. Defaults to `warning`.
+    #[serde(default)]
+    icon: Option<NotificationIcon>,
+    /// The roles to show the notification to. Defaults to all roles.
+    #[serde(default)]
+    role_ids: Option<Vec<RoleId>>,
+}
+
+impl DesiredState {
+    /// Parses a desired state from YAML.
+    pub fn from_yaml_str(yaml: &str) -> Result<Self, crate::Error> {
+        serde_yaml::from_str(yaml).map_err(sync_error)
+    }
+
+    /// Parses a desired state from TOML.
+    ///
+    /// Timestamps may be written as TOML offset date-times, or as strings.
+    pub fn from_toml_str(toml: &str) -> Result<Self, crate::Error> {
+        let value: toml::Value = toml::from_str(toml).map_err(sync_error)?;
+
+        serde_json::from_value(toml_to_json(value)).map_err(sync_error)
+    }
+
+    /// Reads a desired state from a `.yaml`, `.yml` or `.toml` file.
+    pub fn from_path(path: &Path) -> Result<Self, crate::Error> {
+        let contents = std::fs::read_to_string(path).map_err(|e| crate::Error::Io { source: e })?;
+
+        match path.extension().and_then(|extension| extension.to_str()) {
+            Some("yaml" | "yml") => Self::from_yaml_str(&contents),
+            Some("toml") => Self::from_toml_str(&contents),
+            _ => Err(crate::Error::NotificationSync {
+                message: format!("{} is not a YAML or TOML file", path.display()),
+            }),
+        }
+    }
+
+    /// The accounts whose notifications are managed.
+    pub fn accounts(&self) -> &[DesiredAccount] {
+        &self.accounts
+    }
+
+    /// Checks that each account, and each key of an account, is only listed
+    /// once.
+    fn validate(&self) -> Result<(), crate::Error> {
+        let mut account_ids = HashSet::new();
+        for account in &self.accounts {
+            if !account_ids.insert(&account.account_id) {
+                return Err(crate::Error::NotificationSync {
+                    message: format!("account {} is listed more than once", account.account_id),
+                });
+            }
+
+            let mut keys = HashSet::new();
+            for notification in &account.notifications {
+                if !keys.insert(notification.key.as_str()) {
+                    return Err(crate::Error::NotificationSync {
+                        message: format!(
+                            "key {} is listed more than once for account {}",
+                            notification.key, account.account_id
+                        ),
+                    });
+                }
+            }
+        }
+
+        Ok(())
+    }
+}
+
+impl DesiredAccount {
+    /// The ID of the account.
+    pub fn account_id(&self) -> &AccountId {
+        &self.account_id
+    }
+
+    /// The notifications of the account.
+    pub fn notifications(&self) -> &[DesiredNotification] {
+        &self.notifications
+    }
+}
+
+impl DesiredNotification {
+    /// The stable key the notification is matched by.
+    pub fn key(&self) -> &str {
+        &self.key
+    }
+
+    /// The subject of the notification.
+    pub fn subject(&self) -> &str {
+        &self.subject
+    }
+
+    /// The message of the notification.
+    pub fn message(&self) -> &str {
+        &self.message
+    }
+
+    /// When to start showing the notification.
+    pub fn start_at(&self) -> DateTime {
+        self.start_at
+    }
+
+    /// When to stop showing the notification.
+    pub fn end_at(&self) -> DateTime {
+        self.end_at
+    }
+
+    /// The icon to display with the message.
+    pub fn icon(&self) -> Option<NotificationIcon> {
+        self.icon
+    }
+
+    /// The roles to show the notification to.
+    pub fn role_ids(&self) -> Option<&Vec<RoleId>> {
+        self.role_ids.as_ref()
+    }
+
+    /// Builds the notification to send to Canvas, with `subject`.
+    fn to_notification(&self, subject: String) -> Result<AccountNotification, crate::Error> {
+        let mut builder =
+            AccountNotification::builder(subject, &self.message, self.start_at, self.end_at)
+                .set_role_ids(self.role_ids.clone());
+        if let Some(icon) = self.icon {
+            builder = builder.set_icon(icon);
+        }
+
+        builder.build()
+    }
+}
+
+/// How the notifications in Canvas are matched to the keys of the desired
+/// state.
+#[derive(Debug, Clone)]
+pub enum Matching {
+    /// The key is appended to the subject of each notification as `[key]`.
+    /// Notifications without a key in their subject are left alone.
+    SubjectTag,
+    /// The ID of each notification is recorded under its key in a JSON state
+    /// file, which is created by the first [`NotificationSync::apply`].
+    /// Notifications not recorded in the file are left alone.
+    StateFile(PathBuf),
+}
+
+/// What a [`Change`] does to a notification.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ChangeAction {
+    /// Creates a notification that does not exist yet.
+    Create,
+    /// Updates a notification that differs from the desired state.
+    Update,
+    /// Expires a notification that is no longer in the desired state, with
+    /// [`AccountNotificationsExt::expire_global_notification`].
+    Delete,
+}
+
+/// A change to one notification, planned by [`NotificationSync::plan`].
+#[derive(Debug, Clone, Serialize)]
+pub struct Change {
+    /// The account of the notification.
+    account_id: AccountId,
+    /// The key of the notification.
+    key: String,
+    /// What the change does.
+    action: ChangeAction,
+    /// The ID of the notification that is updated or expired.
+    notification_id: Option<NotificationId>,
+    /// The subject of the notification.
+    subject: String,
+    /// The notification that is created or updated, or the current
+    /// notification that is expired.
+    #[serde(skip)]
+    notification: Option<AccountNotification>,
+}
+
+impl Change {
+    /// The account of the notification.
+    pub fn account_id(&self) -> &AccountId {
+        &self.account_id
+    }
+
+    /// The key of the notification.
+    pub fn key(&self) -> &str {
+        &self.key
+    }
+
+    /// What the change does.
+    pub fn action(&self) -> ChangeAction {
+        self.action
+    }
+
+    /// The ID of the notification that is updated or expired.
+    pub fn notification_id(&self) -> Option<&NotificationId> {
+        self.notification_id.as_ref()
+    }
+
+    /// The subject of the notification.
+    pub fn subject(&self) -> &str {
+        &self.subject
+    }
+}
+
+/// The changes that converge Canvas to a desired state.
+#[derive(Debug, Clone, Serialize)]
+pub struct SyncPlan {
+    /// The planned changes.
+    changes: Vec<Change>,
+}
+
+impl SyncPlan {
+    /// The planned changes.
+    pub fn changes(&self) -> &[Change] {
+        &self.changes
+    }
+
+    /// Whether Canvas already matches the desired state.
+    pub fn is_empty(&self) -> bool {
+        self.changes.is_empty()
+    }
+}
+
+/// The result of one applied [`Change`].
+#[derive(Debug, Clone, Serialize)]
+pub struct ChangeResult {
+    /// The change that was applied.
+    #[serde(flatten)]
+    change: Change,
+    /// The ID of the notification after the change, such as the ID of a
+    /// created notification.
+    result_id: Option<NotificationId>,
+    /// Why the change failed, if it did.
+    error: Option<String>,
+}
+
+impl ChangeResult {
+    /// The change that was applied.
+    pub fn change(&self) -> &Change {
+        &self.change
+    }
+
+    /// The ID of the notification after the change, such as the ID of a
+    /// created notification.
+    pub fn result_id(&self) -> Option<&NotificationId> {
+        self.result_id.as_ref()
+    }
+
+    /// Why the change failed, if it did.
+    pub fn error(&self) -> Option<&str> {
+        self.error.as_deref()
+    }
+}
+
+/// The outcome of [`NotificationSync::apply`].
+#[derive(Debug, Clone, Serialize)]
+pub struct SyncReport {
+    /// The result of each planned change.
+    results: Vec<ChangeResult>,
+}
+
+impl SyncReport {
+    /// The result of each planned change.
+    pub fn results(&self) -> &[ChangeResult] {
+        &self.results
+    }
+
+    /// Whether every change was applied.
+    pub fn is_success(&self) -> bool {
+        self.results.iter().all(|result| result.error.is_none())
+    }
+}
+
+/// The notifications recorded by [`Matching::StateFile`].
+#[derive(Debug, Default, Serialize, Deserialize)]
+struct SyncState {
+    notifications: Vec<StateEntry>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+struct StateEntry {
+    account_id: AccountId,
+    key: String,
+    notification_id: NotificationId,
+}
+
+impl SyncState {
+    fn load(path: &Path) -> Result<Self, crate::Error> {
+        match std::fs::read_to_string(path) {
+            Ok(contents) => serde_json::from_str(&contents).map_err(sync_error),
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(Self::default()),
+            Err(e) => Err(crate::Error::Io { source: e }),
+        }
+    }
+
+    fn save(&self, path: &Path) -> Result<(), crate::Error> {
+        let contents = serde_json::to_string_pretty(self).map_err(sync_error)?;
+
+        std::fs::write(path, contents).map_err(|e| crate::Error::Io { source: e })
+    }
+
+    fn record(&mut self, account_id: &AccountId, key: &str, notification_id: NotificationId) {
+        self.forget(account_id, key);
+        self.notifications.push(StateEntry {
+            account_id: account_id.clone(),
+            key: key.to_string(),
+            notification_id,
+        });
+    }
+
+    fn forget(&mut self, account_id: &AccountId, key: &str) {
+        self.notifications
+            .retain(|entry| !(entry.account_id == *account_id && entry.key == key));
+    }
+}
+
+/// Plans and applies the changes that converge the notifications of accounts
+/// to a [`DesiredState`].
+pub struct NotificationSync<'a> {
+    client: &'a CanvasClient,
+    matching: Matching,
+}
+
+impl<'a> NotificationSync<'a> {
+    /// Creates a new [`NotificationSync`] that matches notifications with
+    /// [`Matching::SubjectTag`].
+    pub fn new(client: &'a CanvasClient) -> Self {
+        Self {
+            client,
+            matching: Matching::SubjectTag,
+        }
+    }
+
+    /// Sets how notifications are matched to the keys of the desired state.
+    /// Defaults to [`Matching::SubjectTag`].
+    pub fn set_matching(mut self, matching: Matching) -> Self {
+        self.matching = matching;
+
+        self
+    }
+
+    /// Compares `desired` with the current notifications of its accounts,
+    /// returning the changes that converge them without making any.
+    ///
+    /// Notifications that have already ended are left alone: a desired
+    /// notification that ended is not created, and a notification that ended
+    /// is not expired again once it is removed from the desired state.
+    ///
+    /// Fails with [`crate::Error::InvalidNotificationWindow`] if a desired
+    /// notification does not end after it starts, and with
+    /// [`crate::Error::NotificationSync`] if an account or a key of an account
+    /// appears more than once in `desired`, or if several notifications of an
+    /// account are tagged with the same key.
+    pub async fn plan(&self, desired: &DesiredState) -> Result<SyncPlan, crate::Error> {
+        desired.validate()?;
+
+        let state = match &self.matching {
+            Matching::SubjectTag => None,
+            Matching::StateFile(path) => Some(SyncState::load(path)?),
+        };
+
+        let now = crate::datetime::now();
+        let mut changes = Vec::new();
+        for account in &desired.accounts {
+            // ended notifications are listed too, so that they are not created again
+            let existing = self
+                .client
+                .get_all_notifications_for_account(
+                    account.account_id.clone(),
+                    &NotificationQuery::new().set_include_past(true),
+                )
+                .await?;
+            let mut managed = managed_notifications(&account.account_id, existing, state.as_ref())?;
+
+            for wanted in &account.notifications {
+                let subject = match self.matching {
+                    Matching::SubjectTag => format!("{} [{}]", wanted.subject, wanted.key),
+                    Matching::StateFile(_) => wanted.subject.clone(),
+                };
+                let notification = wanted.to_notification(subject.clone())?;
+
+                let (action, notification_id) = match managed.remove(&wanted.key) {
+                    None if wanted.end_at <= now => continue,
+                    None => (ChangeAction::Create, None),
+                    Some(current) if differs(&current, &notification) => {
+                        (ChangeAction::Update, current.id)
+                    }
+                    Some(_) => continue,
+                };
+                changes.push(Change {
+                    account_id: account.account_id.clone(),
+                    key: wanted.key.clone(),
+                    action,
+                    notification_id,
+                    subject,
+                    notification: Some(notification),
+                });
+            }
+
+            changes.extend(
+                managed
+                    .into_iter()
+                    .filter(|(_, current)| current.end_at > now)
+                    .map(|(key, current)| Change {
+                        account_id: account.account_id.clone(),
+                        key,
+                        action: ChangeAction::Delete,
+                        notification_id: current.id.clone(),
+                        subject: current.subject.clone(),
+                        notification: Some(current),
+                    }),
+            );
+        }
+
+        Ok(SyncPlan { changes })
+    }
+
+    /// Applies the changes of `plan`.
+    ///
+    /// A failed change does not stop the others from being applied, and is
+    /// recorded in the returned [`SyncReport`] instead. With
+    /// [`Matching::StateFile`], the state file is updated with the created and
+    /// expired notifications.
+    pub async fn apply(&self, plan: SyncPlan) -> Result<SyncReport, crate::Error> {
+        let mut state = match &self.matching {
+            Matching::SubjectTag => None,
+            Matching::StateFile(path) => Some(SyncState::load(path)?),
+        };
+
+        let mut results = Vec::with_capacity(plan.changes.len());
+        for change in plan.changes {
+            let applied = self.apply_change(&change).await;
+
+            if let (Some(state), Ok(result_id)) = (state.as_mut(), &applied) {
+                match (change.action, result_id) {
+                    (ChangeAction::Delete, _) => state.forget(&change.account_id, &change.key),
+                    (_, Some(result_id)) => {
+                        state.record(&change.account_id, &change.key, result_id.clone())
+                    }
+                    (_, None) => {}
+                }
+            }
+
+            let (result_id, error) = match applied {
+                Ok(result_id) => (result_id, None),
+                Err(e) => (None, Some(e.to_string())),
+            };
+            results.push(ChangeResult {
+                change,
+                result_id,
+                error,
+            });
+        }
+
+        if let (Some(state), Matching::StateFile(path)) = (state, &self.matching) {
+            state.save(path)?;
+        }
+
+        Ok(SyncReport { results })
+    }
+
+    /// Applies one change, returning the ID of the notification after it.
+    async fn apply_change(&self, change: &Change) -> Result<Option<NotificationId>, crate::Error> {
+        let notification = match (change.action, &change.notification, &change.notification_id) {
+            (ChangeAction::Create, Some(notification), _) => {
+                self.client
+                    .create_global_notification(change.account_id.clone(), notification)
+                    .await?
+            }
+            (ChangeAction::Update, Some(notification), Some(notification_id)) => {
+                self.client
+                    .update_global_notification(
+                        change.account_id.clone(),
+                        notification_id.clone(),
+                        notification,
+                    )
+                    .await?
+            }
+            (ChangeAction::Delete, Some(notification), _) => {
+                self.client
+                    .expire_global_notification(change.account_id.clone(), notification)
+                    .await?
+            }
+            _ => {
+                return Err(crate::Error::NotificationSync {
+                    message: format!("the {:?} of {} is incomplete", change.action, change.key),
+                })
+            }
+        };
+
+        Ok(notification.id)
+    }
+}
+
+/// The notifications among `existing` that are managed by the sync, by key.
+///
+/// Fails if several notifications are tagged with the same key, as it is
+/// ambiguous which of them the key manages.
+fn managed_notifications(
+    account_id: &AccountId,
+    existing: Vec<AccountNotification>,
+    state: Option<&SyncState>,
+) -> Result<BTreeMap<String, AccountNotification>, crate::Error> {
+    let mut managed = BTreeMap::new();
+
+    match state {
+        None => {
+            for notification in existing {
+                let Some(key) = subject_key(&notification.subject).map(str::to_string) else {
+                    continue;
+                };
+                let id = notification.id.clone();
+                if let Some(other) = managed.insert(key.clone(), notification) {
+                    return Err(crate::Error::NotificationSync {
+                        message: format!(
+                            "notifications {} and {} of account {account_id} are both tagged [{key}]",
+                            display_id(other.id.as_ref()),
+                            display_id(id.as_ref()),
+                        ),
+                    });
+                }
+            }
+        }
+        Some(state) => {
+            for entry in &state.notifications {
+                if entry.account_id != *account_id {
+                    continue;
+                }
+                let notification = existing
+                    .iter()
+                    .find(|notification| notification.id.as_ref() == Some(&entry.notification_id));
+                if let Some(notification) = notification {
+                    managed.insert(entry.key.clone(), notification.clone());
+                }
+            }
+        }
+    }
+
+    Ok(managed)
+}
+
+/// The ID of a notification for error messages.
+fn display_id(id: Option<&NotificationId>) -> String {
+    id.map_or_else(|| String::from("without an ID"), ToString::to_string)
+}
+
+/// The key tagged at the end of a subject, as in `Maintenance [key]`.
+fn subject_key(subject: &str) -> Option<&str> {
+    let (_, key) = subject.strip_suffix(']')?.rsplit_once(" [")?;
+
+    (!key.is_empty()).then_some(key)
+}
+
+/// Whether the notification in Canvas differs from the desired one.
+fn differs(current: &AccountNotification, desired: &AccountNotification) -> bool {
+    let roles = |notification: &AccountNotification| {
+        let mut roles: Vec<String> = notification
+            .role_ids
+            .iter()
+            .flatten()
+            .map(ToString::to_string)
+            .collect();
+        roles.sort();
+        roles
+    };
+
+    current.subject != desired.subject
+        || current.message != desired.message
+        || current.start_at != desired.start_at
+        || current.end_at != desired.end_at
+        || current.icon != desired.icon
+        || roles(current) != roles(desired)
+}
+
+fn sync_error(e: impl std::fmt::Display) -> crate::Error {
+    crate::Error::NotificationSync {
+        message: e.to_string(),
+    }
+}
+
+/// Converts a TOML value to JSON, with date-times as RFC 3339 strings.
+fn toml_to_json(value: toml::Value) -> serde_json::Value {
+    match value {
+        toml::Value::String(value) => value.into(),
+        toml::Value::Integer(value) => value.into(),
+        toml::Value::Float(value) => value.into(),
+        toml::Value::Boolean(value) => value.into(),
+        toml::Value::Datetime(value) => value.to_string().into(),
+        toml::Value::Array(values) => values.into_iter().map(toml_to_json).collect(),
+        toml::Value::Table(table) => table
+            .into_iter()
+            .map(|(key, value)| (key, toml_to_json(value)))
+            .collect::<serde_json::Map<_, _>>()
+            .into(),
+    }
+}
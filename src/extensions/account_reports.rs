@@ -1,130 +1,218 @@
+use std::{
+    collections::BTreeMap,
+    path::{Path, PathBuf},
+    time::{Duration, Instant},
+};
+
 use async_trait::async_trait;
+use futures::TryStreamExt;
 use serde::{Deserialize, Serialize};
+use tokio::io::{AsyncWrite, AsyncWriteExt};
+
+use self::{
+    parameters::{ReportParameterProblem, TypedReport},
+    retention::RetentionPolicy,
+    rows::{ReportRow, ReportRows},
+};
+use crate::{
+    file::File, form::NestedForm, AccountId, CanvasClient, CourseId, DateTime, PageStream,
+    ReportId, TermId,
+};
 
-use crate::CanvasClient;
+pub mod parameters;
+pub mod retention;
+pub mod rows;
 
+/// An instance of a report run for an account.
+///
+/// [See docs](https://canvas.instructure.com/doc/api/account_reports.html#Report).
+#[derive(Debug, Clone, Deserialize)]
 pub struct Report {
     /// The unique identifier for the report.
-    id: u64,
+    id: ReportId,
     /// The type of report.
     report: String,
-    /// The url to the report download.
-    file_url: String,
+    /// The url to the report download. Only available after the report has
+    /// completed.
+    file_url: Option<String>,
     /// The attachment api object of the report. Only available after the report
     /// has completed.
-    attachment: Option<String>,
+    attachment: Option<File>,
     /// The status of the report
-    status: String,
+    status: ReportStatus,
     /// The date and time the report was created.
-    created_at: String,
+    #[serde(default, with = "crate::datetime::rfc3339::option")]
+    created_at: Option<DateTime>,
     /// The date and time the report started processing.
-    started_at: String,
+    #[serde(default, with = "crate::datetime::rfc3339::option")]
+    started_at: Option<DateTime>,
     /// The date and time the report finished processing.
-    ended_at: String,
+    #[serde(default, with = "crate::datetime::rfc3339::option")]
+    ended_at: Option<DateTime>,
     /// The report parameters.
+    #[serde(default, deserialize_with = "lenient::required")]
     parameters: ReportParameters,
     /// The progress of the report
+    #[serde(default, deserialize_with = "lenient::required")]
     progress: u8,
     /// This is the current line count being written to the report. It updates
     /// every 1000 records.
-    current_line: u64,
+    #[serde(default, deserialize_with = "lenient::optional")]
+    current_line: Option<u64>,
+}
+
+/// The status of a [`Report`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ReportStatus {
+    /// The report has been created, but has not started running.
+    Created,
+    /// The report is running.
+    Running,
+    /// The report has finished running, and its files are being compiled.
+    Compiling,
+    /// The report completed, and its file is available.
+    Complete,
+    /// The report failed.
+    Error,
+    /// The report was aborted before it completed, such as by an admin.
+    Aborted,
+    /// The report was deleted.
+    Deleted,
+    /// A status not known to this crate.
+    #[serde(other)]
+    Unknown,
+}
+
+impl ReportStatus {
+    /// Whether the report has stopped running, either because it completed,
+    /// failed, was aborted or was deleted.
+    pub fn is_finished(&self) -> bool {
+        matches!(
+            self,
+            Self::Complete | Self::Error | Self::Aborted | Self::Deleted
+        )
+    }
 }
 
 impl Report {
     /// The unique identifier for the report.
-    pub fn id(&self) -> u64 {
-        self.id
+    pub fn id(&self) -> &ReportId {
+        &self.id
     }
     /// The type of report.
     pub fn report(&self) -> &str {
         &self.report
     }
-    /// The url to the report download.
-    pub fn file_url(&self) -> &str {
-        &self.file_url
+    /// The url to the report download. Only available after the report has
+    /// completed.
+    pub fn file_url(&self) -> Option<&str> {
+        self.file_url.as_deref()
     }
     /// The attachment api object of the report. Only available after the report
     /// has completed.
-    pub fn attachment(&self) -> Option<&String> {
+    pub fn attachment(&self) -> Option<&File> {
         self.attachment.as_ref()
     }
     /// The status of the report
-    pub fn status(&self) -> &str {
-        &self.status
+    pub fn status(&self) -> ReportStatus {
+        self.status
     }
     /// The date and time the report was created.
-    pub fn created_at(&self) -> &str {
-        &self.created_at
+    pub fn created_at(&self) -> Option<DateTime> {
+        self.created_at
     }
     /// The date and time the report started processing.
-    pub fn started_at(&self) -> &str {
-        &self.started_at
+    pub fn started_at(&self) -> Option<DateTime> {
+        self.started_at
     }
     /// The date and time the report finished processing.
-    pub fn ended_at(&self) -> &str {
-        &self.ended_at
+    pub fn ended_at(&self) -> Option<DateTime> {
+        self.ended_at
     }
     /// The report parameters.
     pub fn parameters(&self) -> &ReportParameters {
         &self.parameters
     }
-    /// The progress of the report
+    /// The progress of the report, as a percentage.
     pub fn progress(&self) -> u8 {
         self.progress
     }
     /// This is the current line count being written to the report. It updates
     /// every 1000 records.
-    pub fn current_line(&self) -> u64 {
+    pub fn current_line(&self) -> Option<u64> {
         self.current_line
     }
 }
 
-#[derive(Deserialize, Default)]
+/// The parameters a report was run with.
+///
+/// Canvas echoes parameters back as they were submitted in the form body, so
+/// values such as `"true"` and `""` are accepted in place of booleans and
+/// missing values.
+#[derive(Debug, Clone, Deserialize, Default)]
 pub struct ReportParameters {
     /// The canvas id of the term to get grades from.
-    enrollment_term_id: Option<u64>,
+    #[serde(default, deserialize_with = "lenient::optional")]
+    enrollment_term_id: Option<TermId>,
     /// If true, deleted objects will be included. If false, deleted objects
     /// will be omitted.
+    #[serde(default, deserialize_with = "lenient::optional")]
     include_deleted: Option<bool>,
     /// The id of the course to report on.
-    course_id: Option<u64>,
+    #[serde(default, deserialize_with = "lenient::optional")]
+    course_id: Option<CourseId>,
     /// The sort order for the csv, Options: 'users', 'courses', 'outcomes'.
+    #[serde(default, deserialize_with = "lenient::optional")]
     order: Option<String>,
     /// If true, user data will be included. If false, user data will be omitted.
+    #[serde(default, deserialize_with = "lenient::optional")]
     users: Option<bool>,
     /// If true, account data will be included. If false, account data will be
     /// omitted.
+    #[serde(default, deserialize_with = "lenient::optional")]
     accounts: Option<bool>,
     /// If true, term data will be included. If false, term data will be omitted.
+    #[serde(default, deserialize_with = "lenient::optional")]
     terms: Option<bool>,
     /// If true, course data will be included. If false, course data will be omitted.
+    #[serde(default, deserialize_with = "lenient::optional")]
     courses: Option<bool>,
     /// If true, section data will be included. If false, section data will be
     /// omitted.
+    #[serde(default, deserialize_with = "lenient::optional")]
     sections: Option<bool>,
     /// If true, enrollment data will be included. If false, enrollment data
     /// will be omitted.
+    #[serde(default, deserialize_with = "lenient::optional")]
     enrollments: Option<bool>,
     /// If true, group data will be included. If false, group data will be omitted.
+    #[serde(default, deserialize_with = "lenient::optional")]
     groups: Option<bool>,
     /// If true, data for crosslisted courses will be included. If false, data
     /// for crosslisted courses will be omitted.
+    #[serde(default, deserialize_with = "lenient::optional")]
     xlist: Option<bool>,
+    #[serde(default, deserialize_with = "lenient::optional")]
     sis_terms_csv: Option<u64>,
+    #[serde(default, deserialize_with = "lenient::optional")]
     sis_accounts_csv: Option<u64>,
     /// If true, enrollment state will be included. If false, enrollment state
     /// will be omitted. Defaults to false.
+    #[serde(default, deserialize_with = "lenient::optional")]
     include_enrollment_state: Option<bool>,
     /// The beginning date for submissions. Max time range is 2 weeks.
-    start_at: Option<String>,
+    #[serde(default, deserialize_with = "lenient::datetime")]
+    start_at: Option<DateTime>,
     /// The end date for submissions. Max time range is 2 weeks.
-    end_at: Option<String>,
+    #[serde(default, deserialize_with = "lenient::datetime")]
+    end_at: Option<DateTime>,
 }
 
 impl ReportParameters {
     /// The canvas id of the term to get grades from.
-    pub fn enrollment_term_id(&self) -> Option<u64> {
-        self.enrollment_term_id
+    pub fn enrollment_term_id(&self) -> Option<&TermId> {
+        self.enrollment_term_id.as_ref()
     }
     /// If true, deleted objects will be included. If false, deleted objects
     /// will be omitted.
@@ -132,8 +220,8 @@ impl ReportParameters {
         self.include_deleted
     }
     /// The id of the course to report on.
-    pub fn course_id(&self) -> Option<u64> {
-        self.course_id
+    pub fn course_id(&self) -> Option<&CourseId> {
+        self.course_id.as_ref()
     }
     /// The sort order for the csv, Options: 'users', 'courses', 'outcomes'.
     pub fn order(&self) -> Option<&String> {
@@ -187,12 +275,12 @@ impl ReportParameters {
         self.include_enrollment_state
     }
     /// The beginning date for submissions. Max time range is 2 weeks.
-    pub fn start_at(&self) -> Option<&String> {
-        self.start_at.as_ref()
+    pub fn start_at(&self) -> Option<DateTime> {
+        self.start_at
     }
     /// The end date for submissions. Max time range is 2 weeks.
-    pub fn end_at(&self) -> Option<&String> {
-        self.end_at.as_ref()
+    pub fn end_at(&self) -> Option<DateTime> {
+        self.end_at
     }
 }
 
@@ -205,13 +293,64 @@ impl ReportParameters {
     }
 }
 
+/// Configures how [`AccountReportsExt::wait_for_report`] polls a report until
+/// it finishes.
+#[derive(Debug, Clone)]
+pub struct ReportPollConfig {
+    /// How long to wait between polls.
+    interval: Duration,
+    /// How long to wait for the report to finish.
+    timeout: Duration,
+}
+
+impl Default for ReportPollConfig {
+    fn default() -> Self {
+        Self {
+            interval: Duration::from_secs(5),
+            timeout: Duration::from_secs(30 * 60),
+        }
+    }
+}
+
+impl ReportPollConfig {
+    /// Creates a new [`ReportPollConfig`] that polls every 5 seconds for up to
+    /// 30 minutes.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Sets how long to wait between polls. Defaults to 5 seconds.
+    pub fn set_interval(mut self, interval: Duration) -> Self {
+        self.interval = interval;
+
+        self
+    }
+
+    /// Sets how long to wait for the report to finish before failing with
+    /// [`crate::Error::ReportTimedOut`]. Defaults to 30 minutes.
+    pub fn set_timeout(mut self, timeout: Duration) -> Self {
+        self.timeout = timeout;
+
+        self
+    }
+}
+
 #[async_trait]
 pub trait AccountReportsExt {
     async fn get_available_reports_by_account(
         &self,
-        account_id: u64,
+        account_id: AccountId,
     ) -> Result<GetAvailableReportsByAccountResponse, crate::Error>;
 
+    /// Streams the reports available to the account, fetching each page as it
+    /// is consumed.
+    ///
+    /// See [`AccountReportsExt::get_available_reports_by_account`].
+    fn get_available_reports_by_account_stream(
+        &self,
+        account_id: AccountId,
+    ) -> PageStream<'_, ReportResponse>;
+
     /// Generates a report instance for the account. Note that `report_type` in the
     /// request must match one of the available report names.
     ///
@@ -229,33 +368,175 @@ pub trait AccountReportsExt {
     /// will be omitted. Note that not every report will respect this field.
     async fn create_report(
         &self,
-        account_id: u64,
+        account_id: AccountId,
         report_type: String,
         parameters: CreateReportForm,
     ) -> Result<CreateReportResponse, crate::Error>;
 
+    /// Generates a report with [`AccountReportsExt::create_report`], after
+    /// checking `parameters` with [`ReportResponse::validate`] against the
+    /// reports available to the account.
+    ///
+    /// Fails with [`crate::Error::InvalidReportParameters`] without submitting
+    /// the report if the parameters are rejected.
+    async fn create_validated_report(
+        &self,
+        account_id: AccountId,
+        report_type: String,
+        parameters: CreateReportForm,
+    ) -> Result<CreateReportResponse, crate::Error>;
+
+    /// Generates a report from typed parameters, such as a
+    /// [`parameters::ProvisioningReport`], with
+    /// [`AccountReportsExt::create_validated_report`].
+    async fn create_typed_report<R>(
+        &self,
+        account_id: AccountId,
+        report: R,
+    ) -> Result<CreateReportResponse, crate::Error>
+    where
+        R: TypedReport + Send;
+
     /// Shows all reports that have been run for the account of a specific type.
     async fn get_reports_by_type(
         &self,
-        account_id: u64,
+        account_id: AccountId,
         report_type: String,
     ) -> Result<GetReportsByTypeResponse, crate::Error>;
 
+    /// Streams all reports that have been run for the account of a specific
+    /// type, fetching each page as it is consumed.
+    ///
+    /// See [`AccountReportsExt::get_reports_by_type`].
+    fn get_reports_by_type_stream(
+        &self,
+        account_id: AccountId,
+        report_type: String,
+    ) -> PageStream<'_, Report>;
+
     /// Returns the status of a report.
     async fn get_report_by_id(
         &self,
-        account_id: u64,
+        account_id: AccountId,
         report_type: String,
-        report_id: u64,
+        report_id: ReportId,
     ) -> Result<GetReportByIdResponse, crate::Error>;
 
     /// Deletes a generated report instance.
     async fn delete_report_by_id(
         &self,
-        account_id: u64,
+        account_id: AccountId,
         report_type: String,
-        report_id: u64,
+        report_id: ReportId,
     ) -> Result<DeleteReportResponse, crate::Error>;
+
+    /// Applies a retention policy to every type of report available to the
+    /// account, deleting the finished reports the policy does not keep.
+    ///
+    /// Returns the deleted reports, or with [`RetentionPolicy::set_dry_run`]
+    /// the reports that would be deleted, without deleting them.
+    async fn apply_report_retention(
+        &self,
+        account_id: AccountId,
+        policy: &RetentionPolicy,
+    ) -> Result<Vec<Report>, crate::Error>;
+
+    /// Polls a report with [`AccountReportsExt::get_report_by_id`] until it
+    /// finishes, returning the completed report.
+    ///
+    /// Fails with [`crate::Error::ReportFailed`] if the report finishes with
+    /// the `error` status, is aborted or is deleted, and with
+    /// [`crate::Error::ReportTimedOut`] if it does not finish before the
+    /// timeout of `poll`.
+    async fn wait_for_report(
+        &self,
+        account_id: AccountId,
+        report: Report,
+        poll: &ReportPollConfig,
+    ) -> Result<Report, crate::Error>;
+
+    /// Generates a report with [`AccountReportsExt::create_report`], and waits
+    /// for it to complete with [`AccountReportsExt::wait_for_report`].
+    ///
+    /// # Example
+    /// ```rs
+    /// let report = client
+    ///     .run_report_to_completion(
+    ///         AccountId::new(1),
+    ///         String::from("provisioning_csv"),
+    ///         ReportParameters::new().into(),
+    ///         &ReportPollConfig::new(),
+    ///     )
+    ///     .await?;
+    /// let csv_files = client.extract_report(&report, "reports").await?;
+    /// ```
+    async fn run_report_to_completion(
+        &self,
+        account_id: AccountId,
+        report_type: String,
+        parameters: CreateReportForm,
+        poll: &ReportPollConfig,
+    ) -> Result<Report, crate::Error>;
+
+    /// Streams the file of a completed report to `writer`, returning the
+    /// number of bytes written.
+    ///
+    /// Fails with [`crate::Error::MissingReportFile`] if the report has no file.
+    async fn download_report<W>(
+        &self,
+        report: &Report,
+        writer: &mut W,
+    ) -> Result<u64, crate::Error>
+    where
+        W: AsyncWrite + Unpin + Send + ?Sized;
+
+    /// Streams the file of a completed report to `path`, replacing the file if
+    /// it exists. Returns the number of bytes written.
+    async fn download_report_to_path(
+        &self,
+        report: &Report,
+        path: &Path,
+    ) -> Result<u64, crate::Error>;
+
+    /// Downloads the file of a completed report into the directory `dir`,
+    /// returning the paths of the files written.
+    ///
+    /// Reports that produce several CSV files, such as `provisioning_csv`, are
+    /// delivered as a zip archive, which is unpacked into `dir`. Any other file
+    /// is written to `dir` under its own file name.
+    async fn extract_report(
+        &self,
+        report: &Report,
+        dir: &Path,
+    ) -> Result<Vec<PathBuf>, crate::Error>;
+
+    /// Runs a report to completion, extracts it into the directory `dir`, and
+    /// reads the typed rows of one of its CSV files.
+    ///
+    /// The file is picked by [`ReportRow::FILE_NAME`], so the report must
+    /// produce rows of type `T`. For example, [`rows::UserRow`] is read from
+    /// `provisioning_csv` or `sis_export_csv` with the `users` parameter set.
+    ///
+    /// # Example
+    /// ```rs
+    /// let enrollments = client
+    ///     .run_report_rows::<EnrollmentRow>(
+    ///         AccountId::new(1),
+    ///         String::from("provisioning_csv"),
+    ///         ReportParameters::new().into(),
+    ///         &ReportPollConfig::new(),
+    ///         Path::new("reports"),
+    ///     )
+    ///     .await?;
+    /// ```
+    async fn run_report_rows<T: ReportRow>(
+        &self,
+        account_id: AccountId,
+        report_type: String,
+        parameters: CreateReportForm,
+        poll: &ReportPollConfig,
+        dir: &Path,
+    ) -> Result<ReportRows<T>, crate::Error>;
 }
 
 // https://canvas.instructure.com/doc/api/account_reports.html#method.account_reports.available_reports
@@ -264,17 +545,28 @@ pub struct ReportResponse {
     pub report: String,
     pub title: String,
     /// The parameters will vary for each report
-    pub parameters: Option<serde_json::Value>,
+    pub parameters: Option<BTreeMap<String, ReportParameterDescription>>,
+}
+
+/// The description of a parameter accepted by a report.
+#[derive(Debug, Clone, Deserialize)]
+pub struct ReportParameterDescription {
+    /// Whether the report must be run with the parameter.
+    #[serde(default, deserialize_with = "lenient::required")]
+    pub required: bool,
+    /// What the parameter does.
+    #[serde(default)]
+    pub description: Option<String>,
 }
 type GetAvailableReportsByAccountResponse = Vec<ReportResponse>;
 
 // https://canvas.instructure.com/doc/api/account_reports.html#method.account_reports.create
 // CreateReportForm extends from ReportParameters
-#[derive(Serialize)]
+#[derive(Debug, Clone, Default, Serialize)]
 pub struct CreateReportForm {
-    pub enrollment_term_id: Option<u64>,
+    pub enrollment_term_id: Option<TermId>,
     pub include_deleted: Option<bool>,
-    pub course_id: Option<u64>,
+    pub course_id: Option<CourseId>,
     pub order: Option<String>,
     pub users: Option<bool>,
     pub accounts: Option<bool>,
@@ -288,9 +580,18 @@ pub struct CreateReportForm {
     pub sis_accounts_csv: Option<u64>,
     pub skip_message: Option<bool>,
     pub include_enrollment_state: Option<bool>,
-    pub start_at: Option<String>,
-    pub end_at: Option<String>,
+    #[serde(with = "crate::datetime::rfc3339::option")]
+    pub start_at: Option<DateTime>,
+    #[serde(with = "crate::datetime::rfc3339::option")]
+    pub end_at: Option<DateTime>,
+}
+/// The body of a request to create a report, which nests the parameters
+/// under `parameters`.
+#[derive(Serialize)]
+struct CreateReportBody<'a> {
+    parameters: &'a CreateReportForm,
 }
+
 impl From<ReportParameters> for CreateReportForm {
     fn from(value: ReportParameters) -> Self {
         Self {
@@ -316,78 +617,136 @@ impl From<ReportParameters> for CreateReportForm {
     }
 }
 
-type CreateReportResponse = ReportResponse;
+type CreateReportResponse = Report;
 
 // https://canvas.instructure.com/doc/api/account_reports.html#method.account_reports.index
-type GetReportsByTypeResponse = Vec<ReportResponse>;
+type GetReportsByTypeResponse = Vec<Report>;
 
 // https://canvas.instructure.com/doc/api/account_reports.html#method.account_reports.show
-type GetReportByIdResponse = ReportResponse;
+type GetReportByIdResponse = Report;
 
 // https://canvas.instructure.com/doc/api/account_reports.html#method.account_reports.destroy
-type DeleteReportResponse = ReportResponse;
+type DeleteReportResponse = Report;
 
 #[async_trait]
 impl AccountReportsExt for CanvasClient {
     async fn get_available_reports_by_account(
         &self,
-        account_id: u64,
+        account_id: AccountId,
     ) -> Result<GetAvailableReportsByAccountResponse, crate::Error> {
-        let reports = self
-            .make_query(&format!("v1/accounts/{account_id}/reports"))
-            .send()
-            .await?
-            .json()
-            .await?;
+        self.get_available_reports_by_account_stream(account_id)
+            .try_collect()
+            .await
+    }
 
-        Ok(reports)
+    fn get_available_reports_by_account_stream(
+        &self,
+        account_id: AccountId,
+    ) -> PageStream<'_, ReportResponse> {
+        self.paginate_stream(
+            self.make_query(&format!(
+                "v1/accounts/{}/reports",
+                account_id.path_segment()
+            )),
+            |page: GetAvailableReportsByAccountResponse| page,
+        )
     }
 
     async fn create_report(
         &self,
-        account_id: u64,
+        account_id: AccountId,
         report_type: String,
         parameters: CreateReportForm,
     ) -> Result<CreateReportResponse, crate::Error> {
         let report = self
-            .make_post(&format!("v1/accounts/{account_id}/reports/{report_type}"))
-            .form(&parameters)
-            .send()
-            .await?
-            .json()
+            .send(
+                self.make_post(&format!(
+                    "v1/accounts/{}/reports/{}",
+                    account_id.path_segment(),
+                    report_type
+                ))
+                .nested_form(&CreateReportBody {
+                    parameters: &parameters,
+                })?,
+            )
             .await?;
 
         Ok(report)
     }
 
+    async fn create_validated_report(
+        &self,
+        account_id: AccountId,
+        report_type: String,
+        parameters: CreateReportForm,
+    ) -> Result<CreateReportResponse, crate::Error> {
+        let available = self
+            .get_available_reports_by_account(account_id.clone())
+            .await?;
+        match available.iter().find(|report| report.report == report_type) {
+            Some(report) => report.validate(&parameters)?,
+            None => {
+                return Err(crate::Error::InvalidReportParameters {
+                    report_type,
+                    problems: vec![ReportParameterProblem::UnavailableReport],
+                })
+            }
+        }
+
+        self.create_report(account_id, report_type, parameters)
+            .await
+    }
+
+    async fn create_typed_report<R>(
+        &self,
+        account_id: AccountId,
+        report: R,
+    ) -> Result<CreateReportResponse, crate::Error>
+    where
+        R: TypedReport + Send,
+    {
+        self.create_validated_report(account_id, R::REPORT_TYPE.to_string(), report.into())
+            .await
+    }
+
     async fn get_reports_by_type(
         &self,
-        account_id: u64,
+        account_id: AccountId,
         report_type: String,
     ) -> Result<GetReportsByTypeResponse, crate::Error> {
-        let reports = self
-            .make_query(&format!("v1/accounts/{account_id}/reports/{report_type}"))
-            .send()
-            .await?
-            .json()
-            .await?;
+        self.get_reports_by_type_stream(account_id, report_type)
+            .try_collect()
+            .await
+    }
 
-        Ok(reports)
+    fn get_reports_by_type_stream(
+        &self,
+        account_id: AccountId,
+        report_type: String,
+    ) -> PageStream<'_, Report> {
+        self.paginate_stream(
+            self.make_query(&format!(
+                "v1/accounts/{}/reports/{}",
+                account_id.path_segment(),
+                report_type
+            )),
+            |page: GetReportsByTypeResponse| page,
+        )
     }
 
     async fn get_report_by_id(
         &self,
-        account_id: u64,
+        account_id: AccountId,
         report_type: String,
-        report_id: u64,
+        report_id: ReportId,
     ) -> Result<GetReportByIdResponse, crate::Error> {
         let report = self
-            .make_query(&format!(
-                "v1/accounts/{account_id}/reports/{report_type}/{report_id}"
-            ))
-            .send()
-            .await?
-            .json()
+            .send(self.make_query(&format!(
+                "v1/accounts/{}/reports/{}/{}",
+                account_id.path_segment(),
+                report_type,
+                report_id.path_segment()
+            )))
             .await?;
 
         Ok(report)
@@ -395,19 +754,315 @@ impl AccountReportsExt for CanvasClient {
 
     async fn delete_report_by_id(
         &self,
-        account_id: u64,
+        account_id: AccountId,
         report_type: String,
-        report_id: u64,
+        report_id: ReportId,
     ) -> Result<DeleteReportResponse, crate::Error> {
         let report = self
-            .make_delete(&format!(
-                "v1/accounts/{account_id}/reports/{report_type}/{report_id}"
-            ))
-            .send()
-            .await?
-            .json()
+            .send(self.make_delete(&format!(
+                "v1/accounts/{}/reports/{}/{}",
+                account_id.path_segment(),
+                report_type,
+                report_id.path_segment()
+            )))
             .await?;
 
         Ok(report)
     }
+
+    async fn apply_report_retention(
+        &self,
+        account_id: AccountId,
+        policy: &RetentionPolicy,
+    ) -> Result<Vec<Report>, crate::Error> {
+        let mut removed = Vec::new();
+
+        for available in self
+            .get_available_reports_by_account(account_id.clone())
+            .await?
+        {
+            let reports = self
+                .get_reports_by_type(account_id.clone(), available.report.clone())
+                .await?;
+
+            for report in policy.expired(&available.report, reports) {
+                if policy.dry_run() {
+                    removed.push(report);
+                    continue;
+                }
+
+                let deleted = self
+                    .delete_report_by_id(
+                        account_id.clone(),
+                        available.report.clone(),
+                        report.id().clone(),
+                    )
+                    .await?;
+                removed.push(deleted);
+            }
+        }
+
+        Ok(removed)
+    }
+
+    async fn wait_for_report(
+        &self,
+        account_id: AccountId,
+        mut report: Report,
+        poll: &ReportPollConfig,
+    ) -> Result<Report, crate::Error> {
+        let deadline = Instant::now() + poll.timeout;
+
+        loop {
+            match report.status() {
+                ReportStatus::Complete => return Ok(report),
+                ReportStatus::Error | ReportStatus::Aborted | ReportStatus::Deleted => {
+                    return Err(crate::Error::ReportFailed {
+                        report: Box::new(report),
+                    })
+                }
+                _ => {}
+            }
+
+            let now = Instant::now();
+            if now >= deadline {
+                return Err(crate::Error::ReportTimedOut {
+                    report: Box::new(report),
+                });
+            }
+            tokio::time::sleep(poll.interval.min(deadline - now)).await;
+
+            report = self
+                .get_report_by_id(
+                    account_id.clone(),
+                    report.report().to_string(),
+                    report.id().clone(),
+                )
+                .await?;
+        }
+    }
+
+    async fn run_report_to_completion(
+        &self,
+        account_id: AccountId,
+        report_type: String,
+        parameters: CreateReportForm,
+        poll: &ReportPollConfig,
+    ) -> Result<Report, crate::Error> {
+        let report = self
+            .create_report(account_id.clone(), report_type, parameters)
+            .await?;
+
+        self.wait_for_report(account_id, report, poll).await
+    }
+
+    async fn download_report<W>(&self, report: &Report, writer: &mut W) -> Result<u64, crate::Error>
+    where
+        W: AsyncWrite + Unpin + Send + ?Sized,
+    {
+        let url = report
+            .attachment()
+            .map(File::url)
+            .or_else(|| report.file_url())
+            .ok_or_else(|| crate::Error::MissingReportFile {
+                report: Box::new(report.clone()),
+            })?;
+
+        let mut response = self.execute(self.make_download(url)).await?;
+        let mut written = 0;
+        while let Some(chunk) = response.chunk().await? {
+            writer
+                .write_all(&chunk)
+                .await
+                .map_err(|e| crate::Error::Io { source: e })?;
+            written += chunk.len() as u64;
+        }
+        writer
+            .flush()
+            .await
+            .map_err(|e| crate::Error::Io { source: e })?;
+
+        Ok(written)
+    }
+
+    async fn download_report_to_path(
+        &self,
+        report: &Report,
+        path: &Path,
+    ) -> Result<u64, crate::Error> {
+        let mut file = tokio::fs::File::create(path)
+            .await
+            .map_err(|e| crate::Error::Io { source: e })?;
+
+        self.download_report(report, &mut file).await
+    }
+
+    async fn extract_report(
+        &self,
+        report: &Report,
+        dir: &Path,
+    ) -> Result<Vec<PathBuf>, crate::Error> {
+        tokio::fs::create_dir_all(dir)
+            .await
+            .map_err(|e| crate::Error::Io { source: e })?;
+
+        let file_name = report
+            .attachment()
+            .and_then(|attachment| Path::new(attachment.filename()).file_name())
+            .map_or_else(
+                || format!("{}.csv", report.report()).into(),
+                |file_name| file_name.to_owned(),
+            );
+        let path = dir.join(file_name);
+        self.download_report_to_path(report, &path).await?;
+
+        if !is_zip(report, &path) {
+            return Ok(vec![path]);
+        }
+
+        let dir = dir.to_owned();
+        let extracted = tokio::task::spawn_blocking(move || unzip(&path, &dir))
+            .await
+            .map_err(|e| crate::Error::Io {
+                source: std::io::Error::other(e),
+            })??;
+
+        Ok(extracted)
+    }
+
+    async fn run_report_rows<T: ReportRow>(
+        &self,
+        account_id: AccountId,
+        report_type: String,
+        parameters: CreateReportForm,
+        poll: &ReportPollConfig,
+        dir: &Path,
+    ) -> Result<ReportRows<T>, crate::Error> {
+        let report = self
+            .run_report_to_completion(account_id, report_type, parameters, poll)
+            .await?;
+        let paths = self.extract_report(&report, dir).await?;
+
+        ReportRows::from_extracted(&paths)
+    }
+}
+
+/// Whether the downloaded file of a report at `path` is a zip archive.
+fn is_zip(report: &Report, path: &Path) -> bool {
+    let zip_content_type = report.attachment().is_some_and(|attachment| {
+        matches!(
+            attachment.content_type(),
+            "application/zip" | "application/x-zip-compressed"
+        )
+    });
+
+    zip_content_type || path.extension().is_some_and(|extension| extension == "zip")
+}
+
+/// Unpacks the zip archive at `path` into `dir` and removes it, returning the
+/// paths of the unpacked files.
+fn unzip(path: &Path, dir: &Path) -> Result<Vec<PathBuf>, crate::Error> {
+    let file = std::fs::File::open(path).map_err(|e| crate::Error::Io { source: e })?;
+    let mut archive =
+        zip::ZipArchive::new(file).map_err(|e| crate::Error::ReportArchive { source: e })?;
+
+    let mut extracted = Vec::with_capacity(archive.len());
+    for index in 0..archive.len() {
+        let mut entry = archive
+            .by_index(index)
+            .map_err(|e| crate::Error::ReportArchive { source: e })?;
+        // skips directories and entries that would be written outside of `dir`
+        let Some(name) = entry.enclosed_name().map(Path::to_owned) else {
+            continue;
+        };
+        if entry.is_dir() {
+            continue;
+        }
+
+        let entry_path = dir.join(name);
+        if let Some(parent) = entry_path.parent() {
+            std::fs::create_dir_all(parent).map_err(|e| crate::Error::Io { source: e })?;
+        }
+        let mut file =
+            std::fs::File::create(&entry_path).map_err(|e| crate::Error::Io { source: e })?;
+        std::io::copy(&mut entry, &mut file).map_err(|e| crate::Error::Io { source: e })?;
+
+        extracted.push(entry_path);
+    }
+
+    std::fs::remove_file(path).map_err(|e| crate::Error::Io { source: e })?;
+
+    Ok(extracted)
+}
+
+/// Deserializers for report parameters, which Canvas returns as they were
+/// submitted in the form body, and for the flags of report CSV files.
+mod lenient {
+    use serde::{de::DeserializeOwned, Deserialize, Deserializer};
+    use serde_json::Value;
+
+    /// Deserializes a value that may have been submitted as a string, treating
+    /// `null` and `""` as missing.
+    pub(super) fn optional<'de, D, T>(deserializer: D) -> Result<Option<T>, D::Error>
+    where
+        D: Deserializer<'de>,
+        T: DeserializeOwned,
+    {
+        let value = match Value::deserialize(deserializer)? {
+            Value::Null => return Ok(None),
+            Value::String(s) => match s.as_str() {
+                "" => return Ok(None),
+                "true" => Value::Bool(true),
+                "false" => Value::Bool(false),
+                _ => s
+                    .parse::<u64>()
+                    .map_or(Value::String(s), |number| Value::Number(number.into())),
+            },
+            value => value,
+        };
+
+        // flags are also submitted as `1` and `0`
+        let flag = match value.as_u64() {
+            Some(1) => Some(Value::Bool(true)),
+            Some(0) => Some(Value::Bool(false)),
+            _ => None,
+        };
+
+        T::deserialize(value)
+            .or_else(|e| flag.map_or(Err(e), T::deserialize))
+            .map(Some)
+            .map_err(serde::de::Error::custom)
+    }
+
+    /// The same as [`optional`], defaulting a missing value.
+    pub(super) fn required<'de, D, T>(deserializer: D) -> Result<T, D::Error>
+    where
+        D: Deserializer<'de>,
+        T: DeserializeOwned + Default,
+    {
+        optional(deserializer).map(Option::unwrap_or_default)
+    }
+
+    /// The same as [`optional`], for timestamps. A date without a time, as
+    /// submitted by date pickers, is read as midnight UTC.
+    pub(super) fn datetime<'de, D>(deserializer: D) -> Result<Option<crate::DateTime>, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        let Some(s) = Option::<String>::deserialize(deserializer)? else {
+            return Ok(None);
+        };
+
+        let s = s.trim();
+        if s.is_empty() {
+            return Ok(None);
+        }
+        let datetime = match crate::datetime::parse(s) {
+            Ok(datetime) => datetime,
+            Err(e) => crate::datetime::parse(&format!("{s}T00:00:00Z"))
+                .map_err(|_| serde::de::Error::custom(e))?,
+        };
+
+        Ok(Some(datetime))
+    }
 }
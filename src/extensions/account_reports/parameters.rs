@@ -0,0 +1,455 @@
+//! Typed parameters for the built-in account reports, and validation of
+//! report parameters against the descriptions Canvas returns for them.
+//!
+//! # Example
+//! ```rs
+//! let report = client
+//!     .create_typed_report(
+//!         AccountId::new(1),
+//!         ProvisioningReport::new()
+//!             .set_users(true)
+//!             .set_enrollments(true)
+//!             .set_term(TermId::sis("2024-FA")),
+//!     )
+//!     .await?;
+//! ```
+
+use thiserror::Error;
+
+use super::{CreateReportForm, ReportResponse};
+use crate::{CourseId, DateTime, TermId};
+
+/// The longest time range Canvas accepts between `start_at` and `end_at`.
+const MAX_WINDOW_SECONDS: i64 = 14 * 24 * 60 * 60;
+
+/// The parameters of a specific report type.
+pub trait TypedReport: Into<CreateReportForm> {
+    /// The report type, such as `provisioning_csv`.
+    const REPORT_TYPE: &'static str;
+}
+
+/// A problem with the parameters of a report, found by
+/// [`ReportResponse::validate`].
+#[derive(Error, Debug, Clone, PartialEq, Eq)]
+pub enum ReportParameterProblem {
+    /// The report is not available to the account.
+    #[error("the report is not available to the account")]
+    UnavailableReport,
+
+    /// A parameter was set that the report does not accept.
+    #[error("unknown parameter {name}")]
+    UnknownParameter { name: String },
+
+    /// A parameter the report requires was not set.
+    #[error("missing required parameter {name}")]
+    MissingParameter { name: String },
+
+    /// A report that produces a CSV file per kind of object was not asked for
+    /// any of them.
+    #[error("no files were selected")]
+    NoFilesSelected,
+
+    /// `end_at` is not after `start_at`.
+    #[error("end_at is not after start_at")]
+    EndBeforeStart,
+
+    /// `start_at` and `end_at` are more than two weeks apart.
+    #[error("start_at and end_at are more than two weeks apart")]
+    WindowTooLong,
+}
+
+/// The flags that select the CSV files of the `provisioning_csv` and
+/// `sis_export_csv` reports.
+fn selected_files(form: &CreateReportForm) -> [Option<bool>; 8] {
+    [
+        form.users,
+        form.accounts,
+        form.terms,
+        form.courses,
+        form.sections,
+        form.enrollments,
+        form.groups,
+        form.xlist,
+    ]
+}
+
+impl ReportResponse {
+    /// Checks `form` against the parameters this report accepts, before it is
+    /// submitted with [`AccountReportsExt::create_report`](super::AccountReportsExt::create_report).
+    ///
+    /// Fails with [`crate::Error::InvalidReportParameters`] if a parameter is
+    /// not accepted by the report, a required parameter is missing, no files
+    /// are selected for a provisioning or SIS export report, or `start_at` and
+    /// `end_at` are not an up to two week window.
+    pub fn validate(&self, form: &CreateReportForm) -> Result<(), crate::Error> {
+        let mut problems = Vec::new();
+
+        let set = match serde_json::to_value(form) {
+            Ok(serde_json::Value::Object(fields)) => fields
+                .into_iter()
+                .filter(|(_, value)| !value.is_null())
+                .map(|(name, _)| name)
+                .collect(),
+            _ => Vec::new(),
+        };
+
+        if let Some(parameters) = &self.parameters {
+            // `skip_message` is accepted by every report
+            problems.extend(
+                set.iter()
+                    .filter(|name| *name != "skip_message" && !parameters.contains_key(*name))
+                    .map(|name| ReportParameterProblem::UnknownParameter { name: name.clone() }),
+            );
+            problems.extend(
+                parameters
+                    .iter()
+                    .filter(|(name, description)| description.required && !set.contains(name))
+                    .map(|(name, _)| ReportParameterProblem::MissingParameter {
+                        name: name.clone(),
+                    }),
+            );
+        }
+
+        if matches!(self.report.as_str(), "provisioning_csv" | "sis_export_csv")
+            && !selected_files(form).contains(&Some(true))
+        {
+            problems.push(ReportParameterProblem::NoFilesSelected);
+        }
+
+        if let (Some(start_at), Some(end_at)) = (form.start_at, form.end_at) {
+            let window = crate::datetime::seconds_between(&start_at, &end_at);
+            if window <= 0 {
+                problems.push(ReportParameterProblem::EndBeforeStart);
+            } else if window > MAX_WINDOW_SECONDS {
+                problems.push(ReportParameterProblem::WindowTooLong);
+            }
+        }
+
+        if problems.is_empty() {
+            Ok(())
+        } else {
+            Err(crate::Error::InvalidReportParameters {
+                report_type: self.report.clone(),
+                problems,
+            })
+        }
+    }
+}
+
+/// The parameters of the `provisioning_csv` report, which exports the objects
+/// of the account, including those not created through SIS imports.
+#[derive(Debug, Clone, Default)]
+pub struct ProvisioningReport {
+    form: CreateReportForm,
+}
+
+/// The parameters of the `sis_export_csv` report, which exports the objects
+/// of the account created through SIS imports, in the SIS import format.
+#[derive(Debug, Clone, Default)]
+pub struct SisExportReport {
+    form: CreateReportForm,
+}
+
+macro_rules! file_selection {
+    ($name:ident) => {
+        impl $name {
+            #[doc = concat!("Creates a new [`", stringify!($name), "`] with no files selected.")]
+            pub fn new() -> Self {
+                Self::default()
+            }
+
+            /// Sets whether `users.csv` is included.
+            pub fn set_users(mut self, users: bool) -> Self {
+                self.form.users = Some(users);
+
+                self
+            }
+
+            /// Sets whether `accounts.csv` is included.
+            pub fn set_accounts(mut self, accounts: bool) -> Self {
+                self.form.accounts = Some(accounts);
+
+                self
+            }
+
+            /// Sets whether `terms.csv` is included.
+            pub fn set_terms(mut self, terms: bool) -> Self {
+                self.form.terms = Some(terms);
+
+                self
+            }
+
+            /// Sets whether `courses.csv` is included.
+            pub fn set_courses(mut self, courses: bool) -> Self {
+                self.form.courses = Some(courses);
+
+                self
+            }
+
+            /// Sets whether `sections.csv` is included.
+            pub fn set_sections(mut self, sections: bool) -> Self {
+                self.form.sections = Some(sections);
+
+                self
+            }
+
+            /// Sets whether `enrollments.csv` is included.
+            pub fn set_enrollments(mut self, enrollments: bool) -> Self {
+                self.form.enrollments = Some(enrollments);
+
+                self
+            }
+
+            /// Sets whether `groups.csv` is included.
+            pub fn set_groups(mut self, groups: bool) -> Self {
+                self.form.groups = Some(groups);
+
+                self
+            }
+
+            /// Sets whether `xlist.csv`, the crosslisted sections, is
+            /// included.
+            pub fn set_xlist(mut self, xlist: bool) -> Self {
+                self.form.xlist = Some(xlist);
+
+                self
+            }
+
+            /// Limits the report to the objects of a term.
+            pub fn set_term(mut self, term_id: TermId) -> Self {
+                self.form.enrollment_term_id = Some(term_id);
+
+                self
+            }
+
+            /// Sets whether deleted objects are included.
+            pub fn set_include_deleted(mut self, include_deleted: bool) -> Self {
+                self.form.include_deleted = Some(include_deleted);
+
+                self
+            }
+        }
+
+        impl From<$name> for CreateReportForm {
+            fn from(value: $name) -> Self {
+                value.form
+            }
+        }
+    };
+}
+
+file_selection!(ProvisioningReport);
+file_selection!(SisExportReport);
+
+impl TypedReport for ProvisioningReport {
+    const REPORT_TYPE: &'static str = "provisioning_csv";
+}
+
+impl TypedReport for SisExportReport {
+    const REPORT_TYPE: &'static str = "sis_export_csv";
+}
+
+/// The parameters of the `grade_export_csv` report, which exports the current
+/// and final scores of every enrollment.
+#[derive(Debug, Clone, Default)]
+pub struct GradeExportReport {
+    form: CreateReportForm,
+}
+
+impl GradeExportReport {
+    /// Creates a new [`GradeExportReport`] for the default term.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Sets the term to export grades from.
+    pub fn set_term(mut self, term_id: TermId) -> Self {
+        self.form.enrollment_term_id = Some(term_id);
+
+        self
+    }
+
+    /// Sets whether deleted enrollments are included.
+    pub fn set_include_deleted(mut self, include_deleted: bool) -> Self {
+        self.form.include_deleted = Some(include_deleted);
+
+        self
+    }
+}
+
+impl From<GradeExportReport> for CreateReportForm {
+    fn from(value: GradeExportReport) -> Self {
+        value.form
+    }
+}
+
+impl TypedReport for GradeExportReport {
+    const REPORT_TYPE: &'static str = "grade_export_csv";
+}
+
+/// The parameters of the `last_user_access_csv` report, which exports when
+/// each user last accessed Canvas.
+#[derive(Debug, Clone, Default)]
+pub struct LastUserAccessReport {
+    form: CreateReportForm,
+}
+
+impl LastUserAccessReport {
+    /// Creates a new [`LastUserAccessReport`] for every user of the account.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Limits the report to the users enrolled in a term.
+    pub fn set_term(mut self, term_id: TermId) -> Self {
+        self.form.enrollment_term_id = Some(term_id);
+
+        self
+    }
+
+    /// Limits the report to the users enrolled in a course.
+    pub fn set_course(mut self, course_id: CourseId) -> Self {
+        self.form.course_id = Some(course_id);
+
+        self
+    }
+
+    /// Sets whether deleted users are included.
+    pub fn set_include_deleted(mut self, include_deleted: bool) -> Self {
+        self.form.include_deleted = Some(include_deleted);
+
+        self
+    }
+}
+
+impl From<LastUserAccessReport> for CreateReportForm {
+    fn from(value: LastUserAccessReport) -> Self {
+        value.form
+    }
+}
+
+impl TypedReport for LastUserAccessReport {
+    const REPORT_TYPE: &'static str = "last_user_access_csv";
+}
+
+/// The parameters of the `unpublished_courses_csv` report, which exports the
+/// courses that have not been published.
+#[derive(Debug, Clone, Default)]
+pub struct UnpublishedCoursesReport {
+    form: CreateReportForm,
+}
+
+impl UnpublishedCoursesReport {
+    /// Creates a new [`UnpublishedCoursesReport`] for every term.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Limits the report to the courses of a term.
+    pub fn set_term(mut self, term_id: TermId) -> Self {
+        self.form.enrollment_term_id = Some(term_id);
+
+        self
+    }
+}
+
+impl From<UnpublishedCoursesReport> for CreateReportForm {
+    fn from(value: UnpublishedCoursesReport) -> Self {
+        value.form
+    }
+}
+
+impl TypedReport for UnpublishedCoursesReport {
+    const REPORT_TYPE: &'static str = "unpublished_courses_csv";
+}
+
+/// The parameters of the `student_assignment_outcome_map_csv` report, which
+/// exports the outcome results of every student's assignments.
+#[derive(Debug, Clone, Default)]
+pub struct StudentAssignmentOutcomeMapReport {
+    form: CreateReportForm,
+}
+
+impl StudentAssignmentOutcomeMapReport {
+    /// Creates a new [`StudentAssignmentOutcomeMapReport`] for every term.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Limits the report to the courses of a term.
+    pub fn set_term(mut self, term_id: TermId) -> Self {
+        self.form.enrollment_term_id = Some(term_id);
+
+        self
+    }
+
+    /// Sets whether deleted objects are included.
+    pub fn set_include_deleted(mut self, include_deleted: bool) -> Self {
+        self.form.include_deleted = Some(include_deleted);
+
+        self
+    }
+}
+
+impl From<StudentAssignmentOutcomeMapReport> for CreateReportForm {
+    fn from(value: StudentAssignmentOutcomeMapReport) -> Self {
+        value.form
+    }
+}
+
+impl TypedReport for StudentAssignmentOutcomeMapReport {
+    const REPORT_TYPE: &'static str = "student_assignment_outcome_map_csv";
+}
+
+/// The parameters of the `students_with_no_submissions_csv` report, which
+/// exports the students who submitted nothing between `start_at` and
+/// `end_at`, at most two weeks apart.
+#[derive(Debug, Clone)]
+pub struct NoSubmissionsReport {
+    form: CreateReportForm,
+}
+
+impl NoSubmissionsReport {
+    /// Creates a new [`NoSubmissionsReport`] for the window from `start_at` to
+    /// `end_at`.
+    pub fn new(start_at: DateTime, end_at: DateTime) -> Self {
+        Self {
+            form: CreateReportForm {
+                start_at: Some(start_at),
+                end_at: Some(end_at),
+                ..Default::default()
+            },
+        }
+    }
+
+    /// Limits the report to the students enrolled in a term.
+    pub fn set_term(mut self, term_id: TermId) -> Self {
+        self.form.enrollment_term_id = Some(term_id);
+
+        self
+    }
+
+    /// Limits the report to the students enrolled in a course.
+    pub fn set_course(mut self, course_id: CourseId) -> Self {
+        self.form.course_id = Some(course_id);
+
+        self
+    }
+
+    /// Sets whether the state of each enrollment is included.
+    pub fn set_include_enrollment_state(mut self, include_enrollment_state: bool) -> Self {
+        self.form.include_enrollment_state = Some(include_enrollment_state);
+
+        self
+    }
+}
+
+impl From<NoSubmissionsReport> for CreateReportForm {
+    fn from(value: NoSubmissionsReport) -> Self {
+        value.form
+    }
+}
+
+impl TypedReport for NoSubmissionsReport {
+    const REPORT_TYPE: &'static str = "students_with_no_submissions_csv";
+}
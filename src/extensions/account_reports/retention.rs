@@ -0,0 +1,124 @@
+//! Retention policies for the reports of an account, so that old report
+//! attachments do not accumulate in the account's storage.
+//!
+//! # Example
+//! ```rs
+//! let policy = RetentionPolicy::new()
+//!     .set_default_rule(RetentionRule::NewerThan(Duration::from_secs(30 * 24 * 60 * 60)))
+//!     .set_rule("provisioning_csv", RetentionRule::KeepLast(5))
+//!     .set_dry_run(true);
+//! for report in client.apply_report_retention(AccountId::new(1), &policy).await? {
+//!     println!("would delete {} {}", report.report(), report.id());
+//! }
+//! ```
+
+use std::{collections::HashMap, time::Duration};
+
+use super::{Report, ReportStatus};
+
+/// Which finished reports of a type to keep.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum RetentionRule {
+    /// Keep every report.
+    KeepAll,
+    /// Keep the given number of most recently created reports.
+    KeepLast(usize),
+    /// Keep the reports created within the given duration.
+    NewerThan(Duration),
+}
+
+/// Decides which reports [`AccountReportsExt::apply_report_retention`](super::AccountReportsExt::apply_report_retention)
+/// deletes.
+///
+/// Reports that have not finished, or whose creation time is unknown, are
+/// always kept. Report types without a rule of their own follow the default
+/// rule, which keeps every report unless set.
+#[derive(Debug, Clone)]
+pub struct RetentionPolicy {
+    /// The rule for report types without a rule of their own.
+    default_rule: RetentionRule,
+    /// The rule for each report type.
+    rules: HashMap<String, RetentionRule>,
+    /// Whether to only return the reports that would be deleted.
+    dry_run: bool,
+}
+
+impl Default for RetentionPolicy {
+    fn default() -> Self {
+        Self {
+            default_rule: RetentionRule::KeepAll,
+            rules: HashMap::new(),
+            dry_run: false,
+        }
+    }
+}
+
+impl RetentionPolicy {
+    /// Creates a new [`RetentionPolicy`] that keeps every report.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Sets the rule for report types without a rule of their own. Defaults
+    /// to [`RetentionRule::KeepAll`].
+    pub fn set_default_rule(mut self, rule: RetentionRule) -> Self {
+        self.default_rule = rule;
+
+        self
+    }
+
+    /// Sets the rule for the reports of `report_type`, such as
+    /// `provisioning_csv`.
+    pub fn set_rule(mut self, report_type: impl Into<String>, rule: RetentionRule) -> Self {
+        self.rules.insert(report_type.into(), rule);
+
+        self
+    }
+
+    /// Sets whether reports are only listed rather than deleted. Defaults to
+    /// false.
+    pub fn set_dry_run(mut self, dry_run: bool) -> Self {
+        self.dry_run = dry_run;
+
+        self
+    }
+
+    /// Whether reports are only listed rather than deleted.
+    pub fn dry_run(&self) -> bool {
+        self.dry_run
+    }
+
+    /// The rule for the reports of `report_type`.
+    pub fn rule(&self, report_type: &str) -> &RetentionRule {
+        self.rules.get(report_type).unwrap_or(&self.default_rule)
+    }
+
+    /// The reports among `reports`, all of `report_type`, that the policy
+    /// removes.
+    pub(crate) fn expired(&self, report_type: &str, mut reports: Vec<Report>) -> Vec<Report> {
+        reports.retain(|report| {
+            report.status().is_finished()
+                && report.status() != ReportStatus::Deleted
+                && report.created_at().is_some()
+        });
+        // newest first, so that the kept reports are a prefix
+        reports.sort_by_key(|report| std::cmp::Reverse(report.created_at()));
+
+        match self.rule(report_type) {
+            RetentionRule::KeepAll => Vec::new(),
+            RetentionRule::KeepLast(count) => reports.into_iter().skip(*count).collect(),
+            RetentionRule::NewerThan(age) => {
+                let now = crate::datetime::now();
+                let age = i64::try_from(age.as_secs()).unwrap_or(i64::MAX);
+                reports
+                    .into_iter()
+                    .filter(|report| {
+                        report.created_at().is_some_and(|created_at| {
+                            crate::datetime::seconds_between(&created_at, &now) > age
+                        })
+                    })
+                    .collect()
+            }
+        }
+    }
+}
@@ -0,0 +1,423 @@
+//! Typed rows for the CSV files produced by the built-in account reports.
+//!
+//! Each row type reads one CSV file of a report. Columns that a report does
+//! not include, such as the Canvas IDs missing from `sis_export_csv`, are left
+//! as `None`, as are empty cells, and columns unknown to the row type are
+//! ignored. Flags are read from `true`/`false` or `1`/`0`, and dates without a
+//! time as midnight UTC.
+//!
+//! # Example
+//! ```rs
+//! let users = client
+//!     .run_report_rows::<UserRow>(
+//!         AccountId::new(1),
+//!         String::from("provisioning_csv"),
+//!         ReportParameters::new().into(),
+//!         &ReportPollConfig::new(),
+//!         Path::new("reports"),
+//!     )
+//!     .await?;
+//! for user in users {
+//!     println!("{:?}", user?.login_id);
+//! }
+//! ```
+
+use std::{
+    fs::File,
+    io::Read,
+    marker::PhantomData,
+    path::{Path, PathBuf},
+};
+
+use serde::{de::DeserializeOwned, Deserialize};
+
+use super::lenient;
+use crate::{
+    AccountId, AssignmentId, CourseId, DateTime, OutcomeId, RoleId, SectionId, TermId, UserId,
+};
+
+/// A row of a CSV file produced by an account report.
+pub trait ReportRow: DeserializeOwned {
+    /// The name of the file the rows are read from, for reports that produce a
+    /// zip archive of several CSV files. `None` for reports that produce a
+    /// single CSV file.
+    const FILE_NAME: Option<&'static str>;
+}
+
+/// An iterator over the typed rows of a report CSV file.
+///
+/// Rows are parsed as the iterator is advanced, so only a single row is held
+/// in memory at a time.
+pub struct ReportRows<T, R = File> {
+    records: csv::DeserializeRecordsIntoIter<R, T>,
+    _row: PhantomData<T>,
+}
+
+impl<T: ReportRow, R: Read> ReportRows<T, R> {
+    /// Reads rows from a CSV `reader`, starting with its header.
+    pub fn from_reader(reader: R) -> Self {
+        Self {
+            records: csv::Reader::from_reader(reader).into_deserialize(),
+            _row: PhantomData,
+        }
+    }
+}
+
+impl<T: ReportRow> ReportRows<T> {
+    /// Reads rows from the CSV file at `path`.
+    pub fn from_path(path: &Path) -> Result<Self, crate::Error> {
+        let file = File::open(path).map_err(|e| crate::Error::Io { source: e })?;
+
+        Ok(Self::from_reader(file))
+    }
+
+    /// Reads rows from the file of `T` among `paths`, the files of a report
+    /// returned by [`AccountReportsExt::extract_report`](super::AccountReportsExt::extract_report).
+    ///
+    /// Fails with [`crate::Error::MissingReportCsv`] if the report did not
+    /// produce the file, for example because the parameter that includes it
+    /// was not set.
+    pub fn from_extracted(paths: &[PathBuf]) -> Result<Self, crate::Error> {
+        let path = match T::FILE_NAME {
+            Some(file_name) => paths
+                .iter()
+                .find(|path| path.file_name().is_some_and(|name| name == file_name)),
+            None => paths.first().filter(|_| paths.len() == 1),
+        };
+
+        let path = path.ok_or_else(|| crate::Error::MissingReportCsv {
+            file_name: T::FILE_NAME.unwrap_or("a single CSV file").to_string(),
+        })?;
+
+        Self::from_path(path)
+    }
+}
+
+impl<T: ReportRow, R: Read> Iterator for ReportRows<T, R> {
+    type Item = Result<T, crate::Error>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.records
+            .next()
+            .map(|record| record.map_err(|e| crate::Error::Csv { source: e }))
+    }
+}
+
+/// A row of `users.csv`, produced by `provisioning_csv` and `sis_export_csv`
+/// with the `users` parameter.
+#[derive(Debug, Clone, Deserialize)]
+pub struct UserRow {
+    pub canvas_user_id: Option<UserId>,
+    /// The SIS ID of the user.
+    pub user_id: Option<String>,
+    pub integration_id: Option<String>,
+    pub authentication_provider_id: Option<String>,
+    pub login_id: Option<String>,
+    pub first_name: Option<String>,
+    pub last_name: Option<String>,
+    pub full_name: Option<String>,
+    pub sortable_name: Option<String>,
+    pub short_name: Option<String>,
+    pub email: Option<String>,
+    pub status: Option<String>,
+    #[serde(default, deserialize_with = "lenient::optional")]
+    pub created_by_sis: Option<bool>,
+}
+
+impl ReportRow for UserRow {
+    const FILE_NAME: Option<&'static str> = Some("users.csv");
+}
+
+/// A row of `accounts.csv`, produced by `provisioning_csv` and
+/// `sis_export_csv` with the `accounts` parameter.
+#[derive(Debug, Clone, Deserialize)]
+pub struct AccountRow {
+    pub canvas_account_id: Option<AccountId>,
+    /// The SIS ID of the account.
+    pub account_id: Option<String>,
+    pub canvas_parent_id: Option<AccountId>,
+    /// The SIS ID of the parent account.
+    pub parent_account_id: Option<String>,
+    pub name: Option<String>,
+    pub status: Option<String>,
+    #[serde(default, deserialize_with = "lenient::optional")]
+    pub created_by_sis: Option<bool>,
+}
+
+impl ReportRow for AccountRow {
+    const FILE_NAME: Option<&'static str> = Some("accounts.csv");
+}
+
+/// A row of `terms.csv`, produced by `provisioning_csv` and `sis_export_csv`
+/// with the `terms` parameter.
+#[derive(Debug, Clone, Deserialize)]
+pub struct TermRow {
+    pub canvas_term_id: Option<TermId>,
+    /// The SIS ID of the term.
+    pub term_id: Option<String>,
+    pub name: Option<String>,
+    pub status: Option<String>,
+    #[serde(default, deserialize_with = "lenient::datetime")]
+    pub start_date: Option<DateTime>,
+    #[serde(default, deserialize_with = "lenient::datetime")]
+    pub end_date: Option<DateTime>,
+    #[serde(default, deserialize_with = "lenient::optional")]
+    pub created_by_sis: Option<bool>,
+}
+
+impl ReportRow for TermRow {
+    const FILE_NAME: Option<&'static str> = Some("terms.csv");
+}
+
+/// A row of `courses.csv`, produced by `provisioning_csv` and
+/// `sis_export_csv` with the `courses` parameter.
+#[derive(Debug, Clone, Deserialize)]
+pub struct CourseRow {
+    pub canvas_course_id: Option<CourseId>,
+    /// The SIS ID of the course.
+    pub course_id: Option<String>,
+    pub integration_id: Option<String>,
+    pub short_name: Option<String>,
+    pub long_name: Option<String>,
+    pub canvas_account_id: Option<AccountId>,
+    /// The SIS ID of the account.
+    pub account_id: Option<String>,
+    pub canvas_term_id: Option<TermId>,
+    /// The SIS ID of the term.
+    pub term_id: Option<String>,
+    pub status: Option<String>,
+    #[serde(default, deserialize_with = "lenient::datetime")]
+    pub start_date: Option<DateTime>,
+    #[serde(default, deserialize_with = "lenient::datetime")]
+    pub end_date: Option<DateTime>,
+    pub course_format: Option<String>,
+    pub blueprint_course_id: Option<String>,
+    #[serde(default, deserialize_with = "lenient::optional")]
+    pub created_by_sis: Option<bool>,
+}
+
+impl ReportRow for CourseRow {
+    const FILE_NAME: Option<&'static str> = Some("courses.csv");
+}
+
+/// A row of `sections.csv`, produced by `provisioning_csv` and
+/// `sis_export_csv` with the `sections` parameter.
+#[derive(Debug, Clone, Deserialize)]
+pub struct SectionRow {
+    pub canvas_section_id: Option<SectionId>,
+    /// The SIS ID of the section.
+    pub section_id: Option<String>,
+    pub canvas_course_id: Option<CourseId>,
+    /// The SIS ID of the course.
+    pub course_id: Option<String>,
+    pub integration_id: Option<String>,
+    pub name: Option<String>,
+    pub status: Option<String>,
+    #[serde(default, deserialize_with = "lenient::datetime")]
+    pub start_date: Option<DateTime>,
+    #[serde(default, deserialize_with = "lenient::datetime")]
+    pub end_date: Option<DateTime>,
+    pub canvas_account_id: Option<AccountId>,
+    /// The SIS ID of the account.
+    pub account_id: Option<String>,
+    #[serde(default, deserialize_with = "lenient::optional")]
+    pub created_by_sis: Option<bool>,
+}
+
+impl ReportRow for SectionRow {
+    const FILE_NAME: Option<&'static str> = Some("sections.csv");
+}
+
+/// A row of `enrollments.csv`, produced by `provisioning_csv` and
+/// `sis_export_csv` with the `enrollments` parameter.
+#[derive(Debug, Clone, Deserialize)]
+pub struct EnrollmentRow {
+    pub canvas_course_id: Option<CourseId>,
+    /// The SIS ID of the course.
+    pub course_id: Option<String>,
+    pub canvas_user_id: Option<UserId>,
+    /// The SIS ID of the user.
+    pub user_id: Option<String>,
+    pub role: Option<String>,
+    pub role_id: Option<RoleId>,
+    pub canvas_section_id: Option<SectionId>,
+    /// The SIS ID of the section.
+    pub section_id: Option<String>,
+    pub status: Option<String>,
+    pub canvas_associated_user_id: Option<UserId>,
+    /// The SIS ID of the observed user.
+    pub associated_user_id: Option<String>,
+    #[serde(default, deserialize_with = "lenient::optional")]
+    pub created_by_sis: Option<bool>,
+    pub base_role_type: Option<String>,
+    #[serde(default, deserialize_with = "lenient::optional")]
+    pub limit_section_privileges: Option<bool>,
+}
+
+impl ReportRow for EnrollmentRow {
+    const FILE_NAME: Option<&'static str> = Some("enrollments.csv");
+}
+
+/// A row of the CSV file produced by `grade_export_csv`.
+#[derive(Debug, Clone, Deserialize)]
+pub struct GradeExportRow {
+    #[serde(rename = "student name")]
+    pub student_name: Option<String>,
+    #[serde(rename = "student id")]
+    pub student_id: Option<UserId>,
+    #[serde(rename = "student sis")]
+    pub student_sis_id: Option<String>,
+    #[serde(rename = "course")]
+    pub course_name: Option<String>,
+    #[serde(rename = "course id")]
+    pub course_id: Option<CourseId>,
+    #[serde(rename = "course sis")]
+    pub course_sis_id: Option<String>,
+    #[serde(rename = "section")]
+    pub section_name: Option<String>,
+    #[serde(rename = "section id")]
+    pub section_id: Option<SectionId>,
+    #[serde(rename = "section sis")]
+    pub section_sis_id: Option<String>,
+    #[serde(rename = "term")]
+    pub term_name: Option<String>,
+    #[serde(rename = "term id")]
+    pub term_id: Option<TermId>,
+    #[serde(rename = "term sis")]
+    pub term_sis_id: Option<String>,
+    #[serde(rename = "current score")]
+    pub current_score: Option<f64>,
+    #[serde(rename = "final score")]
+    pub final_score: Option<f64>,
+    #[serde(rename = "enrollment state")]
+    pub enrollment_state: Option<String>,
+    #[serde(default, rename = "unposted current score")]
+    pub unposted_current_score: Option<f64>,
+    #[serde(default, rename = "unposted final score")]
+    pub unposted_final_score: Option<f64>,
+    #[serde(default, rename = "override score")]
+    pub override_score: Option<f64>,
+}
+
+impl ReportRow for GradeExportRow {
+    const FILE_NAME: Option<&'static str> = None;
+}
+
+/// A row of the CSV file produced by `last_user_access_csv`.
+#[derive(Debug, Clone, Deserialize)]
+pub struct LastUserAccessRow {
+    #[serde(rename = "user id")]
+    pub user_id: Option<UserId>,
+    #[serde(rename = "user sis id")]
+    pub user_sis_id: Option<String>,
+    #[serde(rename = "user name")]
+    pub user_name: Option<String>,
+    #[serde(
+        default,
+        rename = "last access at",
+        deserialize_with = "lenient::datetime"
+    )]
+    pub last_access_at: Option<DateTime>,
+    #[serde(default, rename = "last ip")]
+    pub last_ip: Option<String>,
+}
+
+impl ReportRow for LastUserAccessRow {
+    const FILE_NAME: Option<&'static str> = None;
+}
+
+/// A row of the CSV file produced by `unpublished_courses_csv`.
+#[derive(Debug, Clone, Deserialize)]
+pub struct UnpublishedCourseRow {
+    #[serde(rename = "course id")]
+    pub course_id: Option<CourseId>,
+    #[serde(rename = "course sis id")]
+    pub course_sis_id: Option<String>,
+    #[serde(rename = "short name")]
+    pub short_name: Option<String>,
+    #[serde(rename = "long name")]
+    pub long_name: Option<String>,
+    #[serde(
+        default,
+        rename = "start date",
+        alias = "start at",
+        deserialize_with = "lenient::datetime"
+    )]
+    pub start_at: Option<DateTime>,
+    #[serde(
+        default,
+        rename = "end date",
+        alias = "end at",
+        deserialize_with = "lenient::datetime"
+    )]
+    pub end_at: Option<DateTime>,
+}
+
+impl ReportRow for UnpublishedCourseRow {
+    const FILE_NAME: Option<&'static str> = None;
+}
+
+/// A row of the CSV file produced by `student_assignment_outcome_map_csv`.
+#[derive(Debug, Clone, Deserialize)]
+pub struct StudentAssignmentOutcomeRow {
+    #[serde(rename = "student name")]
+    pub student_name: Option<String>,
+    #[serde(rename = "student id")]
+    pub student_id: Option<UserId>,
+    #[serde(rename = "student sis id")]
+    pub student_sis_id: Option<String>,
+    #[serde(rename = "assignment title")]
+    pub assignment_title: Option<String>,
+    #[serde(rename = "assignment id")]
+    pub assignment_id: Option<AssignmentId>,
+    #[serde(
+        default,
+        rename = "submission date",
+        deserialize_with = "lenient::datetime"
+    )]
+    pub submission_date: Option<DateTime>,
+    #[serde(rename = "submission score")]
+    pub submission_score: Option<f64>,
+    #[serde(rename = "learning outcome name")]
+    pub learning_outcome_name: Option<String>,
+    #[serde(rename = "learning outcome id")]
+    pub learning_outcome_id: Option<OutcomeId>,
+    #[serde(rename = "attempt")]
+    pub attempt: Option<u32>,
+    #[serde(rename = "outcome score")]
+    pub outcome_score: Option<f64>,
+    #[serde(rename = "course name")]
+    pub course_name: Option<String>,
+    #[serde(rename = "course id")]
+    pub course_id: Option<CourseId>,
+    #[serde(rename = "course sis id")]
+    pub course_sis_id: Option<String>,
+    #[serde(default, rename = "section name")]
+    pub section_name: Option<String>,
+    #[serde(default, rename = "section id")]
+    pub section_id: Option<SectionId>,
+    #[serde(default, rename = "section sis id")]
+    pub section_sis_id: Option<String>,
+    #[serde(default, rename = "assignment url")]
+    pub assignment_url: Option<String>,
+    #[serde(default, rename = "learning outcome points possible")]
+    pub learning_outcome_points_possible: Option<f64>,
+    #[serde(
+        default,
+        rename = "learning outcome mastered",
+        deserialize_with = "lenient::optional"
+    )]
+    pub learning_outcome_mastered: Option<bool>,
+    #[serde(default, rename = "learning outcome rating")]
+    pub learning_outcome_rating: Option<String>,
+    #[serde(default, rename = "account id")]
+    pub account_id: Option<AccountId>,
+    #[serde(default, rename = "account name")]
+    pub account_name: Option<String>,
+    #[serde(default, rename = "enrollment state")]
+    pub enrollment_state: Option<String>,
+}
+
+impl ReportRow for StudentAssignmentOutcomeRow {
+    const FILE_NAME: Option<&'static str> = None;
+}
@@ -0,0 +1,150 @@
+use async_trait::async_trait;
+use futures::TryStreamExt;
+use serde::Deserialize;
+
+use crate::{AccountId, CanvasClient, PageStream};
+
+/// An account, such as a school, department or the root account of a Canvas
+/// instance.
+///
+/// [See docs](https://canvas.instructure.com/doc/api/accounts.html#Account).
+#[derive(Deserialize, Debug, Clone)]
+pub struct Account {
+    /// The ID of the account.
+    id: AccountId,
+    /// The display name of the account.
+    name: String,
+    /// The UUID of the account.
+    uuid: Option<String>,
+    /// The ID of the parent account, or [`None`] for a root account.
+    parent_account_id: Option<AccountId>,
+    /// The ID of the root account, or [`None`] for a root account.
+    root_account_id: Option<AccountId>,
+    /// The state of the account, `active` or `deleted`.
+    workflow_state: Option<String>,
+    /// The SIS ID of the account.
+    sis_account_id: Option<String>,
+    /// The integration ID of the account.
+    integration_id: Option<String>,
+    /// The default time zone of the account, such as `America/Denver`.
+    default_time_zone: Option<String>,
+}
+
+impl Account {
+    /// The ID of the account.
+    pub fn id(&self) -> &AccountId {
+        &self.id
+    }
+
+    /// The display name of the account.
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    /// The UUID of the account.
+    pub fn uuid(&self) -> Option<&str> {
+        self.uuid.as_deref()
+    }
+
+    /// The ID of the parent account, or [`None`] for a root account.
+    pub fn parent_account_id(&self) -> Option<&AccountId> {
+        self.parent_account_id.as_ref()
+    }
+
+    /// The ID of the root account, or [`None`] for a root account.
+    pub fn root_account_id(&self) -> Option<&AccountId> {
+        self.root_account_id.as_ref()
+    }
+
+    /// The state of the account, `active` or `deleted`.
+    pub fn workflow_state(&self) -> Option<&str> {
+        self.workflow_state.as_deref()
+    }
+
+    /// The SIS ID of the account.
+    pub fn sis_account_id(&self) -> Option<&str> {
+        self.sis_account_id.as_deref()
+    }
+
+    /// The integration ID of the account.
+    pub fn integration_id(&self) -> Option<&str> {
+        self.integration_id.as_deref()
+    }
+
+    /// The default time zone of the account, such as `America/Denver`.
+    pub fn default_time_zone(&self) -> Option<&str> {
+        self.default_time_zone.as_deref()
+    }
+}
+
+#[async_trait]
+pub trait AccountsExt {
+    /// Retrieves information on a single account.
+    ///
+    /// [See docs](https://canvas.instructure.com/doc/api/accounts.html#method.accounts.show).
+    async fn get_account(&self, account_id: AccountId) -> Result<Account, crate::Error>;
+
+    /// Lists the sub-accounts of an account.
+    ///
+    /// If `recursive` is true, every sub-account of the account's sub-accounts
+    /// is listed as well, rather than only its direct children.
+    ///
+    /// [See docs](https://canvas.instructure.com/doc/api/accounts.html#method.accounts.sub_accounts).
+    async fn get_sub_accounts(
+        &self,
+        account_id: AccountId,
+        recursive: bool,
+    ) -> Result<Vec<Account>, crate::Error>;
+
+    /// Streams the sub-accounts of an account, fetching each page as it is
+    /// consumed.
+    ///
+    /// See [`AccountsExt::get_sub_accounts`].
+    fn get_sub_accounts_stream(
+        &self,
+        account_id: AccountId,
+        recursive: bool,
+    ) -> PageStream<'_, Account>;
+}
+
+// https://canvas.instructure.com/doc/api/accounts.html#method.accounts.show
+type GetAccountResponse = Account;
+
+// https://canvas.instructure.com/doc/api/accounts.html#method.accounts.sub_accounts
+type GetSubAccountsResponse = Vec<Account>;
+
+#[async_trait]
+impl AccountsExt for CanvasClient {
+    async fn get_account(&self, account_id: AccountId) -> Result<Account, crate::Error> {
+        let account: GetAccountResponse = self
+            .send(self.make_query(&format!("v1/accounts/{}", account_id.path_segment())))
+            .await?;
+
+        Ok(account)
+    }
+
+    async fn get_sub_accounts(
+        &self,
+        account_id: AccountId,
+        recursive: bool,
+    ) -> Result<Vec<Account>, crate::Error> {
+        self.get_sub_accounts_stream(account_id, recursive)
+            .try_collect()
+            .await
+    }
+
+    fn get_sub_accounts_stream(
+        &self,
+        account_id: AccountId,
+        recursive: bool,
+    ) -> PageStream<'_, Account> {
+        self.paginate_stream(
+            self.make_query(&format!(
+                "v1/accounts/{}/sub_accounts",
+                account_id.path_segment()
+            ))
+            .query(&[("recursive", recursive)]),
+            |page: GetSubAccountsResponse| page,
+        )
+    }
+}
@@ -0,0 +1,1138 @@
+use std::time::Duration;
+
+use async_trait::async_trait;
+use futures::TryStreamExt;
+use serde::{ser, Deserialize, Serialize, Serializer};
+
+use crate::{
+    extensions::calendar_events::CalendarEvent, form::NestedForm, AppointmentGroupId,
+    CalendarEventId, CanvasClient, DateTime, GroupId, PageStream, UserId,
+};
+
+/// A set of time slots that users or groups can sign up for, created with the
+/// Canvas Scheduler.
+///
+/// [See docs](https://canvas.instructure.com/doc/api/appointment_groups.html#AppointmentGroup).
+#[derive(Deserialize, Debug, Clone)]
+pub struct AppointmentGroup {
+    /// The ID of the appointment group.
+    id: AppointmentGroupId,
+    /// The title of the appointment group.
+    #[serde(default)]
+    title: String,
+    /// When the first time slot starts.
+    #[serde(default, with = "crate::datetime::rfc3339::option")]
+    start_at: Option<DateTime>,
+    /// When the last time slot ends.
+    #[serde(default, with = "crate::datetime::rfc3339::option")]
+    end_at: Option<DateTime>,
+    /// The description of the appointment group.
+    description: Option<String>,
+    /// The name of the location of the appointment group.
+    location_name: Option<String>,
+    /// The address of the location of the appointment group.
+    location_address: Option<String>,
+    /// The number of participants who have reserved a time slot.
+    participant_count: Option<u64>,
+    /// The time slots the current user has reserved.
+    #[serde(default)]
+    reserved_times: Vec<ReservedTime>,
+    /// Whether observers can sign up.
+    #[serde(default)]
+    allow_observer_signup: bool,
+    /// The contexts the appointment group is in, such as `course_123`.
+    #[serde(default)]
+    context_codes: Vec<String>,
+    /// The sections or group categories the appointment group is limited to,
+    /// such as `course_section_45`.
+    #[serde(default)]
+    sub_context_codes: Vec<String>,
+    /// The state of the appointment group, `pending`, `active` or `deleted`.
+    workflow_state: String,
+    /// Whether the current user still needs to reserve a time slot.
+    #[serde(default)]
+    requiring_action: bool,
+    /// The number of time slots.
+    #[serde(default)]
+    appointments_count: u64,
+    /// The time slots of the appointment group.
+    #[serde(default)]
+    appointments: Vec<CalendarEvent>,
+    /// The most time slots a participant can reserve. `None` if unlimited.
+    max_appointments_per_participant: Option<u64>,
+    /// The fewest time slots a participant must reserve.
+    min_appointments_per_participant: Option<u64>,
+    /// The most participants per time slot. `None` if unlimited.
+    participants_per_appointment: Option<u64>,
+    /// Whether participants can see who else has reserved a time slot.
+    participant_visibility: Option<ParticipantVisibility>,
+    /// Whether users or groups sign up, `User` or `Group`.
+    participant_type: Option<String>,
+    /// The API url of the appointment group.
+    url: Option<String>,
+    /// The url of the appointment group in the Canvas web interface.
+    html_url: Option<String>,
+    /// When the appointment group was created.
+    #[serde(default, with = "crate::datetime::rfc3339::option")]
+    created_at: Option<DateTime>,
+    /// When the appointment group was last updated.
+    #[serde(default, with = "crate::datetime::rfc3339::option")]
+    updated_at: Option<DateTime>,
+}
+
+impl AppointmentGroup {
+    /// The ID of the appointment group.
+    pub fn id(&self) -> &AppointmentGroupId {
+        &self.id
+    }
+
+    /// The title of the appointment group.
+    pub fn title(&self) -> &str {
+        &self.title
+    }
+
+    /// When the first time slot starts.
+    pub fn start_at(&self) -> Option<DateTime> {
+        self.start_at
+    }
+
+    /// When the last time slot ends.
+    pub fn end_at(&self) -> Option<DateTime> {
+        self.end_at
+    }
+
+    /// The description of the appointment group.
+    pub fn description(&self) -> Option<&str> {
+        self.description.as_deref()
+    }
+
+    /// The name of the location of the appointment group.
+    pub fn location_name(&self) -> Option<&str> {
+        self.location_name.as_deref()
+    }
+
+    /// The address of the location of the appointment group.
+    pub fn location_address(&self) -> Option<&str> {
+        self.location_address.as_deref()
+    }
+
+    /// The number of participants who have reserved a time slot. Only
+    /// returned with [`AppointmentGroupInclude::ParticipantCount`].
+    pub fn participant_count(&self) -> Option<u64> {
+        self.participant_count
+    }
+
+    /// The time slots the current user has reserved. Only returned with
+    /// [`AppointmentGroupInclude::ReservedTimes`].
+    pub fn reserved_times(&self) -> &[ReservedTime] {
+        &self.reserved_times
+    }
+
+    /// Whether observers can sign up.
+    pub fn allow_observer_signup(&self) -> bool {
+        self.allow_observer_signup
+    }
+
+    /// The contexts the appointment group is in, such as `course_123`.
+    pub fn context_codes(&self) -> &[String] {
+        &self.context_codes
+    }
+
+    /// The sections or group categories the appointment group is limited to,
+    /// such as `course_section_45`.
+    pub fn sub_context_codes(&self) -> &[String] {
+        &self.sub_context_codes
+    }
+
+    /// The state of the appointment group, `pending`, `active` or `deleted`.
+    pub fn workflow_state(&self) -> &str {
+        &self.workflow_state
+    }
+
+    /// Whether the current user still needs to reserve a time slot.
+    pub fn requiring_action(&self) -> bool {
+        self.requiring_action
+    }
+
+    /// The number of time slots.
+    pub fn appointments_count(&self) -> u64 {
+        self.appointments_count
+    }
+
+    /// The time slots of the appointment group. Only returned with
+    /// [`AppointmentGroupInclude::Appointments`].
+    pub fn appointments(&self) -> &[CalendarEvent] {
+        &self.appointments
+    }
+
+    /// The most time slots a participant can reserve. `None` if unlimited.
+    pub fn max_appointments_per_participant(&self) -> Option<u64> {
+        self.max_appointments_per_participant
+    }
+
+    /// The fewest time slots a participant must reserve.
+    pub fn min_appointments_per_participant(&self) -> Option<u64> {
+        self.min_appointments_per_participant
+    }
+
+    /// The most participants per time slot. `None` if unlimited.
+    pub fn participants_per_appointment(&self) -> Option<u64> {
+        self.participants_per_appointment
+    }
+
+    /// Whether participants can see who else has reserved a time slot.
+    pub fn participant_visibility(&self) -> Option<ParticipantVisibility> {
+        self.participant_visibility
+    }
+
+    /// Whether users or groups sign up, `User` or `Group`.
+    pub fn participant_type(&self) -> Option<&str> {
+        self.participant_type.as_deref()
+    }
+
+    /// The API url of the appointment group.
+    pub fn url(&self) -> Option<&str> {
+        self.url.as_deref()
+    }
+
+    /// The url of the appointment group in the Canvas web interface.
+    pub fn html_url(&self) -> Option<&str> {
+        self.html_url.as_deref()
+    }
+
+    /// When the appointment group was created.
+    pub fn created_at(&self) -> Option<DateTime> {
+        self.created_at
+    }
+
+    /// When the appointment group was last updated.
+    pub fn updated_at(&self) -> Option<DateTime> {
+        self.updated_at
+    }
+}
+
+/// A time slot the current user has reserved.
+#[derive(Deserialize, Debug, Clone)]
+pub struct ReservedTime {
+    /// The ID of the reserved time slot.
+    id: CalendarEventId,
+    /// When the time slot starts.
+    #[serde(with = "crate::datetime::rfc3339")]
+    start_at: DateTime,
+    /// When the time slot ends.
+    #[serde(with = "crate::datetime::rfc3339")]
+    end_at: DateTime,
+}
+
+impl ReservedTime {
+    /// The ID of the reserved time slot.
+    pub fn id(&self) -> &CalendarEventId {
+        &self.id
+    }
+
+    /// When the time slot starts.
+    pub fn start_at(&self) -> DateTime {
+        self.start_at
+    }
+
+    /// When the time slot ends.
+    pub fn end_at(&self) -> DateTime {
+        self.end_at
+    }
+}
+
+/// Whether participants can see who else has reserved a time slot.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ParticipantVisibility {
+    /// Participants only see their own reservations.
+    Private,
+    /// Participants see who has reserved each time slot.
+    Protected,
+}
+
+/// A user who can sign up for an appointment group.
+#[derive(Deserialize, Debug, Clone)]
+pub struct ParticipantUser {
+    /// The ID of the user.
+    id: UserId,
+    /// The name of the user.
+    name: String,
+    /// The name of the user, for sorting.
+    sortable_name: Option<String>,
+    /// The short name of the user.
+    short_name: Option<String>,
+}
+
+impl ParticipantUser {
+    /// The ID of the user.
+    pub fn id(&self) -> &UserId {
+        &self.id
+    }
+
+    /// The name of the user.
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    /// The name of the user, for sorting.
+    pub fn sortable_name(&self) -> Option<&str> {
+        self.sortable_name.as_deref()
+    }
+
+    /// The short name of the user.
+    pub fn short_name(&self) -> Option<&str> {
+        self.short_name.as_deref()
+    }
+}
+
+/// A group that can sign up for an appointment group.
+#[derive(Deserialize, Debug, Clone)]
+pub struct ParticipantGroup {
+    /// The ID of the group.
+    id: GroupId,
+    /// The name of the group.
+    name: String,
+    /// The number of members of the group.
+    members_count: Option<u64>,
+}
+
+impl ParticipantGroup {
+    /// The ID of the group.
+    pub fn id(&self) -> &GroupId {
+        &self.id
+    }
+
+    /// The name of the group.
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    /// The number of members of the group.
+    pub fn members_count(&self) -> Option<u64> {
+        self.members_count
+    }
+}
+
+/// Which appointment groups to list.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum AppointmentGroupScope {
+    /// Appointment groups the current user can reserve time slots in.
+    Reservable,
+    /// Appointment groups the current user can manage.
+    Manageable,
+}
+
+impl AppointmentGroupScope {
+    fn as_str(&self) -> &'static str {
+        match self {
+            Self::Reservable => "reservable",
+            Self::Manageable => "manageable",
+        }
+    }
+}
+
+/// The optional fields to return with appointment groups.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum AppointmentGroupInclude {
+    /// The time slots of each appointment group.
+    Appointments,
+    /// The reservations of each time slot.
+    ChildEvents,
+    /// The number of participants who have reserved a time slot.
+    ParticipantCount,
+    /// The time slots the current user has reserved.
+    ReservedTimes,
+    /// Every context of each appointment group, rather than only those
+    /// visible to the current user.
+    AllContextCodes,
+}
+
+impl AppointmentGroupInclude {
+    fn as_str(&self) -> &'static str {
+        match self {
+            Self::Appointments => "appointments",
+            Self::ChildEvents => "child_events",
+            Self::ParticipantCount => "participant_count",
+            Self::ReservedTimes => "reserved_times",
+            Self::AllContextCodes => "all_context_codes",
+        }
+    }
+}
+
+/// Which participants of an appointment group to list.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum RegistrationStatus {
+    /// Every participant.
+    #[default]
+    All,
+    /// Participants who have reserved a time slot.
+    Registered,
+    /// Participants who have not reserved a time slot.
+    Unregistered,
+}
+
+/// Which appointment groups [`AppointmentGroupsExt::list_appointment_groups`]
+/// lists.
+#[derive(Debug, Clone, Default)]
+pub struct AppointmentGroupQuery {
+    /// Whether to list reservable or manageable appointment groups. Defaults
+    /// to reservable.
+    scope: Option<AppointmentGroupScope>,
+    /// The contexts to list appointment groups from, such as `course_123`.
+    context_codes: Vec<String>,
+    /// Whether to list appointment groups whose time slots have all passed.
+    include_past_appointments: bool,
+    /// The optional fields to return.
+    include: Vec<AppointmentGroupInclude>,
+}
+
+impl AppointmentGroupQuery {
+    /// Creates a new [`AppointmentGroupQuery`] for the upcoming appointment
+    /// groups the current user can reserve time slots in.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Sets whether to list reservable or manageable appointment groups.
+    /// Defaults to reservable.
+    pub fn set_scope(mut self, scope: AppointmentGroupScope) -> Self {
+        self.scope = Some(scope);
+
+        self
+    }
+
+    /// Sets the contexts to list appointment groups from, such as
+    /// `course_123`. Defaults to every context of the current user.
+    pub fn set_context_codes(mut self, context_codes: Vec<String>) -> Self {
+        self.context_codes = context_codes;
+
+        self
+    }
+
+    /// Sets whether to list appointment groups whose time slots have all
+    /// passed. Defaults to false.
+    pub fn set_include_past_appointments(mut self, include_past_appointments: bool) -> Self {
+        self.include_past_appointments = include_past_appointments;
+
+        self
+    }
+
+    /// Sets the optional fields to return.
+    pub fn set_include(mut self, include: Vec<AppointmentGroupInclude>) -> Self {
+        self.include = include;
+
+        self
+    }
+
+    /// The query string parameters of the query.
+    fn to_query(&self) -> Vec<(&'static str, String)> {
+        let mut query = Vec::new();
+        if let Some(scope) = self.scope {
+            query.push(("scope", scope.as_str().to_string()));
+        }
+        query.extend(
+            self.context_codes
+                .iter()
+                .map(|context_code| ("context_codes[]", context_code.clone())),
+        );
+        if self.include_past_appointments {
+            query.push(("include_past_appointments", true.to_string()));
+        }
+        query.extend(include_query(&self.include));
+
+        query
+    }
+}
+
+/// The `include[]` query string parameters of `include`.
+fn include_query(include: &[AppointmentGroupInclude]) -> Vec<(&'static str, String)> {
+    include
+        .iter()
+        .map(|include| ("include[]", include.as_str().to_string()))
+        .collect()
+}
+
+/// A time slot of an appointment group, from `start_at` to `end_at`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct TimeSlot {
+    start_at: DateTime,
+    end_at: DateTime,
+}
+
+impl TimeSlot {
+    /// Creates a new [`TimeSlot`] from `start_at` to `end_at`.
+    pub fn new(start_at: DateTime, end_at: DateTime) -> Self {
+        Self { start_at, end_at }
+    }
+
+    /// Splits the window from `start_at` to `end_at` into back to back time
+    /// slots of `length`.
+    ///
+    /// Time at the end of the window that is too short for another slot is
+    /// left unused. Canvas times have whole seconds, so fails if `length` is
+    /// zero or has a fraction of a second.
+    ///
+    /// # Example
+    /// ```rs
+    /// // 9:00 to 12:00 in 20 minute slots
+    /// let slots = TimeSlot::evenly_spaced(start_at, end_at, Duration::from_secs(20 * 60))?;
+    /// assert_eq!(slots.len(), 9);
+    /// ```
+    pub fn evenly_spaced(
+        start_at: DateTime,
+        end_at: DateTime,
+        length: Duration,
+    ) -> Result<Vec<Self>, crate::Error> {
+        let seconds = i64::try_from(length.as_secs())
+            .ok()
+            .filter(|seconds| *seconds > 0 && length.subsec_nanos() == 0)
+            .ok_or(crate::Error::InvalidTimeSlotLength { length })?;
+
+        let count = crate::datetime::seconds_between(&start_at, &end_at).max(0) / seconds;
+
+        Ok((0..count)
+            .map(|index| {
+                let start = crate::datetime::add_seconds(&start_at, index * seconds);
+
+                Self::new(start, crate::datetime::add_seconds(&start, seconds))
+            })
+            .collect())
+    }
+
+    /// When the time slot starts.
+    pub fn start_at(&self) -> DateTime {
+        self.start_at
+    }
+
+    /// When the time slot ends.
+    pub fn end_at(&self) -> DateTime {
+        self.end_at
+    }
+}
+
+/// The fields of an appointment group to create or update.
+///
+/// Fields that are not set are left unchanged by an update.
+#[derive(Debug, Clone, Serialize)]
+pub struct AppointmentGroupForm {
+    /// The contexts of the appointment group, such as `course_123`.
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    context_codes: Vec<String>,
+    sub_context_codes: Option<Vec<String>>,
+    title: Option<String>,
+    description: Option<String>,
+    location_name: Option<String>,
+    location_address: Option<String>,
+    publish: Option<bool>,
+    participants_per_appointment: Option<u32>,
+    min_appointments_per_participant: Option<u32>,
+    max_appointments_per_participant: Option<u32>,
+    #[serde(serialize_with = "new_appointments")]
+    new_appointments: Vec<TimeSlot>,
+    participant_visibility: Option<ParticipantVisibility>,
+    allow_observer_signup: Option<bool>,
+}
+
+impl AppointmentGroupForm {
+    /// Creates a new [`AppointmentGroupForm`] for an appointment group in
+    /// `context_codes`, such as `course_123`.
+    ///
+    /// Context codes are the asset strings of the courses, in the same form
+    /// as [`AccountCalendar::asset_string`](crate::extensions::calendar::AccountCalendar::asset_string),
+    /// and can be made with [`CourseId::asset_string`](crate::CourseId::asset_string).
+    pub fn new(context_codes: Vec<String>) -> Self {
+        Self {
+            context_codes,
+            sub_context_codes: None,
+            title: None,
+            description: None,
+            location_name: None,
+            location_address: None,
+            publish: None,
+            participants_per_appointment: None,
+            min_appointments_per_participant: None,
+            max_appointments_per_participant: None,
+            new_appointments: Vec::new(),
+            participant_visibility: None,
+            allow_observer_signup: None,
+        }
+    }
+
+    /// Limits the appointment group to sections, such as `course_section_45`,
+    /// or to the groups of a group category, such as `group_category_7`.
+    pub fn set_sub_context_codes(mut self, sub_context_codes: Vec<String>) -> Self {
+        self.sub_context_codes = Some(sub_context_codes);
+
+        self
+    }
+
+    /// Sets the title of the appointment group.
+    pub fn set_title(mut self, title: impl Into<String>) -> Self {
+        self.title = Some(title.into());
+
+        self
+    }
+
+    /// Sets the description of the appointment group.
+    pub fn set_description(mut self, description: impl Into<String>) -> Self {
+        self.description = Some(description.into());
+
+        self
+    }
+
+    /// Sets the name of the location of the appointment group.
+    pub fn set_location_name(mut self, location_name: impl Into<String>) -> Self {
+        self.location_name = Some(location_name.into());
+
+        self
+    }
+
+    /// Sets the address of the location of the appointment group.
+    pub fn set_location_address(mut self, location_address: impl Into<String>) -> Self {
+        self.location_address = Some(location_address.into());
+
+        self
+    }
+
+    /// Sets whether to publish the appointment group, making it visible to
+    /// participants. A published appointment group cannot be unpublished.
+    pub fn set_publish(mut self, publish: bool) -> Self {
+        self.publish = Some(publish);
+
+        self
+    }
+
+    /// Sets the most participants per time slot. Defaults to 1.
+    pub fn set_participants_per_appointment(mut self, participants_per_appointment: u32) -> Self {
+        self.participants_per_appointment = Some(participants_per_appointment);
+
+        self
+    }
+
+    /// Sets the fewest time slots a participant must reserve. Defaults to 1.
+    pub fn set_min_appointments_per_participant(
+        mut self,
+        min_appointments_per_participant: u32,
+    ) -> Self {
+        self.min_appointments_per_participant = Some(min_appointments_per_participant);
+
+        self
+    }
+
+    /// Sets the most time slots a participant can reserve. Defaults to 1.
+    pub fn set_max_appointments_per_participant(
+        mut self,
+        max_appointments_per_participant: u32,
+    ) -> Self {
+        self.max_appointments_per_participant = Some(max_appointments_per_participant);
+
+        self
+    }
+
+    /// Adds time slots to the appointment group, such as those made with
+    /// [`TimeSlot::evenly_spaced`].
+    pub fn add_time_slots(mut self, time_slots: impl IntoIterator<Item = TimeSlot>) -> Self {
+        self.new_appointments.extend(time_slots);
+
+        self
+    }
+
+    /// Sets whether participants can see who else has reserved a time slot.
+    /// Defaults to private.
+    pub fn set_participant_visibility(
+        mut self,
+        participant_visibility: ParticipantVisibility,
+    ) -> Self {
+        self.participant_visibility = Some(participant_visibility);
+
+        self
+    }
+
+    /// Sets whether observers can sign up. Defaults to false.
+    pub fn set_allow_observer_signup(mut self, allow_observer_signup: bool) -> Self {
+        self.allow_observer_signup = Some(allow_observer_signup);
+
+        self
+    }
+}
+
+/// Canvas reads new time slots as a map of indexes to `[start_at, end_at]`,
+/// which is written in index order.
+fn new_appointments<S>(time_slots: &[TimeSlot], serializer: S) -> Result<S::Ok, S::Error>
+where
+    S: Serializer,
+{
+    if time_slots.is_empty() {
+        return serializer.serialize_none();
+    }
+
+    let time_slots = time_slots
+        .iter()
+        .map(|time_slot| {
+            let start_at = crate::datetime::format(&time_slot.start_at);
+            let end_at = crate::datetime::format(&time_slot.end_at);
+
+            Ok([start_at?, end_at?])
+        })
+        .collect::<Result<Vec<_>, String>>()
+        .map_err(ser::Error::custom)?;
+
+    serializer.collect_map(time_slots.iter().enumerate())
+}
+
+/// The options of a time slot reservation.
+#[derive(Debug, Clone, Default)]
+pub struct ReservationForm {
+    /// The user or group to reserve the time slot for.
+    participant_id: Option<String>,
+    /// A comment for the other participants.
+    comments: Option<String>,
+    /// Whether to cancel the participant's existing reservations in the
+    /// appointment group.
+    cancel_existing: bool,
+}
+
+impl ReservationForm {
+    /// Creates a new [`ReservationForm`] to reserve a time slot for the
+    /// current user.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Reserves the time slot on behalf of a user. Requires permission to
+    /// manage the appointment group.
+    pub fn set_user(mut self, user_id: UserId) -> Self {
+        self.participant_id = Some(user_id.path_segment());
+
+        self
+    }
+
+    /// Reserves the time slot on behalf of a group, for appointment groups
+    /// that groups sign up for. Requires permission to manage the appointment
+    /// group.
+    pub fn set_group(mut self, group_id: GroupId) -> Self {
+        self.participant_id = Some(group_id.path_segment());
+
+        self
+    }
+
+    /// Sets a comment for the other participants of the time slot.
+    pub fn set_comments(mut self, comments: impl Into<String>) -> Self {
+        self.comments = Some(comments.into());
+
+        self
+    }
+
+    /// Sets whether to cancel the participant's existing reservations in the
+    /// appointment group, such as when the most time slots a participant can
+    /// reserve have already been reserved. Defaults to false.
+    pub fn set_cancel_existing(mut self, cancel_existing: bool) -> Self {
+        self.cancel_existing = cancel_existing;
+
+        self
+    }
+}
+
+#[async_trait]
+pub trait AppointmentGroupsExt {
+    /// Returns the appointment groups the current user can reserve time slots
+    /// in, or with [`AppointmentGroupScope::Manageable`], manage.
+    ///
+    /// [See docs](https://canvas.instructure.com/doc/api/appointment_groups.html#method.appointment_groups.index).
+    async fn list_appointment_groups(
+        &self,
+        query: &AppointmentGroupQuery,
+    ) -> Result<Vec<AppointmentGroup>, crate::Error>;
+
+    /// Streams the appointment groups matching `query`, fetching each page as
+    /// it is consumed.
+    ///
+    /// See [`AppointmentGroupsExt::list_appointment_groups`].
+    fn list_appointment_groups_stream(
+        &self,
+        query: &AppointmentGroupQuery,
+    ) -> PageStream<'_, AppointmentGroup>;
+
+    /// Returns an appointment group, with the optional fields in `include`.
+    ///
+    /// [See docs](https://canvas.instructure.com/doc/api/appointment_groups.html#method.appointment_groups.show).
+    async fn get_appointment_group(
+        &self,
+        appointment_group_id: AppointmentGroupId,
+        include: &[AppointmentGroupInclude],
+    ) -> Result<AppointmentGroup, crate::Error>;
+
+    /// Creates an appointment group.
+    ///
+    /// # Example
+    /// ```rs
+    /// let slots = TimeSlot::evenly_spaced(start_at, end_at, Duration::from_secs(15 * 60))?;
+    /// let appointment_group = client
+    ///     .create_appointment_group(
+    ///         &AppointmentGroupForm::new(vec![String::from("course_123")])
+    ///             .set_title("Advising")
+    ///             .add_time_slots(slots)
+    ///             .set_publish(true),
+    ///     )
+    ///     .await?;
+    /// ```
+    ///
+    /// [See docs](https://canvas.instructure.com/doc/api/appointment_groups.html#method.appointment_groups.create).
+    async fn create_appointment_group(
+        &self,
+        appointment_group: &AppointmentGroupForm,
+    ) -> Result<AppointmentGroup, crate::Error>;
+
+    /// Updates an appointment group. Time slots set with
+    /// [`AppointmentGroupForm::add_time_slots`] are added to its existing time
+    /// slots.
+    ///
+    /// [See docs](https://canvas.instructure.com/doc/api/appointment_groups.html#method.appointment_groups.update).
+    async fn update_appointment_group(
+        &self,
+        appointment_group_id: AppointmentGroupId,
+        appointment_group: &AppointmentGroupForm,
+    ) -> Result<AppointmentGroup, crate::Error>;
+
+    /// Deletes an appointment group, along with its time slots and their
+    /// reservations. `cancel_reason` is sent to the participants.
+    ///
+    /// Returns the deleted appointment group.
+    ///
+    /// [See docs](https://canvas.instructure.com/doc/api/appointment_groups.html#method.appointment_groups.destroy).
+    async fn delete_appointment_group(
+        &self,
+        appointment_group_id: AppointmentGroupId,
+        cancel_reason: Option<&str>,
+    ) -> Result<AppointmentGroup, crate::Error>;
+
+    /// Returns the users who can sign up for an appointment group, filtered
+    /// by whether they have reserved a time slot.
+    ///
+    /// [See docs](https://canvas.instructure.com/doc/api/appointment_groups.html#method.appointment_groups.users).
+    async fn list_appointment_group_users(
+        &self,
+        appointment_group_id: AppointmentGroupId,
+        registration_status: RegistrationStatus,
+    ) -> Result<Vec<ParticipantUser>, crate::Error>;
+
+    /// Streams the users who can sign up for an appointment group, fetching
+    /// each page as it is consumed.
+    ///
+    /// See [`AppointmentGroupsExt::list_appointment_group_users`].
+    fn list_appointment_group_users_stream(
+        &self,
+        appointment_group_id: AppointmentGroupId,
+        registration_status: RegistrationStatus,
+    ) -> PageStream<'_, ParticipantUser>;
+
+    /// Returns the groups that can sign up for an appointment group, filtered
+    /// by whether they have reserved a time slot.
+    ///
+    /// [See docs](https://canvas.instructure.com/doc/api/appointment_groups.html#method.appointment_groups.groups).
+    async fn list_appointment_group_groups(
+        &self,
+        appointment_group_id: AppointmentGroupId,
+        registration_status: RegistrationStatus,
+    ) -> Result<Vec<ParticipantGroup>, crate::Error>;
+
+    /// Streams the groups that can sign up for an appointment group, fetching
+    /// each page as it is consumed.
+    ///
+    /// See [`AppointmentGroupsExt::list_appointment_group_groups`].
+    fn list_appointment_group_groups_stream(
+        &self,
+        appointment_group_id: AppointmentGroupId,
+        registration_status: RegistrationStatus,
+    ) -> PageStream<'_, ParticipantGroup>;
+
+    /// Returns the next time slot the current user has reserved in any of
+    /// `appointment_group_ids`. `None` if there is none.
+    ///
+    /// [See docs](https://canvas.instructure.com/doc/api/appointment_groups.html#method.appointment_groups.next_appointment).
+    async fn next_appointment(
+        &self,
+        appointment_group_ids: &[AppointmentGroupId],
+    ) -> Result<Option<CalendarEvent>, crate::Error>;
+
+    /// Reserves a time slot of an appointment group, for the current user or
+    /// the participant set with [`ReservationForm::set_user`] or
+    /// [`ReservationForm::set_group`].
+    ///
+    /// Returns the reservation, whose ID is passed to
+    /// [`AppointmentGroupsExt::unreserve_time_slot`] to cancel it.
+    ///
+    /// [See docs](https://canvas.instructure.com/doc/api/calendar_events.html#method.calendar_events_api.reserve).
+    async fn reserve_time_slot(
+        &self,
+        time_slot_id: CalendarEventId,
+        reservation: &ReservationForm,
+    ) -> Result<CalendarEvent, crate::Error>;
+
+    /// Cancels a reservation made with
+    /// [`AppointmentGroupsExt::reserve_time_slot`]. `cancel_reason` is sent to
+    /// the participant.
+    ///
+    /// Returns the cancelled reservation.
+    ///
+    /// [See docs](https://canvas.instructure.com/doc/api/calendar_events.html#method.calendar_events_api.destroy).
+    async fn unreserve_time_slot(
+        &self,
+        reservation_id: CalendarEventId,
+        cancel_reason: Option<&str>,
+    ) -> Result<CalendarEvent, crate::Error>;
+}
+
+// https://canvas.instructure.com/doc/api/appointment_groups.html#method.appointment_groups.index
+type ListAppointmentGroupsResponse = Vec<AppointmentGroup>;
+
+// https://canvas.instructure.com/doc/api/appointment_groups.html#method.appointment_groups.show
+type GetAppointmentGroupResponse = AppointmentGroup;
+
+// https://canvas.instructure.com/doc/api/appointment_groups.html#method.appointment_groups.create
+type CreateAppointmentGroupResponse = AppointmentGroup;
+
+// https://canvas.instructure.com/doc/api/appointment_groups.html#method.appointment_groups.update
+type UpdateAppointmentGroupResponse = AppointmentGroup;
+
+// https://canvas.instructure.com/doc/api/appointment_groups.html#method.appointment_groups.destroy
+type DeleteAppointmentGroupResponse = AppointmentGroup;
+
+// https://canvas.instructure.com/doc/api/appointment_groups.html#method.appointment_groups.users
+type ListAppointmentGroupUsersResponse = Vec<ParticipantUser>;
+
+// https://canvas.instructure.com/doc/api/appointment_groups.html#method.appointment_groups.groups
+type ListAppointmentGroupGroupsResponse = Vec<ParticipantGroup>;
+
+// https://canvas.instructure.com/doc/api/appointment_groups.html#method.appointment_groups.next_appointment
+type NextAppointmentResponse = Vec<CalendarEvent>;
+
+// https://canvas.instructure.com/doc/api/calendar_events.html#method.calendar_events_api.reserve
+type ReserveTimeSlotResponse = CalendarEvent;
+
+// https://canvas.instructure.com/doc/api/calendar_events.html#method.calendar_events_api.destroy
+type UnreserveTimeSlotResponse = CalendarEvent;
+
+#[derive(Serialize)]
+struct AppointmentGroupBody<'a> {
+    appointment_group: &'a AppointmentGroupForm,
+}
+
+#[derive(Serialize)]
+struct CancelQuery<'a> {
+    cancel_reason: Option<&'a str>,
+}
+
+#[derive(Serialize)]
+struct ReservationBody<'a> {
+    comments: Option<&'a str>,
+    cancel_existing: bool,
+}
+
+#[async_trait]
+impl AppointmentGroupsExt for CanvasClient {
+    async fn list_appointment_groups(
+        &self,
+        query: &AppointmentGroupQuery,
+    ) -> Result<Vec<AppointmentGroup>, crate::Error> {
+        self.list_appointment_groups_stream(query)
+            .try_collect()
+            .await
+    }
+
+    fn list_appointment_groups_stream(
+        &self,
+        query: &AppointmentGroupQuery,
+    ) -> PageStream<'_, AppointmentGroup> {
+        self.paginate_stream(
+            self.make_query("v1/appointment_groups")
+                .query(&query.to_query()),
+            |page: ListAppointmentGroupsResponse| page,
+        )
+    }
+
+    async fn get_appointment_group(
+        &self,
+        appointment_group_id: AppointmentGroupId,
+        include: &[AppointmentGroupInclude],
+    ) -> Result<AppointmentGroup, crate::Error> {
+        let appointment_group: GetAppointmentGroupResponse = self
+            .send(
+                self.make_query(&format!(
+                    "v1/appointment_groups/{}",
+                    appointment_group_id.path_segment()
+                ))
+                .query(&include_query(include)),
+            )
+            .await?;
+
+        Ok(appointment_group)
+    }
+
+    async fn create_appointment_group(
+        &self,
+        appointment_group: &AppointmentGroupForm,
+    ) -> Result<AppointmentGroup, crate::Error> {
+        let appointment_group: CreateAppointmentGroupResponse = self
+            .send(
+                self.make_post("v1/appointment_groups")
+                    .nested_form(&AppointmentGroupBody { appointment_group })?,
+            )
+            .await?;
+
+        Ok(appointment_group)
+    }
+
+    async fn update_appointment_group(
+        &self,
+        appointment_group_id: AppointmentGroupId,
+        appointment_group: &AppointmentGroupForm,
+    ) -> Result<AppointmentGroup, crate::Error> {
+        let appointment_group: UpdateAppointmentGroupResponse = self
+            .send(
+                self.make_put(&format!(
+                    "v1/appointment_groups/{}",
+                    appointment_group_id.path_segment()
+                ))
+                .nested_form(&AppointmentGroupBody { appointment_group })?,
+            )
+            .await?;
+
+        Ok(appointment_group)
+    }
+
+    async fn delete_appointment_group(
+        &self,
+        appointment_group_id: AppointmentGroupId,
+        cancel_reason: Option<&str>,
+    ) -> Result<AppointmentGroup, crate::Error> {
+        let appointment_group: DeleteAppointmentGroupResponse = self
+            .send(
+                self.make_delete(&format!(
+                    "v1/appointment_groups/{}",
+                    appointment_group_id.path_segment()
+                ))
+                .query(&CancelQuery { cancel_reason }),
+            )
+            .await?;
+
+        Ok(appointment_group)
+    }
+
+    async fn list_appointment_group_users(
+        &self,
+        appointment_group_id: AppointmentGroupId,
+        registration_status: RegistrationStatus,
+    ) -> Result<Vec<ParticipantUser>, crate::Error> {
+        self.list_appointment_group_users_stream(appointment_group_id, registration_status)
+            .try_collect()
+            .await
+    }
+
+    fn list_appointment_group_users_stream(
+        &self,
+        appointment_group_id: AppointmentGroupId,
+        registration_status: RegistrationStatus,
+    ) -> PageStream<'_, ParticipantUser> {
+        self.paginate_stream(
+            self.make_query(&format!(
+                "v1/appointment_groups/{}/users",
+                appointment_group_id.path_segment()
+            ))
+            .query(&[("registration_status", registration_status)]),
+            |page: ListAppointmentGroupUsersResponse| page,
+        )
+    }
+
+    async fn list_appointment_group_groups(
+        &self,
+        appointment_group_id: AppointmentGroupId,
+        registration_status: RegistrationStatus,
+    ) -> Result<Vec<ParticipantGroup>, crate::Error> {
+        self.list_appointment_group_groups_stream(appointment_group_id, registration_status)
+            .try_collect()
+            .await
+    }
+
+    fn list_appointment_group_groups_stream(
+        &self,
+        appointment_group_id: AppointmentGroupId,
+        registration_status: RegistrationStatus,
+    ) -> PageStream<'_, ParticipantGroup> {
+        self.paginate_stream(
+            self.make_query(&format!(
+                "v1/appointment_groups/{}/groups",
+                appointment_group_id.path_segment()
+            ))
+            .query(&[("registration_status", registration_status)]),
+            |page: ListAppointmentGroupGroupsResponse| page,
+        )
+    }
+
+    async fn next_appointment(
+        &self,
+        appointment_group_ids: &[AppointmentGroupId],
+    ) -> Result<Option<CalendarEvent>, crate::Error> {
+        let query: Vec<_> = appointment_group_ids
+            .iter()
+            .map(|appointment_group_id| ("appointment_group_ids[]", appointment_group_id))
+            .collect();
+
+        let events: NextAppointmentResponse = self
+            .send(
+                self.make_query("v1/appointment_groups/next_appointment")
+                    .query(&query),
+            )
+            .await?;
+
+        Ok(events.into_iter().next())
+    }
+
+    async fn reserve_time_slot(
+        &self,
+        time_slot_id: CalendarEventId,
+        reservation: &ReservationForm,
+    ) -> Result<CalendarEvent, crate::Error> {
+        let path = match &reservation.participant_id {
+            Some(participant_id) => format!(
+                "v1/calendar_events/{}/reservations/{participant_id}",
+                time_slot_id.path_segment()
+            ),
+            None => format!(
+                "v1/calendar_events/{}/reservations",
+                time_slot_id.path_segment()
+            ),
+        };
+
+        let event: ReserveTimeSlotResponse = self
+            .send(self.make_post(&path).nested_form(&ReservationBody {
+                comments: reservation.comments.as_deref(),
+                cancel_existing: reservation.cancel_existing,
+            })?)
+            .await?;
+
+        Ok(event)
+    }
+
+    async fn unreserve_time_slot(
+        &self,
+        reservation_id: CalendarEventId,
+        cancel_reason: Option<&str>,
+    ) -> Result<CalendarEvent, crate::Error> {
+        let event: UnreserveTimeSlotResponse = self
+            .send(
+                self.make_delete(&format!(
+                    "v1/calendar_events/{}",
+                    reservation_id.path_segment()
+                ))
+                .query(&CancelQuery { cancel_reason }),
+            )
+            .await?;
+
+        Ok(event)
+    }
+}
@@ -1,18 +1,24 @@
 use async_trait::async_trait;
+use futures::TryStreamExt;
 use serde::{Deserialize, Serialize};
 
-use crate::CanvasClient;
+#[cfg(feature = "ical")]
+use crate::{
+    extensions::calendar_events::{ical::IcsCalendar, CalendarEvent},
+    CalendarEventsExt,
+};
+use crate::{form::NestedForm, AccountId, CanvasClient, PageStream};
 
 #[derive(Deserialize, Debug)]
 pub struct AccountCalendar {
     /// The ID of the account associated with this calendar.
-    id: u64,
+    id: AccountId,
     /// The name of the account associated with this calendar.
     name: String,
     /// The account's parent ID. `None` if this is the root account.
-    parent_account_id: Option<u64>,
+    parent_account_id: Option<AccountId>,
     /// The ID of the root account. `None` if this is the root account.
-    root_account_id: Option<u64>,
+    root_account_id: Option<AccountId>,
     /// Whether this calendar is visible to users.
     visible: bool,
     /// Number of this account's direct sub-accounts.
@@ -31,8 +37,8 @@ pub struct AccountCalendar {
 
 impl AccountCalendar {
     /// The ID of the account associated with this calendar.
-    pub fn id(&self) -> u64 {
-        self.id
+    pub fn id(&self) -> &AccountId {
+        &self.id
     }
 
     /// The name of the account associated with this calendar.
@@ -41,13 +47,13 @@ impl AccountCalendar {
     }
 
     /// The account's parent ID. `None` if this is the root account.
-    pub fn parent_account_id(&self) -> Option<u64> {
-        self.parent_account_id
+    pub fn parent_account_id(&self) -> Option<&AccountId> {
+        self.parent_account_id.as_ref()
     }
 
     /// The ID of the root account. `None` if this is the root account.
-    pub fn root_account_id(&self) -> Option<u64> {
-        self.root_account_id
+    pub fn root_account_id(&self) -> Option<&AccountId> {
+        self.root_account_id.as_ref()
     }
 
     /// Whether this calendar is visible to users.
@@ -89,8 +95,8 @@ impl AccountCalendar {
 #[derive(Serialize)]
 pub struct AccountVisibility {
     /// The account's id.
-    id: u64,
-    #[serde(rename = "visible")]
+    id: AccountId,
+    #[serde(rename = "visible", serialize_with = "Visibility::serialize_as_bool")]
     /// Indicates the visibility of the account.
     visibility: Visibility,
 }
@@ -99,7 +105,7 @@ impl AccountVisibility {
     /// For use in `CalendarExt::set_multiple_account_calendar_visible`.
     ///
     /// `id` is the account's id, while `visible` determines whether the account calendar is visible.
-    pub fn new(id: u64, visibility: Visibility) -> AccountVisibility {
+    pub fn new(id: AccountId, visibility: Visibility) -> AccountVisibility {
         Self { id, visibility }
     }
 }
@@ -118,6 +124,20 @@ impl Visibility {
             Visibility::Hidden => false,
         }
     }
+
+    fn serialize_as_bool<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: serde::Serializer,
+    {
+        serializer.serialize_bool(self.as_bool())
+    }
+}
+
+/// The body of a bulk visibility update, which Canvas reads as a JSON array.
+#[derive(Serialize)]
+struct AccountVisibilities<'a> {
+    #[serde(rename = "_json")]
+    account_calendars: &'a [AccountVisibility],
 }
 
 #[async_trait]
@@ -131,6 +151,11 @@ pub trait CalendarExt {
     /// [See docs](https://canvas.instructure.com/doc/api/account_calendars.html#method.account_calendars_api.index).
     async fn all_calendars(&self) -> Result<Vec<AccountCalendar>, crate::Error>;
 
+    /// Streams the account calendars available to the current user, fetching each page as it is consumed.
+    ///
+    /// See [`CalendarExt::all_calendars`].
+    fn all_calendars_stream(&self) -> PageStream<'_, AccountCalendar>;
+
     /// Returns a paginated list of account calendars available to the current user that match the specified search term.
     ///
     /// Includes visible account calendars where the user has an account association.
@@ -145,12 +170,17 @@ pub trait CalendarExt {
         search_term: &str,
     ) -> Result<Vec<AccountCalendar>, crate::Error>;
 
+    /// Streams the account calendars available to the current user that match the specified search term, fetching each page as it is consumed.
+    ///
+    /// See [`CalendarExt::search_calendars`].
+    fn search_calendars_stream(&self, search_term: &str) -> PageStream<'_, AccountCalendar>;
+
     /// Get details about a specific account calendar.
     ///
     /// [See docs](https://canvas.instructure.com/doc/api/account_calendars.html#method.account_calendars_api.show).
     async fn calendar_by_account_id(
         &self,
-        account_id: u64,
+        account_id: AccountId,
     ) -> Result<AccountCalendar, crate::Error>;
 
     /// Set an account calendar as hidden or visible.
@@ -162,7 +192,7 @@ pub trait CalendarExt {
     /// [See docs](https://canvas.instructure.com/doc/api/account_calendars.html#method.account_calendars_api.update).
     async fn set_account_calendar_visible(
         &self,
-        account_id: u64,
+        account_id: AccountId,
         visibility: Visibility,
     ) -> Result<AccountCalendar, crate::Error>;
 
@@ -171,7 +201,7 @@ pub trait CalendarExt {
     /// [See docs](https://canvas.instructure.com/doc/api/account_calendars.html#method.account_calendars_api.bulk_update)
     async fn set_many_account_calendars_visible(
         &self,
-        account_id: u64,
+        account_id: AccountId,
         account_calendars: &[AccountVisibility],
     ) -> Result<AccountCalendar, crate::Error>;
 
@@ -186,10 +216,19 @@ pub trait CalendarExt {
     /// [See docs](https://canvas.instructure.com/doc/api/account_calendars.html#method.account_calendars_api.all_calendars).
     async fn all_account_calendars(
         &self,
-        account_id: u64,
+        account_id: AccountId,
         filter: Visibility,
     ) -> Result<Vec<AccountCalendar>, crate::Error>;
 
+    /// Streams the account calendars for the provided account and its first level of sub-accounts, fetching each page as it is consumed.
+    ///
+    /// See [`CalendarExt::all_account_calendars`].
+    fn all_account_calendars_stream(
+        &self,
+        account_id: AccountId,
+        filter: Visibility,
+    ) -> PageStream<'_, AccountCalendar>;
+
     /// Returns a paginated list of account calendars for the provided account and its first level of sub-accounts that matches the specified `search_term`.
     ///
     /// `filter` determines the type of accounts that will be included in the search.
@@ -203,15 +242,49 @@ pub trait CalendarExt {
     /// [See docs](https://canvas.instructure.com/doc/api/account_calendars.html#method.account_calendars_api.all_calendars).
     async fn search_all_account_calendars(
         &self,
-        account_id: u64,
+        account_id: AccountId,
         search_term: &str,
         filter: Visibility,
     ) -> Result<Vec<AccountCalendar>, crate::Error>;
 
+    /// Streams the account calendars for the provided account and its first level of sub-accounts that match the specified `search_term`, fetching each page as it is consumed.
+    ///
+    /// See [`CalendarExt::search_all_account_calendars`].
+    fn search_all_account_calendars_stream(
+        &self,
+        account_id: AccountId,
+        search_term: &str,
+        filter: Visibility,
+    ) -> PageStream<'_, AccountCalendar>;
+
     /// Returns the number of visible account calendars.
     ///
     /// [See docs](https://canvas.instructure.com/doc/api/account_calendars.html#method.account_calendars_api.visible_calendars_count).
-    async fn count_account_visible_calendars(&self, account_id: u64) -> Result<u64, crate::Error>;
+    async fn count_account_visible_calendars(
+        &self,
+        account_id: AccountId,
+    ) -> Result<u64, crate::Error>;
+
+    /// Creates every event of an iCalendar file on the calendar of an account,
+    /// such as to bulk load an external academic calendar.
+    ///
+    /// Events are created one at a time, in the order of the file. If one
+    /// fails to be created, the error is returned and the events before it
+    /// remain.
+    ///
+    /// Returns the created events.
+    ///
+    /// # Example
+    /// ```rs
+    /// let calendar = IcsCalendar::parse(&std::fs::read_to_string("academic.ics")?)?;
+    /// let events = client.import_ics(AccountId::new(1), &calendar).await?;
+    /// ```
+    #[cfg(feature = "ical")]
+    async fn import_ics(
+        &self,
+        account_id: AccountId,
+        calendar: &IcsCalendar,
+    ) -> Result<Vec<CalendarEvent>, crate::Error>;
 }
 
 // https://canvas.instructure.com/doc/api/account_calendars.html#method.account_calendars_api.index
@@ -241,40 +314,42 @@ struct CountAccountVisibleCalendarsResponse {
 #[async_trait]
 impl CalendarExt for CanvasClient {
     async fn all_calendars(&self) -> Result<Vec<AccountCalendar>, crate::Error> {
-        let accounts: AccountCalendarsResponse = self
-            .make_query("v1/account_calendars")
-            .send()
-            .await?
-            .json()
-            .await?;
+        self.all_calendars_stream().try_collect().await
+    }
 
-        Ok(accounts.account_calendars)
+    fn all_calendars_stream(&self) -> PageStream<'_, AccountCalendar> {
+        self.paginate_stream(
+            self.make_query("v1/account_calendars"),
+            |page: AccountCalendarsResponse| page.account_calendars,
+        )
     }
 
     async fn search_calendars(
         &self,
         search_term: &str,
     ) -> Result<Vec<AccountCalendar>, crate::Error> {
-        let accounts: AccountCalendarsResponse = self
-            .make_query("v1/account_calendars")
-            .query(&[("search_term", search_term)])
-            .send()
-            .await?
-            .json()
-            .await?;
+        self.search_calendars_stream(search_term)
+            .try_collect()
+            .await
+    }
 
-        Ok(accounts.account_calendars)
+    fn search_calendars_stream(&self, search_term: &str) -> PageStream<'_, AccountCalendar> {
+        self.paginate_stream(
+            self.make_query("v1/account_calendars")
+                .query(&[("search_term", search_term)]),
+            |page: AccountCalendarsResponse| page.account_calendars,
+        )
     }
 
     async fn calendar_by_account_id(
         &self,
-        account_id: u64,
+        account_id: AccountId,
     ) -> Result<AccountCalendar, crate::Error> {
         let account: AccountCalendarResponse = self
-            .make_query(&format!("v1/account_calendar/{account_id}"))
-            .send()
-            .await?
-            .json()
+            .send(self.make_query(&format!(
+                "v1/account_calendar/{}",
+                account_id.path_segment()
+            )))
             .await?;
 
         Ok(account)
@@ -282,15 +357,17 @@ impl CalendarExt for CanvasClient {
 
     async fn set_account_calendar_visible(
         &self,
-        account_id: u64,
+        account_id: AccountId,
         visible: Visibility,
     ) -> Result<AccountCalendar, crate::Error> {
         let account: SetAccountCalendarVisibleResponse = self
-            .make_put(&format!("v1/account_calendar/{account_id}"))
-            .form(&[("visible", visible.as_bool())])
-            .send()
-            .await?
-            .json()
+            .send(
+                self.make_put(&format!(
+                    "v1/account_calendar/{}",
+                    account_id.path_segment()
+                ))
+                .form(&[("visible", visible.as_bool())]),
+            )
             .await?;
 
         Ok(account)
@@ -298,15 +375,17 @@ impl CalendarExt for CanvasClient {
 
     async fn set_many_account_calendars_visible(
         &self,
-        account_id: u64,
+        account_id: AccountId,
         account_calendars: &[AccountVisibility],
     ) -> Result<AccountCalendar, crate::Error> {
         let account: SetManyAccountCalendarsVisibleResponse = self
-            .make_put(&format!("v1/accounts/{account_id}/account_calendars"))
-            .form(account_calendars)
-            .send()
-            .await?
-            .json()
+            .send(
+                self.make_put(&format!(
+                    "v1/accounts/{}/account_calendars",
+                    account_id.path_segment()
+                ))
+                .nested_form(&AccountVisibilities { account_calendars })?,
+            )
             .await?;
 
         Ok(account)
@@ -314,46 +393,84 @@ impl CalendarExt for CanvasClient {
 
     async fn all_account_calendars(
         &self,
-        account_id: u64,
+        account_id: AccountId,
         filter: Visibility,
     ) -> Result<Vec<AccountCalendar>, crate::Error> {
-        let accounts: AllAccountCalendarsResponse = self
-            .make_query(&format!("v1/accounts/{account_id}/account_calendars"))
-            .query(&[("filter", filter)])
-            .send()
-            .await?
-            .json()
-            .await?;
+        self.all_account_calendars_stream(account_id, filter)
+            .try_collect()
+            .await
+    }
 
-        Ok(accounts.account_calendars)
+    fn all_account_calendars_stream(
+        &self,
+        account_id: AccountId,
+        filter: Visibility,
+    ) -> PageStream<'_, AccountCalendar> {
+        self.paginate_stream(
+            self.make_query(&format!(
+                "v1/accounts/{}/account_calendars",
+                account_id.path_segment()
+            ))
+            .query(&[("filter", filter)]),
+            |page: AllAccountCalendarsResponse| page.account_calendars,
+        )
     }
 
     async fn search_all_account_calendars(
         &self,
-        account_id: u64,
+        account_id: AccountId,
         search_term: &str,
         filter: Visibility,
     ) -> Result<Vec<AccountCalendar>, crate::Error> {
-        let accounts: AllAccountCalendarsResponse = self
-            .make_query(&format!("v1/accounts/{account_id}/account_calendars"))
-            .query(&[("search_term", search_term)])
-            .query(&[("filter", filter)])
-            .send()
-            .await?
-            .json()
-            .await?;
+        self.search_all_account_calendars_stream(account_id, search_term, filter)
+            .try_collect()
+            .await
+    }
 
-        Ok(accounts.account_calendars)
+    fn search_all_account_calendars_stream(
+        &self,
+        account_id: AccountId,
+        search_term: &str,
+        filter: Visibility,
+    ) -> PageStream<'_, AccountCalendar> {
+        self.paginate_stream(
+            self.make_query(&format!(
+                "v1/accounts/{}/account_calendars",
+                account_id.path_segment()
+            ))
+            .query(&[("search_term", search_term)])
+            .query(&[("filter", filter)]),
+            |page: AllAccountCalendarsResponse| page.account_calendars,
+        )
     }
 
-    async fn count_account_visible_calendars(&self, account_id: u64) -> Result<u64, crate::Error> {
+    async fn count_account_visible_calendars(
+        &self,
+        account_id: AccountId,
+    ) -> Result<u64, crate::Error> {
         let count: CountAccountVisibleCalendarsResponse = self
-            .make_query(&format!("v1/accounts/{account_id}/visible_calendars_count"))
-            .send()
-            .await?
-            .json()
+            .send(self.make_query(&format!(
+                "v1/accounts/{}/visible_calendars_count",
+                account_id.path_segment()
+            )))
             .await?;
 
         Ok(count.count)
     }
+
+    #[cfg(feature = "ical")]
+    async fn import_ics(
+        &self,
+        account_id: AccountId,
+        calendar: &IcsCalendar,
+    ) -> Result<Vec<CalendarEvent>, crate::Error> {
+        let account_calendar = self.calendar_by_account_id(account_id).await?;
+
+        let mut events = Vec::with_capacity(calendar.events().len());
+        for form in calendar.to_forms(account_calendar.asset_string()) {
+            events.push(self.create_calendar_event(&form).await?);
+        }
+
+        Ok(events)
+    }
 }
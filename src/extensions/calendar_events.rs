@@ -0,0 +1,758 @@
+use async_trait::async_trait;
+use futures::TryStreamExt;
+use serde::{Deserialize, Deserializer, Serialize};
+
+use crate::{form::NestedForm, CalendarEventId, CanvasClient, DateTime, PageStream};
+
+#[cfg(feature = "ical")]
+pub mod ical;
+
+/// An event on a calendar, such as that of a course, group or user.
+///
+/// [See docs](https://canvas.instructure.com/doc/api/calendar_events.html#CalendarEvent).
+#[derive(Deserialize, Debug, Clone)]
+pub struct CalendarEvent {
+    /// The ID of the event.
+    id: CalendarEventId,
+    /// The title of the event.
+    #[serde(default)]
+    title: String,
+    /// When the event starts. `None` for undated events.
+    #[serde(default, with = "crate::datetime::rfc3339::option")]
+    start_at: Option<DateTime>,
+    /// When the event ends. `None` for undated events.
+    #[serde(default, with = "crate::datetime::rfc3339::option")]
+    end_at: Option<DateTime>,
+    /// The HTML description of the event.
+    description: Option<String>,
+    /// The name of the location of the event.
+    location_name: Option<String>,
+    /// The address of the location of the event.
+    location_address: Option<String>,
+    /// The context of the event, such as `course_123`.
+    context_code: String,
+    /// The context the event is shown in, if it differs from
+    /// [`CalendarEvent::context_code`], such as for appointment slots.
+    effective_context_code: Option<String>,
+    /// The name of the context of the event.
+    context_name: Option<String>,
+    /// The state of the event, `active`, `locked` or `deleted`.
+    workflow_state: String,
+    /// Whether the event is hidden, such as a course event with section
+    /// specific child events.
+    #[serde(default)]
+    hidden: bool,
+    /// The ID of the parent of a section specific child event.
+    parent_event_id: Option<CalendarEventId>,
+    /// The number of child events.
+    #[serde(default)]
+    child_events_count: u64,
+    /// The section specific child events of the event.
+    #[serde(default)]
+    child_events: Vec<CalendarEvent>,
+    /// The API url of the event.
+    url: Option<String>,
+    /// The url of the event in the Canvas web interface.
+    html_url: Option<String>,
+    /// Whether the event lasts all day.
+    #[serde(default)]
+    all_day: bool,
+    /// The date of an all day event, such as `2024-06-01`.
+    all_day_date: Option<String>,
+    /// When the event was created.
+    #[serde(default, with = "crate::datetime::rfc3339::option")]
+    created_at: Option<DateTime>,
+    /// When the event was last updated.
+    #[serde(default, with = "crate::datetime::rfc3339::option")]
+    updated_at: Option<DateTime>,
+    /// The UUID of the recurring series the event belongs to.
+    series_uuid: Option<String>,
+    /// The recurrence rule of the series the event belongs to, such as
+    /// `FREQ=WEEKLY;INTERVAL=1;COUNT=3`.
+    rrule: Option<String>,
+    /// A human readable description of the recurrence rule.
+    series_natural_language: Option<String>,
+    /// Whether the event is the first event of its series.
+    series_head: Option<bool>,
+    /// Whether the event is a blackout date on a course pacing calendar.
+    #[serde(default)]
+    blackout_date: bool,
+    /// The other events created along with the event, as duplicates or as
+    /// the rest of a recurring series.
+    #[serde(default, deserialize_with = "duplicates")]
+    duplicates: Vec<CalendarEvent>,
+}
+
+impl CalendarEvent {
+    /// The ID of the event.
+    pub fn id(&self) -> &CalendarEventId {
+        &self.id
+    }
+
+    /// The title of the event.
+    pub fn title(&self) -> &str {
+        &self.title
+    }
+
+    /// When the event starts. `None` for undated events.
+    pub fn start_at(&self) -> Option<DateTime> {
+        self.start_at
+    }
+
+    /// When the event ends. `None` for undated events.
+    pub fn end_at(&self) -> Option<DateTime> {
+        self.end_at
+    }
+
+    /// The HTML description of the event.
+    pub fn description(&self) -> Option<&str> {
+        self.description.as_deref()
+    }
+
+    /// The name of the location of the event.
+    pub fn location_name(&self) -> Option<&str> {
+        self.location_name.as_deref()
+    }
+
+    /// The address of the location of the event.
+    pub fn location_address(&self) -> Option<&str> {
+        self.location_address.as_deref()
+    }
+
+    /// The context of the event, such as `course_123`.
+    pub fn context_code(&self) -> &str {
+        &self.context_code
+    }
+
+    /// The context the event is shown in, if it differs from
+    /// [`CalendarEvent::context_code`], such as for appointment slots.
+    pub fn effective_context_code(&self) -> Option<&str> {
+        self.effective_context_code.as_deref()
+    }
+
+    /// The name of the context of the event.
+    pub fn context_name(&self) -> Option<&str> {
+        self.context_name.as_deref()
+    }
+
+    /// The state of the event, `active`, `locked` or `deleted`.
+    pub fn workflow_state(&self) -> &str {
+        &self.workflow_state
+    }
+
+    /// Whether the event is hidden, such as a course event with section
+    /// specific child events.
+    pub fn hidden(&self) -> bool {
+        self.hidden
+    }
+
+    /// The ID of the parent of a section specific child event.
+    pub fn parent_event_id(&self) -> Option<&CalendarEventId> {
+        self.parent_event_id.as_ref()
+    }
+
+    /// The number of child events.
+    pub fn child_events_count(&self) -> u64 {
+        self.child_events_count
+    }
+
+    /// The section specific child events of the event.
+    pub fn child_events(&self) -> &[CalendarEvent] {
+        &self.child_events
+    }
+
+    /// The API url of the event.
+    pub fn url(&self) -> Option<&str> {
+        self.url.as_deref()
+    }
+
+    /// The url of the event in the Canvas web interface.
+    pub fn html_url(&self) -> Option<&str> {
+        self.html_url.as_deref()
+    }
+
+    /// Whether the event lasts all day.
+    pub fn all_day(&self) -> bool {
+        self.all_day
+    }
+
+    /// The date of an all day event, such as `2024-06-01`.
+    pub fn all_day_date(&self) -> Option<&str> {
+        self.all_day_date.as_deref()
+    }
+
+    /// When the event was created.
+    pub fn created_at(&self) -> Option<DateTime> {
+        self.created_at
+    }
+
+    /// When the event was last updated.
+    pub fn updated_at(&self) -> Option<DateTime> {
+        self.updated_at
+    }
+
+    /// The UUID of the recurring series the event belongs to.
+    pub fn series_uuid(&self) -> Option<&str> {
+        self.series_uuid.as_deref()
+    }
+
+    /// The recurrence rule of the series the event belongs to, such as
+    /// `FREQ=WEEKLY;INTERVAL=1;COUNT=3`.
+    pub fn rrule(&self) -> Option<&str> {
+        self.rrule.as_deref()
+    }
+
+    /// A human readable description of the recurrence rule.
+    pub fn series_natural_language(&self) -> Option<&str> {
+        self.series_natural_language.as_deref()
+    }
+
+    /// Whether the event is the first event of its series.
+    pub fn series_head(&self) -> Option<bool> {
+        self.series_head
+    }
+
+    /// Whether the event is a blackout date on a course pacing calendar.
+    pub fn blackout_date(&self) -> bool {
+        self.blackout_date
+    }
+
+    /// The other events created along with the event, as duplicates or as
+    /// the rest of a recurring series. Only returned by
+    /// [`CalendarEventsExt::create_calendar_event`].
+    pub fn duplicates(&self) -> &[CalendarEvent] {
+        &self.duplicates
+    }
+}
+
+/// Canvas wraps each duplicate as `{"calendar_event": {...}}`.
+fn duplicates<'de, D>(deserializer: D) -> Result<Vec<CalendarEvent>, D::Error>
+where
+    D: Deserializer<'de>,
+{
+    #[derive(Deserialize)]
+    struct Duplicate {
+        calendar_event: CalendarEvent,
+    }
+
+    let duplicates = Option::<Vec<Duplicate>>::deserialize(deserializer)?;
+
+    Ok(duplicates
+        .into_iter()
+        .flatten()
+        .map(|duplicate| duplicate.calendar_event)
+        .collect())
+}
+
+/// The kind of calendar items to list.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum CalendarEventType {
+    Event,
+    Assignment,
+}
+
+impl CalendarEventType {
+    fn as_str(&self) -> &'static str {
+        match self {
+            Self::Event => "event",
+            Self::Assignment => "assignment",
+        }
+    }
+}
+
+/// Which calendar events [`CalendarEventsExt::list_calendar_events`] lists.
+#[derive(Debug, Clone, Default)]
+pub struct CalendarEventQuery {
+    /// The kind of items to list. Defaults to events.
+    event_type: Option<CalendarEventType>,
+    /// The contexts to list events from, such as `course_123`.
+    context_codes: Vec<String>,
+    /// Only list events that end on or after this time.
+    start_date: Option<DateTime>,
+    /// Only list events that start on or before this time.
+    end_date: Option<DateTime>,
+    /// Whether to only list undated events.
+    undated: bool,
+    /// Whether to list every event, ignoring the date range.
+    all_events: bool,
+}
+
+impl CalendarEventQuery {
+    /// Creates a new [`CalendarEventQuery`] for today's events in the
+    /// current user's calendars.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Sets the kind of items to list. Defaults to events.
+    pub fn set_event_type(mut self, event_type: CalendarEventType) -> Self {
+        self.event_type = Some(event_type);
+
+        self
+    }
+
+    /// Sets the contexts to list events from, such as `course_123` or
+    /// `user_1`. Canvas limits the number of contexts, which defaults to the
+    /// user's own calendar.
+    pub fn set_context_codes(mut self, context_codes: Vec<String>) -> Self {
+        self.context_codes = context_codes;
+
+        self
+    }
+
+    /// Sets the range of events to list, from events that end on or after
+    /// `start_date` to events that start on or before `end_date`.
+    pub fn set_date_range(mut self, start_date: DateTime, end_date: DateTime) -> Self {
+        self.start_date = Some(start_date);
+        self.end_date = Some(end_date);
+
+        self
+    }
+
+    /// Sets whether to only list undated events. Defaults to false.
+    pub fn set_undated(mut self, undated: bool) -> Self {
+        self.undated = undated;
+
+        self
+    }
+
+    /// Sets whether to list every event, ignoring the date range. Defaults to
+    /// false.
+    pub fn set_all_events(mut self, all_events: bool) -> Self {
+        self.all_events = all_events;
+
+        self
+    }
+
+    /// The query string parameters of the query.
+    fn to_query(&self) -> Vec<(&'static str, String)> {
+        let mut query = Vec::new();
+        if let Some(event_type) = self.event_type {
+            query.push(("type", event_type.as_str().to_string()));
+        }
+        query.extend(
+            self.context_codes
+                .iter()
+                .map(|context_code| ("context_codes[]", context_code.clone())),
+        );
+        if self.undated {
+            query.push(("undated", true.to_string()));
+        }
+        if self.all_events {
+            query.push(("all_events", true.to_string()));
+        }
+
+        query
+    }
+
+    /// The date range parameters of the query. These are serialized by the
+    /// request, which fails if a timestamp cannot be formatted.
+    fn date_range(&self) -> DateRangeQuery {
+        DateRangeQuery {
+            start_date: self.start_date,
+            end_date: self.end_date,
+        }
+    }
+}
+
+#[derive(Serialize)]
+struct DateRangeQuery {
+    #[serde(
+        with = "crate::datetime::rfc3339::option",
+        skip_serializing_if = "Option::is_none"
+    )]
+    start_date: Option<DateTime>,
+    #[serde(
+        with = "crate::datetime::rfc3339::option",
+        skip_serializing_if = "Option::is_none"
+    )]
+    end_date: Option<DateTime>,
+}
+
+/// How often duplicates of an event repeat.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum DuplicateFrequency {
+    Daily,
+    Weekly,
+    Monthly,
+}
+
+/// Creates copies of an event along with it, at a regular interval.
+#[derive(Debug, Clone, Serialize)]
+pub struct Duplicate {
+    /// The number of copies to create.
+    count: u32,
+    /// The number of `frequency` periods between copies.
+    interval: u32,
+    /// How often the copies repeat.
+    frequency: DuplicateFrequency,
+    /// Whether to append a number to the title of each copy.
+    append_iterator: bool,
+}
+
+impl Duplicate {
+    /// Creates `count` copies of an event, each one `frequency` period after
+    /// the last.
+    pub fn new(count: u32, frequency: DuplicateFrequency) -> Self {
+        Self {
+            count,
+            interval: 1,
+            frequency,
+            append_iterator: false,
+        }
+    }
+
+    /// Sets the number of `frequency` periods between copies. Defaults to 1.
+    pub fn set_interval(mut self, interval: u32) -> Self {
+        self.interval = interval;
+
+        self
+    }
+
+    /// Sets whether to append a number to the title of each copy, such as
+    /// `Lecture 2`. Defaults to false.
+    pub fn set_append_iterator(mut self, append_iterator: bool) -> Self {
+        self.append_iterator = append_iterator;
+
+        self
+    }
+}
+
+/// The fields of a calendar event to create or update.
+///
+/// Fields that are not set are left unchanged by an update.
+#[derive(Debug, Clone, Serialize)]
+pub struct CalendarEventForm {
+    /// The context of the event, such as `course_123`.
+    context_code: String,
+    title: Option<String>,
+    description: Option<String>,
+    #[serde(with = "crate::datetime::rfc3339::option")]
+    start_at: Option<DateTime>,
+    #[serde(with = "crate::datetime::rfc3339::option")]
+    end_at: Option<DateTime>,
+    location_name: Option<String>,
+    location_address: Option<String>,
+    time_zone_edited: Option<String>,
+    all_day: Option<bool>,
+    rrule: Option<String>,
+    blackout_date: Option<bool>,
+    duplicate: Option<Duplicate>,
+}
+
+impl CalendarEventForm {
+    /// Creates a new [`CalendarEventForm`] for an event in `context_code`,
+    /// such as `course_123`, `group_4` or `user_5`.
+    pub fn new(context_code: impl Into<String>) -> Self {
+        Self {
+            context_code: context_code.into(),
+            title: None,
+            description: None,
+            start_at: None,
+            end_at: None,
+            location_name: None,
+            location_address: None,
+            time_zone_edited: None,
+            all_day: None,
+            rrule: None,
+            blackout_date: None,
+            duplicate: None,
+        }
+    }
+
+    /// Sets the title of the event.
+    pub fn set_title(mut self, title: impl Into<String>) -> Self {
+        self.title = Some(title.into());
+
+        self
+    }
+
+    /// Sets the HTML description of the event.
+    pub fn set_description(mut self, description: impl Into<String>) -> Self {
+        self.description = Some(description.into());
+
+        self
+    }
+
+    /// Sets when the event starts and ends.
+    pub fn set_time(mut self, start_at: DateTime, end_at: DateTime) -> Self {
+        self.start_at = Some(start_at);
+        self.end_at = Some(end_at);
+
+        self
+    }
+
+    /// Sets the name of the location of the event.
+    pub fn set_location_name(mut self, location_name: impl Into<String>) -> Self {
+        self.location_name = Some(location_name.into());
+
+        self
+    }
+
+    /// Sets the address of the location of the event.
+    pub fn set_location_address(mut self, location_address: impl Into<String>) -> Self {
+        self.location_address = Some(location_address.into());
+
+        self
+    }
+
+    /// Sets the time zone the event was edited in, such as `America/Denver`,
+    /// which Canvas uses to expand recurrence rules and all day events.
+    pub fn set_time_zone_edited(mut self, time_zone_edited: impl Into<String>) -> Self {
+        self.time_zone_edited = Some(time_zone_edited.into());
+
+        self
+    }
+
+    /// Sets whether the event lasts all day.
+    pub fn set_all_day(mut self, all_day: bool) -> Self {
+        self.all_day = Some(all_day);
+
+        self
+    }
+
+    /// Makes the event a recurring series following an RFC 5545 recurrence
+    /// rule, such as `FREQ=WEEKLY;INTERVAL=1;COUNT=10`.
+    pub fn set_rrule(mut self, rrule: impl Into<String>) -> Self {
+        self.rrule = Some(rrule.into());
+
+        self
+    }
+
+    /// Sets whether the event is a blackout date on a course pacing calendar.
+    pub fn set_blackout_date(mut self, blackout_date: bool) -> Self {
+        self.blackout_date = Some(blackout_date);
+
+        self
+    }
+
+    /// Creates copies of the event along with it. Only used when creating an
+    /// event.
+    pub fn set_duplicate(mut self, duplicate: Duplicate) -> Self {
+        self.duplicate = Some(duplicate);
+
+        self
+    }
+}
+
+/// Which events of a recurring series an update or deletion applies to.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Which {
+    /// Only the given event.
+    One,
+    /// Every event of the series.
+    All,
+    /// The given event and every event after it.
+    Following,
+}
+
+#[async_trait]
+pub trait CalendarEventsExt {
+    /// Returns the calendar events of the current user, or of the contexts
+    /// set with [`CalendarEventQuery::set_context_codes`].
+    ///
+    /// [See docs](https://canvas.instructure.com/doc/api/calendar_events.html#method.calendar_events_api.index).
+    async fn list_calendar_events(
+        &self,
+        query: &CalendarEventQuery,
+    ) -> Result<Vec<CalendarEvent>, crate::Error>;
+
+    /// Streams the calendar events matching `query`, fetching each page as it
+    /// is consumed.
+    ///
+    /// See [`CalendarEventsExt::list_calendar_events`].
+    fn list_calendar_events_stream(
+        &self,
+        query: &CalendarEventQuery,
+    ) -> PageStream<'_, CalendarEvent>;
+
+    /// Returns a calendar event.
+    ///
+    /// [See docs](https://canvas.instructure.com/doc/api/calendar_events.html#method.calendar_events_api.show).
+    async fn get_calendar_event(
+        &self,
+        event_id: CalendarEventId,
+    ) -> Result<CalendarEvent, crate::Error>;
+
+    /// Creates a calendar event.
+    ///
+    /// With [`CalendarEventForm::set_rrule`], a recurring series is created,
+    /// and with [`CalendarEventForm::set_duplicate`], copies of the event. In
+    /// both cases the first event is returned, with the others in
+    /// [`CalendarEvent::duplicates`].
+    ///
+    /// # Example
+    /// ```rs
+    /// let event = client
+    ///     .create_calendar_event(
+    ///         &CalendarEventForm::new("course_123")
+    ///             .set_title("Lecture")
+    ///             .set_time(start_at, end_at)
+    ///             .set_rrule("FREQ=WEEKLY;INTERVAL=1;COUNT=12"),
+    ///     )
+    ///     .await?;
+    /// ```
+    ///
+    /// [See docs](https://canvas.instructure.com/doc/api/calendar_events.html#method.calendar_events_api.create).
+    async fn create_calendar_event(
+        &self,
+        event: &CalendarEventForm,
+    ) -> Result<CalendarEvent, crate::Error>;
+
+    /// Updates a calendar event, or with `which`, several events of its
+    /// recurring series.
+    ///
+    /// Returns every updated event.
+    ///
+    /// [See docs](https://canvas.instructure.com/doc/api/calendar_events.html#method.calendar_events_api.update).
+    async fn update_calendar_event(
+        &self,
+        event_id: CalendarEventId,
+        event: &CalendarEventForm,
+        which: Option<Which>,
+    ) -> Result<Vec<CalendarEvent>, crate::Error>;
+
+    /// Deletes a calendar event, or with `which`, several events of its
+    /// recurring series. `cancel_reason` is sent to the participants of the
+    /// event.
+    ///
+    /// Returns every deleted event.
+    ///
+    /// [See docs](https://canvas.instructure.com/doc/api/calendar_events.html#method.calendar_events_api.destroy).
+    async fn delete_calendar_event(
+        &self,
+        event_id: CalendarEventId,
+        cancel_reason: Option<&str>,
+        which: Option<Which>,
+    ) -> Result<Vec<CalendarEvent>, crate::Error>;
+}
+
+// https://canvas.instructure.com/doc/api/calendar_events.html#method.calendar_events_api.index
+type ListCalendarEventsResponse = Vec<CalendarEvent>;
+
+// https://canvas.instructure.com/doc/api/calendar_events.html#method.calendar_events_api.show
+type GetCalendarEventResponse = CalendarEvent;
+
+// https://canvas.instructure.com/doc/api/calendar_events.html#method.calendar_events_api.create
+type CreateCalendarEventResponse = CalendarEvent;
+
+// https://canvas.instructure.com/doc/api/calendar_events.html#method.calendar_events_api.update
+// a single event is returned, unless several events of a series are changed
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum SeriesResponse {
+    One(Box<CalendarEvent>),
+    Many(Vec<CalendarEvent>),
+}
+
+impl From<SeriesResponse> for Vec<CalendarEvent> {
+    fn from(value: SeriesResponse) -> Self {
+        match value {
+            SeriesResponse::One(event) => vec![*event],
+            SeriesResponse::Many(events) => events,
+        }
+    }
+}
+
+#[derive(Serialize)]
+struct CalendarEventBody<'a> {
+    calendar_event: &'a CalendarEventForm,
+    which: Option<Which>,
+}
+
+#[derive(Serialize)]
+struct DeleteCalendarEventQuery<'a> {
+    cancel_reason: Option<&'a str>,
+    which: Option<Which>,
+}
+
+#[async_trait]
+impl CalendarEventsExt for CanvasClient {
+    async fn list_calendar_events(
+        &self,
+        query: &CalendarEventQuery,
+    ) -> Result<Vec<CalendarEvent>, crate::Error> {
+        self.list_calendar_events_stream(query).try_collect().await
+    }
+
+    fn list_calendar_events_stream(
+        &self,
+        query: &CalendarEventQuery,
+    ) -> PageStream<'_, CalendarEvent> {
+        self.paginate_stream(
+            self.make_query("v1/calendar_events")
+                .query(&query.to_query())
+                .query(&query.date_range()),
+            |page: ListCalendarEventsResponse| page,
+        )
+    }
+
+    async fn get_calendar_event(
+        &self,
+        event_id: CalendarEventId,
+    ) -> Result<CalendarEvent, crate::Error> {
+        let event: GetCalendarEventResponse = self
+            .send(self.make_query(&format!("v1/calendar_events/{}", event_id.path_segment())))
+            .await?;
+
+        Ok(event)
+    }
+
+    async fn create_calendar_event(
+        &self,
+        event: &CalendarEventForm,
+    ) -> Result<CalendarEvent, crate::Error> {
+        let event: CreateCalendarEventResponse = self
+            .send(
+                self.make_post("v1/calendar_events")
+                    .nested_form(&CalendarEventBody {
+                        calendar_event: event,
+                        which: None,
+                    })?,
+            )
+            .await?;
+
+        Ok(event)
+    }
+
+    async fn update_calendar_event(
+        &self,
+        event_id: CalendarEventId,
+        event: &CalendarEventForm,
+        which: Option<Which>,
+    ) -> Result<Vec<CalendarEvent>, crate::Error> {
+        let events: SeriesResponse = self
+            .send(
+                self.make_put(&format!("v1/calendar_events/{}", event_id.path_segment()))
+                    .nested_form(&CalendarEventBody {
+                        calendar_event: event,
+                        which,
+                    })?,
+            )
+            .await?;
+
+        Ok(events.into())
+    }
+
+    async fn delete_calendar_event(
+        &self,
+        event_id: CalendarEventId,
+        cancel_reason: Option<&str>,
+        which: Option<Which>,
+    ) -> Result<Vec<CalendarEvent>, crate::Error> {
+        let events: SeriesResponse = self
+            .send(
+                self.make_delete(&format!("v1/calendar_events/{}", event_id.path_segment()))
+                    .query(&DeleteCalendarEventQuery {
+                        cancel_reason,
+                        which,
+                    }),
+            )
+            .await?;
+
+        Ok(events.into())
+    }
+}
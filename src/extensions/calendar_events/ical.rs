@@ -0,0 +1,1720 @@
+//! Converts calendar events to and from iCalendar (RFC 5545) text, such as the
+//! `.ics` files calendars are published to and imported from other systems
+//! with.
+//!
+//! [`IcsExport`] writes [`CalendarEvent`]s as a `VCALENDAR`, optionally named
+//! after the [`AccountCalendar`] they belong to. All day events are written as
+//! dates, and a recurring series as its first event with the `RRULE` of the
+//! series. Timed events are written in UTC, or with an [`IcsTimeZone`] in its
+//! local time, along with the `VTIMEZONE` describing it. A recurring series
+//! repeats at the same local time across daylight saving time changes, so
+//! timed series can only be exported with a time zone.
+//!
+//! [`IcsCalendar::parse`] reads the `VEVENT`s of an `.ics` file, resolving
+//! times with a `TZID` through the `VTIMEZONE`s of the file, and turns them
+//! into [`CalendarEventForm`]s. [`CalendarExt::import_ics`](crate::CalendarExt::import_ics)
+//! creates them on an account calendar.
+//!
+//! Overridden occurrences of a recurring event (`RECURRENCE-ID`) and excluded
+//! dates (`EXDATE`) have no counterpart in Canvas, and are ignored.
+//!
+//! # Example
+//! ```rs
+//! let time_zone = IcsTimeZone::parse(&std::fs::read_to_string("America-Denver.ics")?)?;
+//! let ics = IcsExport::new(&events)
+//!     .set_calendar(&calendar)
+//!     .set_time_zone(&time_zone)
+//!     .to_ics()?;
+//!
+//! let calendar = IcsCalendar::parse(&std::fs::read_to_string("academic.ics")?)?;
+//! let events = client.import_ics(AccountId::new(1), &calendar).await?;
+//! ```
+
+use std::collections::{HashMap, HashSet};
+
+use super::{CalendarEvent, CalendarEventForm};
+use crate::{extensions::calendar::AccountCalendar, DateTime};
+
+/// The number of seconds in a day.
+const DAY: i64 = 86_400;
+
+/// The longest line of an iCalendar file, in octets, before it is folded.
+const LINE_LENGTH: usize = 75;
+
+/// Writes calendar events as an iCalendar file with [`IcsExport::to_ics`].
+#[derive(Debug, Clone)]
+pub struct IcsExport<'a> {
+    /// The events to write.
+    events: &'a [CalendarEvent],
+    /// The account calendar the events belong to.
+    calendar: Option<&'a AccountCalendar>,
+    /// The time zone timed events are written in.
+    time_zone: Option<&'a IcsTimeZone>,
+}
+
+impl<'a> IcsExport<'a> {
+    /// Creates a new [`IcsExport`] of `events`.
+    ///
+    /// Undated events are left out, as are the events of a recurring series
+    /// whose first event is also in `events`. Hidden events with section
+    /// specific child events are written as their child events.
+    pub fn new(events: &'a [CalendarEvent]) -> Self {
+        Self {
+            events,
+            calendar: None,
+            time_zone: None,
+        }
+    }
+
+    /// Sets the account calendar the events belong to, which the exported
+    /// calendar is named after.
+    pub fn set_calendar(mut self, calendar: &'a AccountCalendar) -> Self {
+        self.calendar = Some(calendar);
+
+        self
+    }
+
+    /// Sets the time zone timed events are written in, which calendar clients
+    /// also display the calendar in. Without one, times are written in UTC.
+    pub fn set_time_zone(mut self, time_zone: &'a IcsTimeZone) -> Self {
+        self.time_zone = Some(time_zone);
+
+        self
+    }
+
+    /// Writes the events as an iCalendar file.
+    ///
+    /// Fails if a timed recurring series is exported without a time zone, as
+    /// its occurrences would shift by an hour across daylight saving time
+    /// changes.
+    pub fn to_ics(&self) -> Result<String, crate::Error> {
+        let events = self.flattened_events();
+        if self.time_zone.is_none() {
+            if let Some(series) = events
+                .iter()
+                .find(|event| is_series_head(event) && all_day_date(event).is_none())
+            {
+                return Err(crate::Error::IcsExport {
+                    message: format!(
+                        "event {} is a recurring series, which needs a time zone",
+                        series.id()
+                    ),
+                });
+            }
+        }
+
+        let mut writer = IcsWriter {
+            ics: String::new(),
+            time_zone: self.time_zone,
+        };
+
+        writer.property("BEGIN", "VCALENDAR");
+        writer.property("VERSION", "2.0");
+        writer.property("PRODID", "-//OverHash//canvas-api//EN");
+        writer.property("CALSCALE", "GREGORIAN");
+        writer.property("METHOD", "PUBLISH");
+        if let Some(calendar) = self.calendar {
+            writer.text("X-WR-CALNAME", calendar.name());
+            writer.text("X-WR-RELCALID", calendar.asset_string());
+        }
+        if let Some(time_zone) = self.time_zone {
+            writer.text("X-WR-TIMEZONE", &time_zone.tzid);
+            for line in &time_zone.lines {
+                writer.line(line);
+            }
+        }
+
+        for event in events {
+            writer.event(event);
+        }
+
+        writer.property("END", "VCALENDAR");
+
+        Ok(writer.ics)
+    }
+
+    /// The events to write, with section specific child events in place of
+    /// their parents.
+    fn flattened_events(&self) -> Vec<&'a CalendarEvent> {
+        let events: Vec<&CalendarEvent> = self
+            .events
+            .iter()
+            .flat_map(|event| {
+                if event.hidden() && !event.child_events().is_empty() {
+                    event.child_events().iter().collect()
+                } else {
+                    vec![event]
+                }
+            })
+            .collect();
+
+        // the rest of a series is described by the RRULE of its first event
+        let series_heads: HashSet<&str> = events
+            .iter()
+            .filter(|event| event.series_head() == Some(true))
+            .filter_map(|event| event.series_uuid())
+            .collect();
+
+        events
+            .into_iter()
+            .filter(|event| {
+                event.series_head() == Some(true)
+                    || event
+                        .series_uuid()
+                        .is_none_or(|series_uuid| !series_heads.contains(series_uuid))
+            })
+            .collect()
+    }
+}
+
+/// Writes the content lines of an iCalendar file.
+struct IcsWriter<'a> {
+    ics: String,
+    /// The time zone timed events are written in.
+    time_zone: Option<&'a IcsTimeZone>,
+}
+
+impl IcsWriter<'_> {
+    /// Writes a line, folded onto continuation lines if it is too long.
+    fn line(&mut self, line: &str) {
+        let mut rest = line;
+        // continuation lines start with a space
+        let mut limit = LINE_LENGTH;
+
+        while rest.len() > limit {
+            let mut at = limit;
+            while !rest.is_char_boundary(at) {
+                at -= 1;
+            }
+
+            self.ics.push_str(&rest[..at]);
+            self.ics.push_str("\r\n ");
+            rest = &rest[at..];
+            limit = LINE_LENGTH - 1;
+        }
+
+        self.ics.push_str(rest);
+        self.ics.push_str("\r\n");
+    }
+
+    /// Writes a property whose value is already formatted.
+    fn property(&mut self, name: &str, value: &str) {
+        self.line(&format!("{name}:{value}"));
+    }
+
+    /// Writes a property with a text value.
+    fn text(&mut self, name: &str, value: &str) {
+        self.property(name, &escape_text(value));
+    }
+
+    /// Writes a date-time property, in the local time of the time zone if
+    /// there is one.
+    fn time(&mut self, name: &str, datetime: &DateTime) {
+        let timestamp = crate::datetime::unix_timestamp(datetime);
+
+        match self.time_zone {
+            Some(time_zone) => {
+                let local = format_local(time_zone.rules.local_at(timestamp));
+
+                self.property(
+                    &format!("{name};TZID={}", param_value(&time_zone.tzid)),
+                    &local,
+                );
+            }
+            None => self.property(name, &format_utc(timestamp)),
+        }
+    }
+
+    /// Writes a `VEVENT`. Undated events are skipped.
+    fn event(&mut self, event: &CalendarEvent) {
+        let all_day_date = all_day_date(event);
+        if all_day_date.is_none() && event.start_at().is_none() {
+            return;
+        }
+
+        self.property("BEGIN", "VEVENT");
+        let uid = match event.id().assignment_id() {
+            Some(assignment_id) => format!("event-assignment-{assignment_id}"),
+            None => format!("event-calendar-event-{}", event.id()),
+        };
+        self.property("UID", &uid);
+        let stamp = event
+            .updated_at()
+            .or(event.created_at())
+            .unwrap_or_else(crate::datetime::now);
+        self.property(
+            "DTSTAMP",
+            &format_utc(crate::datetime::unix_timestamp(&stamp)),
+        );
+
+        if let Some(day) = all_day_date {
+            self.property("DTSTART;VALUE=DATE", &format_date(day));
+            self.property("DTEND;VALUE=DATE", &format_date(day + 1));
+        } else if let Some(start_at) = event.start_at() {
+            self.time("DTSTART", &start_at);
+            if let Some(end_at) = event.end_at() {
+                self.time("DTEND", &end_at);
+            }
+        }
+
+        if is_series_head(event) {
+            if let Some(rrule) = event.rrule() {
+                self.property("RRULE", rrule.trim_start_matches("RRULE:"));
+            }
+        }
+
+        self.text("SUMMARY", event.title());
+        let location = [event.location_name(), event.location_address()]
+            .into_iter()
+            .flatten()
+            .filter(|part| !part.is_empty())
+            .collect::<Vec<_>>()
+            .join(", ");
+        if !location.is_empty() {
+            self.text("LOCATION", &location);
+        }
+        if let Some(description) = event.description().filter(|d| !d.is_empty()) {
+            self.text("DESCRIPTION", &html_to_text(description));
+            self.text("X-ALT-DESC;FMTTYPE=text/html", description);
+        }
+        if let Some(html_url) = event.html_url() {
+            self.property("URL", html_url);
+        }
+        if event.workflow_state() == "deleted" {
+            self.property("STATUS", "CANCELLED");
+        }
+
+        self.property("END", "VEVENT");
+    }
+}
+
+/// The day of an all day event, in days since the Unix epoch.
+fn all_day_date(event: &CalendarEvent) -> Option<i64> {
+    event
+        .all_day()
+        .then(|| event.all_day_date().and_then(parse_iso_date))
+        .flatten()
+}
+
+/// Whether the event is the first event of a recurring series.
+fn is_series_head(event: &CalendarEvent) -> bool {
+    event.series_head() == Some(true) && event.rrule().is_some()
+}
+
+/// A time zone to export events in, along with the `VTIMEZONE` that describes
+/// its UTC offsets to calendar clients.
+#[derive(Debug, Clone)]
+pub struct IcsTimeZone {
+    /// The `TZID` of the time zone, such as `America/Denver`.
+    tzid: String,
+    /// The UTC offsets of the time zone.
+    rules: TimeZone,
+    /// The content lines of the `VTIMEZONE`.
+    lines: Vec<String>,
+}
+
+impl IcsTimeZone {
+    /// Parses the first `VTIMEZONE` of an iCalendar file, such as the files
+    /// tzurl.org publishes for each IANA time zone.
+    pub fn parse(ics: &str) -> Result<Self, crate::Error> {
+        let lines = unfold(ics)
+            .into_iter()
+            .map(|(number, line)| ContentLine::parse(number, &line))
+            .collect::<Result<Vec<_>, _>>()?;
+        let components = parse_components(lines)?;
+        let component = components
+            .iter()
+            .flat_map(|component| std::iter::once(component).chain(&component.components))
+            .find(|component| component.name == "VTIMEZONE")
+            .ok_or_else(|| ics_error(1, "the file has no VTIMEZONE"))?;
+        let tzid = component
+            .property("TZID")
+            .ok_or_else(|| ics_error(component.line, "VTIMEZONE has no TZID"))?;
+
+        Ok(Self {
+            tzid: tzid.value.clone(),
+            rules: TimeZone::parse(component)?,
+            lines: component.content_lines(),
+        })
+    }
+
+    /// A time zone with a fixed UTC offset, in seconds, such as
+    /// `America/Phoenix` at `-25200`.
+    pub fn fixed(tzid: impl Into<String>, offset: i32) -> Self {
+        let tzid = tzid.into();
+        let offset = i64::from(offset);
+        let lines = [
+            "BEGIN:VTIMEZONE".to_string(),
+            format!("TZID:{tzid}"),
+            "BEGIN:STANDARD".to_string(),
+            "DTSTART:19700101T000000".to_string(),
+            format!("TZOFFSETFROM:{}", format_utc_offset(offset)),
+            format!("TZOFFSETTO:{}", format_utc_offset(offset)),
+            "END:STANDARD".to_string(),
+            "END:VTIMEZONE".to_string(),
+        ];
+
+        Self {
+            tzid,
+            rules: TimeZone {
+                observances: vec![Observance {
+                    start: 0,
+                    offset_from: offset,
+                    offset_to: offset,
+                    rule: None,
+                }],
+            },
+            lines: lines.into(),
+        }
+    }
+
+    /// The `TZID` of the time zone, such as `America/Denver`.
+    pub fn tzid(&self) -> &str {
+        &self.tzid
+    }
+}
+
+/// The events of an iCalendar file.
+#[derive(Debug, Clone)]
+pub struct IcsCalendar {
+    /// The name of the calendar, from `X-WR-CALNAME`.
+    name: Option<String>,
+    /// The time zone of the calendar, from `X-WR-TIMEZONE`.
+    time_zone: Option<String>,
+    /// The events of the calendar.
+    events: Vec<IcsEvent>,
+}
+
+/// An event of an iCalendar file.
+#[derive(Debug, Clone)]
+pub struct IcsEvent {
+    /// The unique identifier of the event.
+    uid: Option<String>,
+    /// The title of the event.
+    summary: String,
+    /// The HTML description of the event.
+    description: Option<String>,
+    /// The location of the event.
+    location: Option<String>,
+    /// When the event starts. `None` for undated events.
+    start_at: Option<DateTime>,
+    /// When the event ends. `None` for undated events.
+    end_at: Option<DateTime>,
+    /// Whether the event lasts a single whole day.
+    all_day: bool,
+    /// The recurrence rule of the event.
+    rrule: Option<String>,
+    /// The time zone the times of the event were written in.
+    time_zone: Option<String>,
+}
+
+impl IcsCalendar {
+    /// Parses the events of an iCalendar file.
+    ///
+    /// Times with a `TZID` are resolved through the `VTIMEZONE` of the same
+    /// name, and floating times through the `VTIMEZONE` named by
+    /// `X-WR-TIMEZONE`, or as UTC if there is none. Cancelled events are left
+    /// out.
+    pub fn parse(ics: &str) -> Result<Self, crate::Error> {
+        let lines = unfold(ics)
+            .into_iter()
+            .map(|(number, line)| ContentLine::parse(number, &line))
+            .collect::<Result<Vec<_>, _>>()?;
+        let calendar = parse_components(lines)?
+            .into_iter()
+            .find(|component| component.name == "VCALENDAR")
+            .ok_or_else(|| ics_error(1, "the file has no VCALENDAR"))?;
+
+        let time_zones = calendar
+            .components("VTIMEZONE")
+            .map(|component| {
+                let tzid = component
+                    .property("TZID")
+                    .ok_or_else(|| ics_error(component.line, "VTIMEZONE has no TZID"))?;
+
+                Ok((tzid.value.clone(), TimeZone::parse(component)?))
+            })
+            .collect::<Result<HashMap<_, _>, crate::Error>>()?;
+        let time_zone = calendar
+            .property("X-WR-TIMEZONE")
+            .map(|line| unescape_text(&line.value));
+        let resolver = TimeResolver {
+            floating: time_zone.as_deref().and_then(|tzid| time_zones.get(tzid)),
+            time_zones: &time_zones,
+        };
+
+        let events = calendar
+            .components("VEVENT")
+            .filter(|component| {
+                component.property("RECURRENCE-ID").is_none()
+                    && component
+                        .property("STATUS")
+                        .is_none_or(|status| !status.value.eq_ignore_ascii_case("CANCELLED"))
+            })
+            .map(|component| IcsEvent::parse(component, &resolver, time_zone.as_deref()))
+            .collect::<Result<Vec<_>, _>>()?;
+
+        Ok(Self {
+            name: calendar
+                .property("X-WR-CALNAME")
+                .map(|line| unescape_text(&line.value)),
+            time_zone,
+            events,
+        })
+    }
+
+    /// The name of the calendar, from `X-WR-CALNAME`.
+    pub fn name(&self) -> Option<&str> {
+        self.name.as_deref()
+    }
+
+    /// The time zone of the calendar, from `X-WR-TIMEZONE`.
+    pub fn time_zone(&self) -> Option<&str> {
+        self.time_zone.as_deref()
+    }
+
+    /// The events of the calendar.
+    pub fn events(&self) -> &[IcsEvent] {
+        &self.events
+    }
+
+    /// The forms to create each event of the calendar in `context_code`, such
+    /// as `account_1` or `course_123`.
+    pub fn to_forms(&self, context_code: &str) -> Vec<CalendarEventForm> {
+        self.events
+            .iter()
+            .map(|event| event.to_form(context_code))
+            .collect()
+    }
+}
+
+impl IcsEvent {
+    fn parse(
+        component: &Component,
+        resolver: &TimeResolver,
+        calendar_time_zone: Option<&str>,
+    ) -> Result<Self, crate::Error> {
+        let text = |name: &str| {
+            component
+                .property(name)
+                .map(|line| unescape_text(&line.value))
+        };
+
+        let start = component
+            .property("DTSTART")
+            .map(|line| resolver.resolve(line))
+            .transpose()?;
+        let end = match (&start, component.property("DTEND")) {
+            (Some(_), Some(line)) => Some(resolver.resolve(line)?),
+            (Some(start), None) => {
+                let (length, line) = match component.property("DURATION") {
+                    Some(line) => (
+                        parse_duration(&line.value)
+                            .ok_or_else(|| ics_error(line.number, "invalid DURATION"))?,
+                        line.number,
+                    ),
+                    // a date lasts the whole day, and a date-time is
+                    // instantaneous
+                    None if start.date => (DAY, start.line),
+                    None => (0, start.line),
+                };
+                let out_of_range = || ics_error(line, "the event ends out of range");
+
+                Some(ResolvedTime {
+                    timestamp: start
+                        .timestamp
+                        .checked_add(length)
+                        .ok_or_else(out_of_range)?,
+                    local: start.local.checked_add(length).ok_or_else(out_of_range)?,
+                    date: start.date,
+                    tzid: start.tzid.clone(),
+                    line,
+                })
+            }
+            (None, _) => None,
+        };
+
+        let all_day = match (&start, &end) {
+            (Some(start), Some(end)) => start.date && end.date && end.local - start.local == DAY,
+            _ => false,
+        };
+        let (start_at, end_at) = match (start.as_ref(), end.as_ref()) {
+            (Some(start), Some(end)) => (Some(start.to_datetime()?), Some(end.to_datetime()?)),
+            _ => (None, None),
+        };
+
+        let description = component
+            .properties("X-ALT-DESC")
+            .find(|line| {
+                line.param("FMTTYPE")
+                    .is_some_and(|fmttype| fmttype.eq_ignore_ascii_case("text/html"))
+            })
+            .map(|line| unescape_text(&line.value))
+            .or_else(|| text("DESCRIPTION").map(|text| text_to_html(&text)))
+            .filter(|description| !description.is_empty());
+
+        Ok(Self {
+            uid: text("UID"),
+            summary: text("SUMMARY").unwrap_or_default(),
+            description,
+            location: text("LOCATION").filter(|location| !location.is_empty()),
+            start_at,
+            end_at,
+            all_day,
+            rrule: component.property("RRULE").map(|line| line.value.clone()),
+            time_zone: start
+                .and_then(|start| start.tzid)
+                .or(calendar_time_zone.map(str::to_string)),
+        })
+    }
+
+    /// The unique identifier of the event.
+    pub fn uid(&self) -> Option<&str> {
+        self.uid.as_deref()
+    }
+
+    /// The title of the event.
+    pub fn summary(&self) -> &str {
+        &self.summary
+    }
+
+    /// The HTML description of the event. Plain text descriptions are
+    /// converted to HTML.
+    pub fn description(&self) -> Option<&str> {
+        self.description.as_deref()
+    }
+
+    /// The location of the event.
+    pub fn location(&self) -> Option<&str> {
+        self.location.as_deref()
+    }
+
+    /// When the event starts. `None` for undated events.
+    pub fn start_at(&self) -> Option<DateTime> {
+        self.start_at
+    }
+
+    /// When the event ends. `None` for undated events.
+    pub fn end_at(&self) -> Option<DateTime> {
+        self.end_at
+    }
+
+    /// Whether the event lasts a single whole day.
+    pub fn all_day(&self) -> bool {
+        self.all_day
+    }
+
+    /// The recurrence rule of the event, such as
+    /// `FREQ=WEEKLY;INTERVAL=1;COUNT=10`.
+    pub fn rrule(&self) -> Option<&str> {
+        self.rrule.as_deref()
+    }
+
+    /// The time zone the times of the event were written in, such as
+    /// `America/Denver`.
+    pub fn time_zone(&self) -> Option<&str> {
+        self.time_zone.as_deref()
+    }
+
+    /// The form to create the event in `context_code`, such as `account_1` or
+    /// `course_123`.
+    ///
+    /// The time zone of the event is passed on to Canvas to expand its
+    /// recurrence rule in, if it is an IANA time zone name.
+    pub fn to_form(&self, context_code: &str) -> CalendarEventForm {
+        let mut form = CalendarEventForm::new(context_code).set_title(&self.summary);
+
+        if let Some(description) = &self.description {
+            form = form.set_description(description);
+        }
+        if let Some(location) = &self.location {
+            form = form.set_location_name(location);
+        }
+        if let (Some(start_at), Some(end_at)) = (self.start_at, self.end_at) {
+            form = form.set_time(start_at, end_at);
+        }
+        if self.all_day {
+            form = form.set_all_day(true);
+        }
+        if let Some(rrule) = &self.rrule {
+            form = form.set_rrule(rrule);
+        }
+        if let Some(time_zone) = self.time_zone.as_deref().filter(|tz| tz.contains('/')) {
+            form = form.set_time_zone_edited(time_zone);
+        }
+
+        form
+    }
+}
+
+/// A property of an iCalendar file, such as `DTSTART;TZID=UTC:20240601T090000`.
+#[derive(Debug)]
+struct ContentLine {
+    /// The line number the property starts on.
+    number: usize,
+    /// The upper case name of the property.
+    name: String,
+    /// The parameters of the property, with upper case names.
+    params: Vec<(String, String)>,
+    /// The raw value of the property.
+    value: String,
+}
+
+impl ContentLine {
+    fn parse(number: usize, line: &str) -> Result<Self, crate::Error> {
+        let name_end = line
+            .find([';', ':'])
+            .ok_or_else(|| ics_error(number, "expected a property name followed by ':'"))?;
+        let name = line[..name_end].trim().to_ascii_uppercase();
+        let mut rest = &line[name_end..];
+
+        let mut params = Vec::new();
+        while let Some(param) = rest.strip_prefix(';') {
+            let (param_name, param_value) = param
+                .split_once('=')
+                .ok_or_else(|| ics_error(number, "expected a parameter name followed by '='"))?;
+
+            let (value, remainder) = match param_value.strip_prefix('"') {
+                Some(quoted) => {
+                    let end = quoted
+                        .find('"')
+                        .ok_or_else(|| ics_error(number, "unterminated quoted parameter"))?;
+
+                    (&quoted[..end], &quoted[end + 1..])
+                }
+                None => {
+                    let end = param_value.find([';', ':']).unwrap_or(param_value.len());
+
+                    param_value.split_at(end)
+                }
+            };
+
+            params.push((param_name.to_ascii_uppercase(), value.to_string()));
+            rest = remainder;
+        }
+
+        let value = rest
+            .strip_prefix(':')
+            .ok_or_else(|| ics_error(number, format!("{name} has no value")))?;
+
+        Ok(Self {
+            number,
+            name,
+            params,
+            value: value.to_string(),
+        })
+    }
+
+    /// The value of a parameter.
+    fn param(&self, name: &str) -> Option<&str> {
+        self.params
+            .iter()
+            .find(|(param, _)| param == name)
+            .map(|(_, value)| value.as_str())
+    }
+
+    /// The unfolded line of the property.
+    fn to_line(&self) -> String {
+        let mut line = self.name.clone();
+        for (name, value) in &self.params {
+            line.push_str(&format!(";{name}={}", param_value(value)));
+        }
+        line.push(':');
+        line.push_str(&self.value);
+
+        line
+    }
+}
+
+/// A component of an iCalendar file, such as a `VEVENT`.
+#[derive(Debug)]
+struct Component {
+    /// The upper case name of the component.
+    name: String,
+    /// The line number of the `BEGIN` of the component.
+    line: usize,
+    properties: Vec<ContentLine>,
+    components: Vec<Component>,
+}
+
+impl Component {
+    /// The first property named `name`.
+    fn property(&self, name: &str) -> Option<&ContentLine> {
+        self.properties.iter().find(|line| line.name == name)
+    }
+
+    /// The properties named `name`.
+    fn properties<'a>(&'a self, name: &'a str) -> impl Iterator<Item = &'a ContentLine> {
+        self.properties.iter().filter(move |line| line.name == name)
+    }
+
+    /// The nested components named `name`.
+    fn components<'a>(&'a self, name: &'a str) -> impl Iterator<Item = &'a Component> {
+        self.components
+            .iter()
+            .filter(move |component| component.name == name)
+    }
+
+    /// The unfolded lines of the component, from its `BEGIN` to its `END`.
+    fn content_lines(&self) -> Vec<String> {
+        let mut lines = vec![format!("BEGIN:{}", self.name)];
+        lines.extend(self.properties.iter().map(ContentLine::to_line));
+        lines.extend(self.components.iter().flat_map(Component::content_lines));
+        lines.push(format!("END:{}", self.name));
+
+        lines
+    }
+}
+
+/// Joins folded lines, returning each line with its line number.
+fn unfold(ics: &str) -> Vec<(usize, String)> {
+    let mut lines: Vec<(usize, String)> = Vec::new();
+
+    for (index, line) in ics.trim_start_matches('\u{feff}').lines().enumerate() {
+        if let Some(continuation) = line.strip_prefix([' ', '\t']) {
+            if let Some((_, last)) = lines.last_mut() {
+                last.push_str(continuation);
+                continue;
+            }
+        }
+
+        if !line.trim().is_empty() {
+            lines.push((index + 1, line.to_string()));
+        }
+    }
+
+    lines
+}
+
+/// Nests properties into the components delimited by `BEGIN` and `END`.
+fn parse_components(lines: Vec<ContentLine>) -> Result<Vec<Component>, crate::Error> {
+    let mut stack: Vec<Component> = Vec::new();
+    let mut components = Vec::new();
+
+    for line in lines {
+        match line.name.as_str() {
+            "BEGIN" => stack.push(Component {
+                name: line.value.trim().to_ascii_uppercase(),
+                line: line.number,
+                properties: Vec::new(),
+                components: Vec::new(),
+            }),
+            "END" => {
+                let component = stack
+                    .pop()
+                    .filter(|component| component.name.eq_ignore_ascii_case(line.value.trim()))
+                    .ok_or_else(|| {
+                        ics_error(line.number, format!("unexpected END:{}", line.value))
+                    })?;
+
+                match stack.last_mut() {
+                    Some(parent) => parent.components.push(component),
+                    None => components.push(component),
+                }
+            }
+            _ => stack
+                .last_mut()
+                .ok_or_else(|| {
+                    ics_error(
+                        line.number,
+                        format!("{} is outside of a component", line.name),
+                    )
+                })?
+                .properties
+                .push(line),
+        }
+    }
+
+    match stack.pop() {
+        Some(component) => Err(ics_error(
+            component.line,
+            format!("BEGIN:{} is never ended", component.name),
+        )),
+        None => Ok(components),
+    }
+}
+
+/// A time zone defined by a `VTIMEZONE`.
+#[derive(Debug, Clone)]
+struct TimeZone {
+    /// The standard and daylight saving time periods of the time zone.
+    observances: Vec<Observance>,
+}
+
+/// A `STANDARD` or `DAYLIGHT` period of a time zone.
+#[derive(Debug, Clone)]
+struct Observance {
+    /// The local time the observance first starts, in seconds since the Unix
+    /// epoch.
+    start: i64,
+    /// The UTC offset before the observance starts, in seconds.
+    offset_from: i64,
+    /// The UTC offset during the observance, in seconds.
+    offset_to: i64,
+    /// When the observance starts again each year.
+    rule: Option<YearlyRule>,
+}
+
+/// A yearly recurrence on a weekday of a month, such as the second Sunday of
+/// March (`FREQ=YEARLY;BYMONTH=3;BYDAY=2SU`).
+#[derive(Debug, Clone)]
+struct YearlyRule {
+    month: u32,
+    /// The week of the month, counted from the end of the month if negative.
+    week: i64,
+    /// The day of the week, from Sunday as 0.
+    weekday: i64,
+    /// The last local time the observance starts, in seconds since the Unix
+    /// epoch.
+    until: Option<i64>,
+}
+
+impl TimeZone {
+    fn parse(component: &Component) -> Result<Self, crate::Error> {
+        let observances = component
+            .components
+            .iter()
+            .filter(|observance| matches!(observance.name.as_str(), "STANDARD" | "DAYLIGHT"))
+            .map(Observance::parse)
+            .collect::<Result<Vec<_>, _>>()?;
+
+        Ok(Self { observances })
+    }
+
+    /// The UTC offset in effect at a local time, in seconds.
+    fn offset_at(&self, local: i64) -> i64 {
+        self.observances
+            .iter()
+            .filter_map(|observance| {
+                observance
+                    .latest_onset(local)
+                    .map(|onset| (onset, observance.offset_to))
+            })
+            .max_by_key(|(onset, _)| *onset)
+            .map(|(_, offset)| offset)
+            .or_else(|| {
+                // before every observance, the offset the first one changes
+                // from was in effect
+                self.observances
+                    .iter()
+                    .min_by_key(|observance| observance.start)
+                    .map(|observance| observance.offset_from)
+            })
+            .unwrap_or(0)
+    }
+
+    /// The local time at a timestamp, in seconds since the Unix epoch.
+    fn local_at(&self, timestamp: i64) -> i64 {
+        // the offset in effect is the one whose local time is resolved back
+        // to the timestamp
+        self.observances
+            .iter()
+            .flat_map(|observance| [observance.offset_to, observance.offset_from])
+            .map(|offset| timestamp + offset)
+            .find(|local| local - self.offset_at(*local) == timestamp)
+            .unwrap_or_else(|| timestamp + self.offset_at(timestamp))
+    }
+}
+
+impl Observance {
+    fn parse(component: &Component) -> Result<Self, crate::Error> {
+        let property = |name: &str| {
+            component.property(name).ok_or_else(|| {
+                ics_error(component.line, format!("{} has no {name}", component.name))
+            })
+        };
+        let offset = |name: &str| {
+            let line = property(name)?;
+
+            parse_utc_offset(&line.value)
+                .ok_or_else(|| ics_error(line.number, format!("invalid {name}")))
+        };
+
+        let dtstart = property("DTSTART")?;
+        let start = parse_time(&dtstart.value)
+            .ok_or_else(|| ics_error(dtstart.number, "invalid DTSTART"))?
+            .seconds;
+        let offset_from = offset("TZOFFSETFROM")?;
+        let rule = component
+            .property("RRULE")
+            .and_then(|line| YearlyRule::parse(&line.value, offset_from));
+
+        Ok(Self {
+            start,
+            offset_from,
+            offset_to: offset("TZOFFSETTO")?,
+            rule,
+        })
+    }
+
+    /// The last local time the observance started, at or before `local`.
+    fn latest_onset(&self, local: i64) -> Option<i64> {
+        if self.start > local {
+            return None;
+        }
+        let Some(rule) = &self.rule else {
+            return Some(self.start);
+        };
+
+        let (year, _, _) = civil_from_days(local.div_euclid(DAY));
+        let last = rule.until.map_or(local, |until| until.min(local));
+        let (last_year, _, _) = civil_from_days(last.div_euclid(DAY));
+        let time_of_day = self.start.rem_euclid(DAY);
+
+        [year, year - 1, last_year, last_year - 1]
+            .into_iter()
+            .filter_map(|year| rule.onset_day(year))
+            .map(|day| day * DAY + time_of_day)
+            .filter(|onset| *onset >= self.start && *onset <= last)
+            .max()
+            .or(Some(self.start))
+    }
+}
+
+impl YearlyRule {
+    /// Parses a recurrence rule, returning `None` if it is not a yearly
+    /// recurrence on a single weekday of a single month.
+    fn parse(rrule: &str, offset_from: i64) -> Option<Self> {
+        let parts: HashMap<&str, &str> = rrule
+            .split(';')
+            .filter_map(|part| part.split_once('='))
+            .collect();
+        if !parts.get("FREQ")?.eq_ignore_ascii_case("YEARLY") {
+            return None;
+        }
+
+        let month = parts.get("BYMONTH")?.parse().ok()?;
+        let by_day = parts.get("BYDAY")?;
+        let weekday = by_day.get(by_day.len().checked_sub(2)?..)?;
+        let week = match &by_day[..by_day.len() - 2] {
+            "" => 1,
+            week => week.trim_start_matches('+').parse().ok()?,
+        };
+        let until = match parts.get("UNTIL") {
+            Some(until) => {
+                let until = parse_time(until)?;
+
+                Some(if until.utc {
+                    until.seconds + offset_from
+                } else {
+                    until.seconds
+                })
+            }
+            None => None,
+        };
+
+        Some(Self {
+            month,
+            week,
+            weekday: parse_weekday(weekday)?,
+            until,
+        })
+    }
+
+    /// The day the rule falls on in `year`, in days since the Unix epoch.
+    fn onset_day(&self, year: i64) -> Option<i64> {
+        let first = days_from_civil(year, self.month, 1);
+        let next_month = if self.month == 12 {
+            days_from_civil(year + 1, 1, 1)
+        } else {
+            days_from_civil(year, self.month + 1, 1)
+        };
+
+        let day = if self.week > 0 {
+            first + (self.weekday - weekday(first)).rem_euclid(7) + 7 * (self.week - 1)
+        } else {
+            let last = next_month - 1;
+
+            last - (weekday(last) - self.weekday).rem_euclid(7) - 7 * (-self.week - 1)
+        };
+
+        (first..next_month).contains(&day).then_some(day)
+    }
+}
+
+/// Resolves the times of events to UTC.
+struct TimeResolver<'a> {
+    /// The time zone floating times and dates are in.
+    floating: Option<&'a TimeZone>,
+    /// The time zones of the file, by their `TZID`.
+    time_zones: &'a HashMap<String, TimeZone>,
+}
+
+/// A resolved `DTSTART` or `DTEND`.
+#[derive(Debug)]
+struct ResolvedTime {
+    /// Seconds since the Unix epoch.
+    timestamp: i64,
+    /// The local time, in seconds since the Unix epoch.
+    local: i64,
+    /// Whether the value is a date, rather than a date-time.
+    date: bool,
+    /// The `TZID` the value was written in.
+    tzid: Option<String>,
+    /// The line number of the property the value was read from.
+    line: usize,
+}
+
+impl ResolvedTime {
+    fn to_datetime(&self) -> Result<DateTime, crate::Error> {
+        crate::datetime::from_unix_timestamp(self.timestamp)
+            .ok_or_else(|| ics_error(self.line, "time is out of range"))
+    }
+}
+
+impl TimeResolver<'_> {
+    fn resolve(&self, line: &ContentLine) -> Result<ResolvedTime, crate::Error> {
+        let time = parse_time(&line.value).ok_or_else(|| {
+            ics_error(
+                line.number,
+                format!("invalid {} {:?}", line.name, line.value),
+            )
+        })?;
+        let tzid = line
+            .param("TZID")
+            .filter(|_| !time.utc && !time.date)
+            .map(str::to_string);
+
+        let offset = if time.utc {
+            0
+        } else if let Some(tzid) = &tzid {
+            self.time_zones
+                .get(tzid)
+                .ok_or_else(|| ics_error(line.number, format!("unknown time zone {tzid}")))?
+                .offset_at(time.seconds)
+        } else {
+            self.floating
+                .map_or(0, |time_zone| time_zone.offset_at(time.seconds))
+        };
+
+        Ok(ResolvedTime {
+            timestamp: time.seconds - offset,
+            local: time.seconds,
+            date: time.date,
+            tzid,
+            line: line.number,
+        })
+    }
+}
+
+/// A date or date-time value.
+struct TimeValue {
+    /// The time, in seconds since the Unix epoch, as if it were UTC.
+    seconds: i64,
+    /// Whether the value ends in `Z`.
+    utc: bool,
+    /// Whether the value is a date, rather than a date-time.
+    date: bool,
+}
+
+/// Parses a date, such as `20240601`, or a date-time, such as
+/// `20240601T090000` or `20240601T090000Z`.
+fn parse_time(value: &str) -> Option<TimeValue> {
+    let value = value.trim();
+    let number = |range: std::ops::Range<usize>| -> Option<i64> {
+        let digits = value.get(range)?;
+
+        digits
+            .bytes()
+            .all(|b| b.is_ascii_digit())
+            .then(|| digits.parse().ok())
+            .flatten()
+    };
+
+    let (year, month, day) = (number(0..4)?, number(4..6)?, number(6..8)?);
+    let days = days_from_civil(year, u32::try_from(month).ok()?, u32::try_from(day).ok()?);
+    if civil_from_days(days) != (year, month as u32, day as u32) {
+        return None;
+    }
+
+    if value.len() == 8 {
+        return Some(TimeValue {
+            seconds: days * DAY,
+            utc: false,
+            date: true,
+        });
+    }
+
+    if value.get(8..9)? != "T" {
+        return None;
+    }
+    let (hour, minute, second) = (number(9..11)?, number(11..13)?, number(13..15)?);
+    if hour > 23 || minute > 59 || second > 60 {
+        return None;
+    }
+    let utc = match value.get(15..)? {
+        "" => false,
+        "Z" | "z" => true,
+        _ => return None,
+    };
+
+    Some(TimeValue {
+        // a leap second is read as the first second of the next minute
+        seconds: days * DAY + hour * 3600 + minute * 60 + second,
+        utc,
+        date: false,
+    })
+}
+
+/// Parses a UTC offset, such as `-0600` or `+053000`, in seconds.
+fn parse_utc_offset(value: &str) -> Option<i64> {
+    let value = value.trim();
+    let (sign, digits) = match value.get(..1)? {
+        "+" => (1, &value[1..]),
+        "-" => (-1, &value[1..]),
+        _ => return None,
+    };
+    if !matches!(digits.len(), 4 | 6) || !digits.bytes().all(|b| b.is_ascii_digit()) {
+        return None;
+    }
+
+    let hours: i64 = digits[0..2].parse().ok()?;
+    let minutes: i64 = digits[2..4].parse().ok()?;
+    let seconds: i64 = digits.get(4..6).map_or(Ok(0), str::parse).ok()?;
+
+    Some(sign * (hours * 3600 + minutes * 60 + seconds))
+}
+
+/// Parses a duration, such as `PT1H30M` or `P1W`, in seconds. `None` if it is
+/// invalid or too long to represent.
+fn parse_duration(value: &str) -> Option<i64> {
+    let value = value.trim();
+    let (sign, value) = match value.strip_prefix('-') {
+        Some(value) => (-1, value),
+        None => (1, value.strip_prefix('+').unwrap_or(value)),
+    };
+    let value = value.strip_prefix('P')?;
+
+    let mut seconds: i64 = 0;
+    let mut number = String::new();
+    let mut in_time = false;
+    for c in value.chars() {
+        match c {
+            '0'..='9' => number.push(c),
+            'T' if number.is_empty() && !in_time => in_time = true,
+            unit => {
+                let amount: i64 = number.parse().ok()?;
+                number.clear();
+
+                let unit = match (in_time, unit) {
+                    (false, 'W') => 7 * DAY,
+                    (false, 'D') => DAY,
+                    (true, 'H') => 3600,
+                    (true, 'M') => 60,
+                    (true, 'S') => 1,
+                    _ => return None,
+                };
+                seconds = seconds.checked_add(amount.checked_mul(unit)?)?;
+            }
+        }
+    }
+
+    number.is_empty().then_some(sign * seconds)
+}
+
+/// Parses a two letter weekday, such as `SU`, from Sunday as 0.
+fn parse_weekday(weekday: &str) -> Option<i64> {
+    ["SU", "MO", "TU", "WE", "TH", "FR", "SA"]
+        .iter()
+        .position(|day| day.eq_ignore_ascii_case(weekday))
+        .map(|day| day as i64)
+}
+
+/// Parses an ISO 8601 date, such as `2024-06-01`, in days since the Unix
+/// epoch.
+fn parse_iso_date(date: &str) -> Option<i64> {
+    parse_time(&date.replace('-', ""))
+        .filter(|time| time.date)
+        .map(|time| time.seconds / DAY)
+}
+
+/// Formats a timestamp as a UTC date-time, such as `20240601T150000Z`.
+fn format_utc(timestamp: i64) -> String {
+    format!("{}Z", format_local(timestamp))
+}
+
+/// Formats a local time in seconds since the Unix epoch as a local date-time,
+/// such as `20240601T090000`.
+fn format_local(local: i64) -> String {
+    let seconds = local.rem_euclid(DAY);
+
+    format!(
+        "{}T{:02}{:02}{:02}",
+        format_date(local.div_euclid(DAY)),
+        seconds / 3600,
+        seconds % 3600 / 60,
+        seconds % 60
+    )
+}
+
+/// Formats a UTC offset in seconds, such as `-0600` or `+053000`.
+fn format_utc_offset(offset: i64) -> String {
+    let sign = if offset < 0 { '-' } else { '+' };
+    let offset = offset.abs();
+    let seconds = match offset % 60 {
+        0 => String::new(),
+        seconds => format!("{seconds:02}"),
+    };
+
+    format!(
+        "{sign}{:02}{:02}{seconds}",
+        offset / 3600,
+        offset % 3600 / 60
+    )
+}
+
+/// Formats a day since the Unix epoch as a date, such as `20240601`.
+fn format_date(days: i64) -> String {
+    let (year, month, day) = civil_from_days(days);
+
+    format!("{year:04}{month:02}{day:02}")
+}
+
+/// The number of days from the Unix epoch to a date of the proleptic
+/// Gregorian calendar.
+fn days_from_civil(year: i64, month: u32, day: u32) -> i64 {
+    let year = if month <= 2 { year - 1 } else { year };
+    let era = year.div_euclid(400);
+    let year_of_era = year - era * 400;
+    let month = i64::from(month);
+    let day_of_year =
+        (153 * (if month > 2 { month - 3 } else { month + 9 }) + 2) / 5 + i64::from(day) - 1;
+    let day_of_era = year_of_era * 365 + year_of_era / 4 - year_of_era / 100 + day_of_year;
+
+    era * 146_097 + day_of_era - 719_468
+}
+
+/// The date of the proleptic Gregorian calendar a number of days from the
+/// Unix epoch falls on, as a year, month and day.
+fn civil_from_days(days: i64) -> (i64, u32, u32) {
+    let days = days + 719_468;
+    let era = days.div_euclid(146_097);
+    let day_of_era = days - era * 146_097;
+    let year_of_era =
+        (day_of_era - day_of_era / 1460 + day_of_era / 36_524 - day_of_era / 146_096) / 365;
+    let day_of_year = day_of_era - (365 * year_of_era + year_of_era / 4 - year_of_era / 100);
+    let month_index = (5 * day_of_year + 2) / 153;
+    let day = day_of_year - (153 * month_index + 2) / 5 + 1;
+    let month = if month_index < 10 {
+        month_index + 3
+    } else {
+        month_index - 9
+    };
+    let year = year_of_era + era * 400 + i64::from(month <= 2);
+
+    (year, month as u32, day as u32)
+}
+
+/// The day of the week of a day since the Unix epoch, from Sunday as 0.
+fn weekday(days: i64) -> i64 {
+    // the Unix epoch was a Thursday
+    (days + 4).rem_euclid(7)
+}
+
+/// Quotes a parameter value if it contains a delimiter.
+fn param_value(value: &str) -> String {
+    if value.contains([':', ';', ',']) {
+        format!("\"{value}\"")
+    } else {
+        value.to_string()
+    }
+}
+
+/// Escapes a text value.
+fn escape_text(text: &str) -> String {
+    text.replace('\\', "\\\\")
+        .replace(';', "\\;")
+        .replace(',', "\\,")
+        .replace("\r\n", "\\n")
+        .replace('\n', "\\n")
+}
+
+/// Unescapes a text value.
+fn unescape_text(text: &str) -> String {
+    let mut unescaped = String::with_capacity(text.len());
+    let mut chars = text.chars();
+
+    while let Some(c) = chars.next() {
+        match c {
+            '\\' => match chars.next() {
+                Some('n' | 'N') => unescaped.push('\n'),
+                Some(escaped) => unescaped.push(escaped),
+                None => unescaped.push('\\'),
+            },
+            c => unescaped.push(c),
+        }
+    }
+
+    unescaped
+}
+
+/// Strips the tags of an HTML description, for the plain text `DESCRIPTION`.
+fn html_to_text(html: &str) -> String {
+    let mut text = String::with_capacity(html.len());
+    let mut tag: Option<String> = None;
+
+    for c in html.chars() {
+        match (&mut tag, c) {
+            (None, '<') => tag = Some(String::new()),
+            (Some(name), '>') => {
+                let name = name
+                    .trim_start_matches('/')
+                    .split(|c: char| c.is_whitespace() || c == '/')
+                    .next()
+                    .unwrap_or_default()
+                    .to_ascii_lowercase();
+                if matches!(name.as_str(), "br" | "p" | "div" | "li") && !text.ends_with('\n') {
+                    text.push('\n');
+                }
+
+                tag = None;
+            }
+            (Some(name), c) => name.push(c),
+            (None, c) => text.push(c),
+        }
+    }
+
+    text.replace("&nbsp;", " ")
+        .replace("&lt;", "<")
+        .replace("&gt;", ">")
+        .replace("&quot;", "\"")
+        .replace("&#39;", "'")
+        .replace("&amp;", "&")
+        .trim()
+        .to_string()
+}
+
+/// Converts a plain text `DESCRIPTION` to HTML.
+fn text_to_html(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('\n', "<br>")
+}
+
+fn ics_error(line: usize, message: impl Into<String>) -> crate::Error {
+    crate::Error::Ics {
+        line,
+        message: message.into(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const DENVER: &str = "BEGIN:VTIMEZONE\r
+TZID:America/Denver\r
+BEGIN:DAYLIGHT\r
+TZOFFSETFROM:-0700\r
+TZOFFSETTO:-0600\r
+DTSTART:19700308T020000\r
+RRULE:FREQ=YEARLY;BYMONTH=3;BYDAY=2SU\r
+TZNAME:MDT\r
+END:DAYLIGHT\r
+BEGIN:STANDARD\r
+TZOFFSETFROM:-0600\r
+TZOFFSETTO:-0700\r
+DTSTART:19701101T020000\r
+RRULE:FREQ=YEARLY;BYMONTH=11;BYDAY=1SU\r
+TZNAME:MST\r
+END:STANDARD\r
+END:VTIMEZONE\r
+";
+
+    fn timestamp(s: &str) -> i64 {
+        crate::datetime::unix_timestamp(&crate::datetime::parse(s).unwrap())
+    }
+
+    fn local(s: &str) -> i64 {
+        parse_time(s).unwrap().seconds
+    }
+
+    fn calendar(body: &str) -> String {
+        format!("BEGIN:VCALENDAR\r\nVERSION:2.0\r\n{DENVER}{body}END:VCALENDAR\r\n")
+    }
+
+    fn event(json: serde_json::Value) -> CalendarEvent {
+        let mut event = serde_json::json!({
+            "id": 1,
+            "title": "Lecture",
+            "context_code": "account_1",
+            "workflow_state": "active",
+        });
+        event
+            .as_object_mut()
+            .unwrap()
+            .extend(json.as_object().unwrap().clone());
+
+        serde_json::from_value(event).unwrap()
+    }
+
+    #[test]
+    fn unfolds_continuation_lines() {
+        let lines = unfold("\u{feff}SUMMARY:A long\r\n  title\r\n\tcontinued\r\n\r\nEND:VEVENT\n");
+
+        assert_eq!(
+            lines,
+            [
+                (1, String::from("SUMMARY:A long titlecontinued")),
+                (5, String::from("END:VEVENT"))
+            ]
+        );
+    }
+
+    #[test]
+    fn folds_long_lines() {
+        let mut writer = IcsWriter {
+            ics: String::new(),
+            time_zone: None,
+        };
+        writer.text("SUMMARY", &"é".repeat(60));
+
+        let lines: Vec<&str> = writer.ics.split("\r\n").collect();
+        assert!(lines.iter().all(|line| line.len() <= LINE_LENGTH));
+        assert_eq!(
+            unfold(&writer.ics),
+            [(1, format!("SUMMARY:{}", "é".repeat(60)))]
+        );
+    }
+
+    #[test]
+    fn resolves_times_with_tzid() {
+        let time_zone = IcsTimeZone::parse(DENVER).unwrap();
+        let time_zones = HashMap::from([(time_zone.tzid.clone(), time_zone.rules)]);
+        let resolver = TimeResolver {
+            floating: None,
+            time_zones: &time_zones,
+        };
+        let resolve = |line: &str| resolver.resolve(&ContentLine::parse(1, line).unwrap());
+
+        let summer = resolve("DTSTART;TZID=America/Denver:20240601T090000").unwrap();
+        assert_eq!(summer.timestamp, timestamp("2024-06-01T15:00:00Z"));
+        assert_eq!(summer.tzid.as_deref(), Some("America/Denver"));
+
+        let winter = resolve("DTSTART;TZID=\"America/Denver\":20240115T090000").unwrap();
+        assert_eq!(winter.timestamp, timestamp("2024-01-15T16:00:00Z"));
+
+        // UTC times and dates ignore the TZID
+        let utc = resolve("DTSTART;TZID=America/Denver:20240601T090000Z").unwrap();
+        assert_eq!(utc.timestamp, timestamp("2024-06-01T09:00:00Z"));
+        assert_eq!(utc.tzid, None);
+
+        assert!(matches!(
+            resolve("DTSTART;TZID=Europe/Paris:20240601T090000"),
+            Err(crate::Error::Ics { line: 1, .. })
+        ));
+    }
+
+    #[test]
+    fn resolves_observances_around_transitions() {
+        let rules = IcsTimeZone::parse(DENVER).unwrap().rules;
+
+        // daylight saving time starts at 02:00 on the second Sunday of March
+        assert_eq!(rules.offset_at(local("20240310T015959")), -7 * 3600);
+        assert_eq!(rules.offset_at(local("20240310T030000")), -6 * 3600);
+        // and ends at 02:00 on the first Sunday of November
+        assert_eq!(rules.offset_at(local("20241103T005959")), -6 * 3600);
+        assert_eq!(rules.offset_at(local("20241103T020000")), -7 * 3600);
+        // before the first observance, its TZOFFSETFROM is in effect
+        assert_eq!(rules.offset_at(local("19600601T000000")), -7 * 3600);
+
+        assert_eq!(
+            rules.local_at(timestamp("2024-03-10T10:00:00Z")),
+            local("20240310T040000")
+        );
+        assert_eq!(
+            rules.local_at(timestamp("2024-11-03T07:30:00Z")),
+            local("20241103T013000")
+        );
+    }
+
+    #[test]
+    fn ends_observances_at_until() {
+        let ics = DENVER.replace(
+            "BYMONTH=3;BYDAY=2SU",
+            "BYMONTH=3;BYDAY=2SU;UNTIL=20060402T090000Z",
+        );
+        let rules = IcsTimeZone::parse(&ics).unwrap().rules;
+
+        assert_eq!(rules.offset_at(local("20060601T000000")), -6 * 3600);
+        // the last onset of daylight saving time is in 2006
+        assert_eq!(rules.offset_at(local("20070601T000000")), -7 * 3600);
+    }
+
+    #[test]
+    fn parses_durations() {
+        assert_eq!(parse_duration("PT1H30M"), Some(5400));
+        assert_eq!(parse_duration("P1W"), Some(7 * DAY));
+        assert_eq!(parse_duration("P1DT2H"), Some(DAY + 7200));
+        assert_eq!(parse_duration("-PT15M"), Some(-900));
+        assert_eq!(parse_duration("PT1X"), None);
+        assert_eq!(parse_duration("P1H"), None);
+        assert_eq!(parse_duration("PT1"), None);
+        assert_eq!(parse_duration("P99999999999999999W"), None);
+        assert_eq!(parse_duration("PT9223372036854775807S"), Some(i64::MAX));
+        assert_eq!(parse_duration("PT9223372036854775807S1S"), None);
+    }
+
+    #[test]
+    fn ends_events_after_their_duration() {
+        let ics = calendar(
+            "BEGIN:VEVENT\r\n\
+             DTSTART;TZID=America/Denver:20240601T090000\r\n\
+             DURATION:PT1H30M\r\n\
+             END:VEVENT\r\n",
+        );
+        let calendar = IcsCalendar::parse(&ics).unwrap();
+
+        assert_eq!(
+            calendar.events()[0].end_at(),
+            Some(crate::datetime::parse("2024-06-01T16:30:00Z").unwrap())
+        );
+    }
+
+    #[test]
+    fn rejects_durations_out_of_range() {
+        let ics = calendar(
+            "BEGIN:VEVENT\r\n\
+             DTSTART:20240601T090000Z\r\n\
+             DURATION:P99999999999999999W\r\n\
+             END:VEVENT\r\n",
+        );
+
+        assert!(matches!(
+            IcsCalendar::parse(&ics),
+            Err(crate::Error::Ics { line: 22, .. })
+        ));
+
+        let ics = calendar(
+            "BEGIN:VEVENT\r\n\
+             DTSTART:20240601T090000Z\r\n\
+             DURATION:PT9223372036854775807S\r\n\
+             END:VEVENT\r\n",
+        );
+
+        assert!(matches!(
+            IcsCalendar::parse(&ics),
+            Err(crate::Error::Ics { line: 22, .. })
+        ));
+    }
+
+    #[test]
+    fn round_trips_events_in_a_time_zone() {
+        let events = [
+            event(serde_json::json!({
+                "id": 1,
+                "title": "Lecture; weekly",
+                "start_at": "2024-03-04T16:00:00Z",
+                "end_at": "2024-03-04T17:30:00Z",
+                "description": "<p>Bring notes</p>",
+                "series_uuid": "a1",
+                "series_head": true,
+                "rrule": "FREQ=WEEKLY;INTERVAL=1;COUNT=4",
+            })),
+            event(serde_json::json!({
+                "id": 2,
+                "title": "Lecture; weekly",
+                "start_at": "2024-03-11T15:00:00Z",
+                "end_at": "2024-03-11T16:30:00Z",
+                "series_uuid": "a1",
+                "series_head": false,
+            })),
+            event(serde_json::json!({
+                "id": 3,
+                "title": "Holiday",
+                "start_at": "2024-05-27T06:00:00Z",
+                "end_at": "2024-05-27T06:00:00Z",
+                "all_day": true,
+                "all_day_date": "2024-05-27",
+            })),
+        ];
+        let time_zone = IcsTimeZone::parse(&calendar("")).unwrap();
+
+        let ics = IcsExport::new(&events)
+            .set_time_zone(&time_zone)
+            .to_ics()
+            .unwrap();
+
+        assert!(ics.contains("\r\nDTSTART;TZID=America/Denver:20240304T090000\r\n"));
+        assert!(ics.contains("\r\nTZOFFSETFROM:-0700\r\n"));
+        let calendar = IcsCalendar::parse(&ics).unwrap();
+        assert_eq!(calendar.time_zone(), Some("America/Denver"));
+        let [lecture, holiday] = calendar.events() else {
+            panic!("expected 2 events, got {:?}", calendar.events());
+        };
+
+        assert_eq!(lecture.uid(), Some("event-calendar-event-1"));
+        assert_eq!(lecture.summary(), "Lecture; weekly");
+        assert_eq!(lecture.description(), Some("<p>Bring notes</p>"));
+        assert_eq!(lecture.start_at(), events[0].start_at());
+        assert_eq!(lecture.end_at(), events[0].end_at());
+        assert_eq!(lecture.rrule(), Some("FREQ=WEEKLY;INTERVAL=1;COUNT=4"));
+        assert_eq!(lecture.time_zone(), Some("America/Denver"));
+        assert!(!lecture.all_day());
+
+        assert_eq!(holiday.summary(), "Holiday");
+        assert!(holiday.all_day());
+        assert_eq!(
+            holiday.start_at(),
+            Some(crate::datetime::parse("2024-05-27T06:00:00Z").unwrap())
+        );
+    }
+
+    #[test]
+    fn writes_fixed_offset_time_zones() {
+        let events = [event(serde_json::json!({
+            "start_at": "2024-06-01T16:00:00Z",
+            "end_at": "2024-06-01T17:00:00Z",
+        }))];
+        let time_zone = IcsTimeZone::fixed("America/Phoenix", -7 * 3600);
+
+        let ics = IcsExport::new(&events)
+            .set_time_zone(&time_zone)
+            .to_ics()
+            .unwrap();
+
+        assert!(ics.contains("\r\nDTSTART;TZID=America/Phoenix:20240601T090000\r\n"));
+        let calendar = IcsCalendar::parse(&ics).unwrap();
+        assert_eq!(calendar.events()[0].start_at(), events[0].start_at());
+        assert_eq!(calendar.events()[0].end_at(), events[0].end_at());
+    }
+
+    #[test]
+    fn refuses_timed_series_without_a_time_zone() {
+        let series = [event(serde_json::json!({
+            "start_at": "2024-03-04T16:00:00Z",
+            "end_at": "2024-03-04T17:00:00Z",
+            "series_uuid": "a1",
+            "series_head": true,
+            "rrule": "FREQ=WEEKLY;COUNT=4",
+        }))];
+
+        assert!(matches!(
+            IcsExport::new(&series).to_ics(),
+            Err(crate::Error::IcsExport { .. })
+        ));
+
+        // single events and all day series are written without one
+        let events = [
+            event(serde_json::json!({
+                "start_at": "2024-03-04T16:00:00Z",
+                "end_at": "2024-03-04T17:00:00Z",
+            })),
+            event(serde_json::json!({
+                "id": 2,
+                "start_at": "2024-03-04T07:00:00Z",
+                "end_at": "2024-03-04T07:00:00Z",
+                "all_day": true,
+                "all_day_date": "2024-03-04",
+                "series_uuid": "b2",
+                "series_head": true,
+                "rrule": "FREQ=WEEKLY;COUNT=4",
+            })),
+        ];
+        let ics = IcsExport::new(&events).to_ics().unwrap();
+
+        assert!(ics.contains("\r\nDTSTART:20240304T160000Z\r\n"));
+        assert!(ics.contains("\r\nDTSTART;VALUE=DATE:20240304\r\n"));
+    }
+}
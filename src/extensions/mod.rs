@@ -1,4 +1,14 @@
+#[cfg(feature = "account_domains")]
 pub mod account_domains;
+#[cfg(feature = "account_notifications")]
 pub mod account_notifications;
+#[cfg(feature = "account_reports")]
 pub mod account_reports;
+#[cfg(feature = "accounts")]
+pub mod accounts;
+#[cfg(feature = "appointment_groups")]
+pub mod appointment_groups;
+#[cfg(feature = "calendar")]
 pub mod calendar;
+#[cfg(feature = "calendar_events")]
+pub mod calendar_events;
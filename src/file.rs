@@ -0,0 +1,96 @@
+use serde::{Deserialize, Serialize};
+
+use crate::{DateTime, FileId};
+
+/// A file stored in Canvas, such as the attachment of a completed report.
+///
+/// [See docs](https://canvas.instructure.com/doc/api/files.html#File).
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct File {
+    /// The ID of the file.
+    id: FileId,
+    /// The UUID of the file.
+    uuid: Option<String>,
+    /// The name of the file as displayed in Canvas.
+    display_name: String,
+    /// The name of the file when it was uploaded.
+    filename: String,
+    /// The MIME type of the file.
+    #[serde(rename = "content-type")]
+    content_type: String,
+    /// The url to download the file from.
+    url: String,
+    /// The size of the file in bytes.
+    size: u64,
+    /// When the file was created.
+    #[serde(with = "crate::datetime::rfc3339")]
+    created_at: DateTime,
+    /// When the file was last updated.
+    #[serde(with = "crate::datetime::rfc3339")]
+    updated_at: DateTime,
+    /// Whether the file is locked.
+    #[serde(default)]
+    locked: bool,
+    /// Whether the file is hidden.
+    #[serde(default)]
+    hidden: bool,
+    /// The url of a thumbnail of the file, if one is available.
+    thumbnail_url: Option<String>,
+    /// The class of the MIME type of the file, such as `doc` or `image`.
+    mime_class: Option<String>,
+}
+
+impl File {
+    /// The ID of the file.
+    pub fn id(&self) -> &FileId {
+        &self.id
+    }
+    /// The UUID of the file.
+    pub fn uuid(&self) -> Option<&str> {
+        self.uuid.as_deref()
+    }
+    /// The name of the file as displayed in Canvas.
+    pub fn display_name(&self) -> &str {
+        &self.display_name
+    }
+    /// The name of the file when it was uploaded.
+    pub fn filename(&self) -> &str {
+        &self.filename
+    }
+    /// The MIME type of the file.
+    pub fn content_type(&self) -> &str {
+        &self.content_type
+    }
+    /// The url to download the file from.
+    pub fn url(&self) -> &str {
+        &self.url
+    }
+    /// The size of the file in bytes.
+    pub fn size(&self) -> u64 {
+        self.size
+    }
+    /// When the file was created.
+    pub fn created_at(&self) -> DateTime {
+        self.created_at
+    }
+    /// When the file was last updated.
+    pub fn updated_at(&self) -> DateTime {
+        self.updated_at
+    }
+    /// Whether the file is locked.
+    pub fn locked(&self) -> bool {
+        self.locked
+    }
+    /// Whether the file is hidden.
+    pub fn hidden(&self) -> bool {
+        self.hidden
+    }
+    /// The url of a thumbnail of the file, if one is available.
+    pub fn thumbnail_url(&self) -> Option<&str> {
+        self.thumbnail_url.as_deref()
+    }
+    /// The class of the MIME type of the file, such as `doc` or `image`.
+    pub fn mime_class(&self) -> Option<&str> {
+        self.mime_class.as_deref()
+    }
+}
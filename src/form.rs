@@ -0,0 +1,163 @@
+//! Encodes request bodies as Rails-style nested forms, the format Canvas
+//! parses form parameters in.
+//!
+//! Fields are named after their path through the body: a nested object
+//! becomes `parent[child]=...`, and each item of a sequence is repeated under
+//! `parent[]`, so `{"roles": [1, 2]}` is encoded as `roles[]=1&roles[]=2`, and
+//! `{"_json": [{"id": 1}]}` as `_json[][id]=1`. Field names that already
+//! contain brackets, such as `account_notification[subject]`, are kept as they
+//! are. `None` fields and empty sequences are left out, as Rack reads a lone
+//! `parent[]=` as a list holding an empty string rather than an empty list.
+//!
+//! Fields are encoded in the order they are serialized in, such as the order
+//! a struct declares them in.
+
+use reqwest::RequestBuilder;
+use serde::Serialize;
+use serde_json::Value;
+
+/// Encodes `form` as the fields of a nested form.
+///
+/// `form` must serialize to a map, such as a struct.
+pub(crate) fn encode<T: Serialize + ?Sized>(
+    form: &T,
+) -> Result<Vec<(String, String)>, crate::Error> {
+    let value = serde_json::to_value(form).map_err(|e| crate::Error::EncodingForm { source: e })?;
+
+    let Value::Object(fields) = value else {
+        return Err(crate::Error::EncodingForm {
+            source: serde::ser::Error::custom("a form must be a map of fields"),
+        });
+    };
+
+    let mut pairs = Vec::new();
+    for (name, value) in fields {
+        flatten(name, value, &mut pairs);
+    }
+
+    Ok(pairs)
+}
+
+fn flatten(name: String, value: Value, pairs: &mut Vec<(String, String)>) {
+    match value {
+        Value::Null => {}
+        Value::Bool(value) => pairs.push((name, value.to_string())),
+        Value::Number(value) => pairs.push((name, value.to_string())),
+        Value::String(value) => pairs.push((name, value)),
+        Value::Array(items) => {
+            for item in items {
+                flatten(format!("{name}[]"), item, pairs);
+            }
+        }
+        Value::Object(fields) => {
+            for (field, value) in fields {
+                flatten(format!("{name}[{field}]"), value, pairs);
+            }
+        }
+    }
+}
+
+/// Sets the body of a request to a nested form.
+pub(crate) trait NestedForm: Sized {
+    /// Sets the body to `form` encoded with [`encode`], and the content type
+    /// to `application/x-www-form-urlencoded`.
+    fn nested_form<T: Serialize + ?Sized>(self, form: &T) -> Result<Self, crate::Error>;
+}
+
+impl NestedForm for RequestBuilder {
+    fn nested_form<T: Serialize + ?Sized>(self, form: &T) -> Result<Self, crate::Error> {
+        Ok(self.form(&encode(form)?))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use serde::Serialize;
+    use serde_json::json;
+
+    use super::*;
+
+    fn pairs(pairs: &[(&str, &str)]) -> Vec<(String, String)> {
+        pairs
+            .iter()
+            .map(|(name, value)| (name.to_string(), value.to_string()))
+            .collect()
+    }
+
+    #[test]
+    fn encodes_nested_objects() {
+        let form = json!({ "event": { "title": "Office hours", "location": { "name": "B12" } } });
+
+        assert_eq!(
+            encode(&form).unwrap(),
+            pairs(&[
+                ("event[title]", "Office hours"),
+                ("event[location][name]", "B12"),
+            ])
+        );
+    }
+
+    #[test]
+    fn encodes_sequences() {
+        let form = json!({ "roles": [1, 2], "_json": [{ "id": 1 }, { "id": 2 }] });
+
+        assert_eq!(
+            encode(&form).unwrap(),
+            pairs(&[
+                ("roles[]", "1"),
+                ("roles[]", "2"),
+                ("_json[][id]", "1"),
+                ("_json[][id]", "2"),
+            ])
+        );
+    }
+
+    #[test]
+    fn omits_empty_sequences() {
+        let form =
+            json!({ "roles": [], "event": { "title": "Office hours", "child_event_data": [] } });
+
+        assert_eq!(
+            encode(&form).unwrap(),
+            pairs(&[("event[title]", "Office hours")])
+        );
+    }
+
+    #[test]
+    fn keeps_bracketed_names_and_leaves_out_none() {
+        #[derive(Serialize)]
+        struct Form {
+            #[serde(rename = "account_notification[subject]")]
+            subject: &'static str,
+            #[serde(rename = "account_notification[icon]")]
+            icon: Option<&'static str>,
+            #[serde(rename = "account_notification_roles")]
+            roles: Option<Vec<u64>>,
+            send_message: bool,
+        }
+
+        let form = Form {
+            subject: "Maintenance",
+            icon: None,
+            roles: Some(vec![3]),
+            send_message: false,
+        };
+
+        assert_eq!(
+            encode(&form).unwrap(),
+            pairs(&[
+                ("account_notification[subject]", "Maintenance"),
+                ("account_notification_roles[]", "3"),
+                ("send_message", "false"),
+            ])
+        );
+    }
+
+    #[test]
+    fn rejects_forms_that_are_not_maps() {
+        assert!(matches!(
+            encode(&json!(["a", "b"])),
+            Err(crate::Error::EncodingForm { .. })
+        ));
+    }
+}
@@ -0,0 +1,523 @@
+//! Strongly typed identifiers for Canvas objects.
+//!
+//! Each kind of object has its own ID type, so that an account ID cannot be
+//! passed where a report ID is expected. IDs are created from the numeric
+//! Canvas ID with `new` (or `From<u64>`), and the types of objects that Canvas
+//! can also look up by an alternate identifier, such as an SIS ID, have a
+//! constructor for each of them. Parsing an ID from a string only accepts the
+//! alternate identifiers of its type, so `sis_course_id:ABC` is not an
+//! [`AccountId`].
+//!
+//! # Example
+//! ```rs
+//! let calendar = client.calendar_by_account_id(AccountId::new(1)).await?;
+//! let calendar = client.calendar_by_account_id(AccountId::sis("ABC")).await?;
+//! ```
+//!
+//! [See docs](https://canvas.instructure.com/doc/api/file.object_ids.html).
+
+use std::{fmt, marker::PhantomData, str::FromStr};
+
+use percent_encoding::{utf8_percent_encode, AsciiSet, NON_ALPHANUMERIC};
+use serde::{de, Deserialize, Deserializer, Serialize, Serializer};
+
+/// The characters encoded in the value of an alternate identifier. Periods
+/// must be encoded, as Canvas would otherwise read them as a format extension.
+const PATH_SEGMENT: &AsciiSet = &NON_ALPHANUMERIC.remove(b'-').remove(b'_').remove(b'~');
+
+/// The identifier shared by every ID type.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+enum Identifier {
+    /// The numeric Canvas ID.
+    Id(u64),
+    /// An alternate identifier, such as `sis_account_id:ABC`.
+    Alternate { kind: String, value: String },
+    /// The current user, `self`.
+    Current,
+    /// The calendar item of an assignment, `assignment_987`.
+    Assignment(u64),
+}
+
+impl Identifier {
+    fn alternate(kind: &str, value: impl Into<String>) -> Self {
+        Self::Alternate {
+            kind: kind.to_string(),
+            value: value.into(),
+        }
+    }
+
+    /// The asset string of the object, such as `course_123`. `None` if this
+    /// is not a numeric ID.
+    fn asset_string(&self, kind: &str) -> Option<String> {
+        match self {
+            Self::Id(id) => Some(format!("{kind}_{id}")),
+            _ => None,
+        }
+    }
+
+    /// Parses a numeric ID, or an alternate identifier of one of `kinds`.
+    /// `self` is only accepted if `kinds` lists it.
+    fn parse(s: &str, kinds: &[&str]) -> Result<Self, crate::Error> {
+        if let Ok(id) = s.parse() {
+            return Ok(Self::Id(id));
+        }
+        if s == "self" && kinds.contains(&"self") {
+            return Ok(Self::Current);
+        }
+
+        match s.split_once(':') {
+            Some((kind, value)) if kinds.contains(&kind) && !value.is_empty() => {
+                Ok(Self::alternate(kind, value))
+            }
+            _ => Err(crate::Error::InvalidId { id: s.to_string() }),
+        }
+    }
+
+    fn path_segment(&self) -> String {
+        match self {
+            Self::Alternate { kind, value } => {
+                format!("{kind}:{}", utf8_percent_encode(value, PATH_SEGMENT))
+            }
+            identifier => identifier.to_string(),
+        }
+    }
+}
+
+impl fmt::Display for Identifier {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Id(id) => write!(f, "{id}"),
+            Self::Alternate { kind, value } => write!(f, "{kind}:{value}"),
+            Self::Current => f.write_str("self"),
+            Self::Assignment(id) => write!(f, "assignment_{id}"),
+        }
+    }
+}
+
+impl Serialize for Identifier {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        match self {
+            Self::Id(id) => serializer.serialize_u64(*id),
+            identifier => serializer.collect_str(identifier),
+        }
+    }
+}
+
+/// Deserializes an ID type from a numeric ID, or from a string parsed with
+/// its `FromStr` implementation.
+struct IdentifierVisitor<T> {
+    from_id: fn(Identifier) -> T,
+    marker: PhantomData<T>,
+}
+
+impl<T> IdentifierVisitor<T> {
+    fn new(from_id: fn(Identifier) -> T) -> Self {
+        Self {
+            from_id,
+            marker: PhantomData,
+        }
+    }
+}
+
+impl<'de, T> de::Visitor<'de> for IdentifierVisitor<T>
+where
+    T: FromStr<Err = crate::Error>,
+{
+    type Value = T;
+
+    fn expecting(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str("a Canvas ID")
+    }
+
+    fn visit_u64<E: de::Error>(self, id: u64) -> Result<Self::Value, E> {
+        Ok((self.from_id)(Identifier::Id(id)))
+    }
+
+    fn visit_i64<E: de::Error>(self, id: i64) -> Result<Self::Value, E> {
+        match u64::try_from(id) {
+            Ok(id) => self.visit_u64(id),
+            Err(_) => Err(E::invalid_value(de::Unexpected::Signed(id), &self)),
+        }
+    }
+
+    // Canvas returns IDs as strings when requested with
+    // `Accept: application/json+canvas-string-ids`
+    fn visit_str<E: de::Error>(self, id: &str) -> Result<Self::Value, E> {
+        id.parse().map_err(E::custom)
+    }
+}
+
+// each ID type lists the kinds of alternate identifiers Canvas looks its
+// objects up by, and `self` if it stands for the current user
+macro_rules! id_type {
+    ($(#[$meta:meta])* $name:ident $(, [$($kind:literal),+ $(,)?])?) => {
+        id_type!(@common $(#[$meta])* $name);
+
+        impl FromStr for $name {
+            type Err = crate::Error;
+
+            /// Parses a numeric ID, or one of the alternate identifiers the
+            /// type has a constructor for, such as `sis_account_id:ABC`.
+            fn from_str(s: &str) -> Result<Self, Self::Err> {
+                Identifier::parse(s, &[$($($kind),+)?]).map(Self)
+            }
+        }
+
+        impl<'de> Deserialize<'de> for $name {
+            fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+            where
+                D: Deserializer<'de>,
+            {
+                deserializer.deserialize_any(IdentifierVisitor::new(Self))
+            }
+        }
+    };
+    // an ID type that parses its own kinds of identifiers
+    (@common $(#[$meta:meta])* $name:ident) => {
+        $(#[$meta])*
+        #[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize)]
+        #[serde(transparent)]
+        pub struct $name(Identifier);
+
+        impl $name {
+            /// Creates an ID from the numeric Canvas ID.
+            pub fn new(id: u64) -> Self {
+                Self(Identifier::Id(id))
+            }
+
+            /// The numeric Canvas ID. `None` if this is an alternate identifier.
+            pub fn as_u64(&self) -> Option<u64> {
+                match self.0 {
+                    Identifier::Id(id) => Some(id),
+                    _ => None,
+                }
+            }
+
+            /// The ID as a segment of a request path, with the value of an
+            /// alternate identifier URL-encoded.
+            pub fn path_segment(&self) -> String {
+                self.0.path_segment()
+            }
+        }
+
+        impl From<u64> for $name {
+            fn from(id: u64) -> Self {
+                Self::new(id)
+            }
+        }
+
+        impl fmt::Display for $name {
+            fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+                self.0.fmt(f)
+            }
+        }
+    };
+}
+
+id_type!(
+    /// The ID of an account.
+    AccountId,
+    ["sis_account_id", "lti_context_id"]
+);
+
+id_type!(
+    /// The ID of a course.
+    CourseId,
+    ["sis_course_id", "sis_integration_id", "lti_context_id"]
+);
+
+id_type!(
+    /// The ID of a user.
+    UserId,
+    [
+        "self",
+        "sis_user_id",
+        "sis_login_id",
+        "sis_integration_id",
+        "lti_context_id"
+    ]
+);
+
+id_type!(
+    /// The ID of an enrollment term.
+    TermId,
+    ["sis_term_id"]
+);
+
+id_type!(
+    /// The ID of a course section.
+    SectionId,
+    ["sis_section_id", "sis_integration_id"]
+);
+
+id_type!(
+    /// The ID of a role.
+    RoleId
+);
+
+id_type!(
+    /// The ID of an assignment.
+    AssignmentId
+);
+
+id_type!(
+    /// The ID of a learning outcome.
+    OutcomeId
+);
+
+id_type!(@common
+    /// The ID of a calendar event, or of the calendar item of an assignment,
+    /// such as `assignment_987`.
+    CalendarEventId
+);
+
+id_type!(
+    /// The ID of a report.
+    ReportId
+);
+
+id_type!(
+    /// The ID of a file.
+    FileId
+);
+
+id_type!(
+    /// The ID of an account notification.
+    NotificationId
+);
+
+id_type!(
+    /// The ID of an appointment group.
+    AppointmentGroupId
+);
+
+id_type!(
+    /// The ID of a group.
+    GroupId
+);
+
+impl AccountId {
+    /// The asset string of the account, such as `account_1`, which calendars
+    /// use as a context code. `None` if this is an alternate identifier.
+    pub fn asset_string(&self) -> Option<String> {
+        self.0.asset_string("account")
+    }
+
+    /// The account with the given SIS ID.
+    pub fn sis(sis_account_id: impl Into<String>) -> Self {
+        Self(Identifier::alternate("sis_account_id", sis_account_id))
+    }
+
+    /// The account with the given LTI context ID.
+    pub fn lti_context(lti_context_id: impl Into<String>) -> Self {
+        Self(Identifier::alternate("lti_context_id", lti_context_id))
+    }
+}
+
+impl CourseId {
+    /// The asset string of the course, such as `course_123`, which calendars
+    /// use as a context code. `None` if this is an alternate identifier.
+    pub fn asset_string(&self) -> Option<String> {
+        self.0.asset_string("course")
+    }
+
+    /// The course with the given SIS ID.
+    pub fn sis(sis_course_id: impl Into<String>) -> Self {
+        Self(Identifier::alternate("sis_course_id", sis_course_id))
+    }
+
+    /// The course with the given SIS integration ID.
+    pub fn sis_integration(sis_integration_id: impl Into<String>) -> Self {
+        Self(Identifier::alternate(
+            "sis_integration_id",
+            sis_integration_id,
+        ))
+    }
+
+    /// The course with the given LTI context ID.
+    pub fn lti_context(lti_context_id: impl Into<String>) -> Self {
+        Self(Identifier::alternate("lti_context_id", lti_context_id))
+    }
+}
+
+impl UserId {
+    /// The asset string of the user, such as `user_5`, which calendars use as
+    /// a context code. `None` if this is not a numeric ID.
+    pub fn asset_string(&self) -> Option<String> {
+        self.0.asset_string("user")
+    }
+
+    /// The user the client is authenticated as.
+    pub fn current() -> Self {
+        Self(Identifier::Current)
+    }
+
+    /// The user with the given SIS ID.
+    pub fn sis(sis_user_id: impl Into<String>) -> Self {
+        Self(Identifier::alternate("sis_user_id", sis_user_id))
+    }
+
+    /// The user with the given SIS login ID.
+    pub fn sis_login(sis_login_id: impl Into<String>) -> Self {
+        Self(Identifier::alternate("sis_login_id", sis_login_id))
+    }
+
+    /// The user with the given SIS integration ID.
+    pub fn sis_integration(sis_integration_id: impl Into<String>) -> Self {
+        Self(Identifier::alternate(
+            "sis_integration_id",
+            sis_integration_id,
+        ))
+    }
+
+    /// The user with the given LTI context ID.
+    pub fn lti_context(lti_context_id: impl Into<String>) -> Self {
+        Self(Identifier::alternate("lti_context_id", lti_context_id))
+    }
+}
+
+impl SectionId {
+    /// The asset string of the section, such as `course_section_45`, which
+    /// appointment groups use as a sub-context code. `None` if this is an
+    /// alternate identifier.
+    pub fn asset_string(&self) -> Option<String> {
+        self.0.asset_string("course_section")
+    }
+
+    /// The section with the given SIS ID.
+    pub fn sis(sis_section_id: impl Into<String>) -> Self {
+        Self(Identifier::alternate("sis_section_id", sis_section_id))
+    }
+
+    /// The section with the given SIS integration ID.
+    pub fn sis_integration(sis_integration_id: impl Into<String>) -> Self {
+        Self(Identifier::alternate(
+            "sis_integration_id",
+            sis_integration_id,
+        ))
+    }
+}
+
+impl TermId {
+    /// The enrollment term with the given SIS ID.
+    pub fn sis(sis_term_id: impl Into<String>) -> Self {
+        Self(Identifier::alternate("sis_term_id", sis_term_id))
+    }
+}
+
+impl CalendarEventId {
+    /// The calendar item of an assignment, `assignment_987`, as listed along
+    /// with calendar events.
+    pub fn assignment(assignment_id: u64) -> Self {
+        Self(Identifier::Assignment(assignment_id))
+    }
+
+    /// The ID of the assignment, if this is the calendar item of an
+    /// assignment.
+    pub fn assignment_id(&self) -> Option<AssignmentId> {
+        match self.0 {
+            Identifier::Assignment(id) => Some(AssignmentId::new(id)),
+            _ => None,
+        }
+    }
+}
+
+impl FromStr for CalendarEventId {
+    type Err = crate::Error;
+
+    /// Parses a numeric ID, or the ID of an assignment's calendar item such
+    /// as `assignment_987`.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.strip_prefix("assignment_").map(str::parse) {
+            Some(Ok(id)) => Ok(Self::assignment(id)),
+            _ => Identifier::parse(s, &[]).map(Self),
+        }
+    }
+}
+
+impl<'de> Deserialize<'de> for CalendarEventId {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        deserializer.deserialize_any(IdentifierVisitor::new(Self))
+    }
+}
+
+impl GroupId {
+    /// The asset string of the group, such as `group_4`, which calendars use
+    /// as a context code. `None` if this is an alternate identifier.
+    pub fn asset_string(&self) -> Option<String> {
+        self.0.asset_string("group")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_alternate_identifiers_of_the_type() {
+        assert_eq!(
+            "sis_account_id:A1".parse::<AccountId>().unwrap(),
+            AccountId::sis("A1")
+        );
+        assert_eq!(
+            "sis_login_id:jdoe".parse::<UserId>().unwrap(),
+            UserId::sis_login("jdoe")
+        );
+        assert_eq!("self".parse::<UserId>().unwrap(), UserId::current());
+        assert_eq!("42".parse::<RoleId>().unwrap(), RoleId::new(42));
+    }
+
+    #[test]
+    fn rejects_alternate_identifiers_of_other_types() {
+        assert!("sis_course_id:X".parse::<AccountId>().is_err());
+        assert!("sis_user_id:X".parse::<SectionId>().is_err());
+        assert!("self".parse::<AccountId>().is_err());
+        assert!("sis_role_id:X".parse::<RoleId>().is_err());
+        assert!("sis_course_id:X".parse::<CalendarEventId>().is_err());
+        assert!("sis_account_id:".parse::<AccountId>().is_err());
+    }
+
+    #[test]
+    fn deserializes_alternate_identifiers_of_the_type() {
+        let id: CourseId = serde_json::from_str(r#""sis_course_id:C1""#).unwrap();
+        assert_eq!(id, CourseId::sis("C1"));
+
+        assert!(serde_json::from_str::<AccountId>(r#""sis_course_id:C1""#).is_err());
+        assert_eq!(
+            serde_json::from_str::<AccountId>(r#""7""#).unwrap(),
+            AccountId::new(7)
+        );
+    }
+
+    #[test]
+    fn encodes_alternate_identifier_values_in_path_segments() {
+        assert_eq!(
+            CourseId::sis("FALL.2024/BIO 101").path_segment(),
+            "sis_course_id:FALL%2E2024%2FBIO%20101"
+        );
+        assert_eq!(
+            UserId::sis_login("j.doe@example.com").path_segment(),
+            "sis_login_id:j%2Edoe%40example%2Ecom"
+        );
+        assert_eq!(
+            SectionId::sis_integration("a-b_c~d").path_segment(),
+            "sis_integration_id:a-b_c~d"
+        );
+    }
+
+    #[test]
+    fn keeps_other_identifiers_in_path_segments() {
+        assert_eq!(AccountId::new(1).path_segment(), "1");
+        assert_eq!(UserId::current().path_segment(), "self");
+        assert_eq!(
+            CalendarEventId::assignment(987).path_segment(),
+            "assignment_987"
+        );
+    }
+}
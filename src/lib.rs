@@ -1,12 +1,48 @@
+#[cfg(feature = "blocking")]
+pub mod blocking;
 mod canvas_client;
+pub mod cassette;
+pub mod datetime;
 mod error;
 pub mod extensions;
+pub mod file;
+#[cfg(any(
+    feature = "account_notifications",
+    feature = "account_reports",
+    feature = "appointment_groups",
+    feature = "calendar",
+    feature = "calendar_events"
+))]
+mod form;
+pub mod ids;
+#[cfg(feature = "mock")]
+pub mod mock;
+pub mod oauth;
+mod rate_limit;
+mod retry;
 
-pub use canvas_client::CanvasClient;
-pub use error::Error;
+pub use canvas_client::{CanvasClient, CanvasClientBuilder, PageStream};
+pub use datetime::DateTime;
+pub use error::{ApiErrorResponse, Error};
+pub use ids::{
+    AccountId, AppointmentGroupId, AssignmentId, CalendarEventId, CourseId, FileId, GroupId,
+    NotificationId, OutcomeId, ReportId, RoleId, SectionId, TermId, UserId,
+};
+pub use rate_limit::RateLimitConfig;
+pub use retry::RetryPolicy;
 
 // api extensions
-pub use extensions::{
-    account_domains::AccountDomainsExt, account_notifications::AccountNotificationsExt,
-    account_reports::AccountReportsExt, calendar::CalendarExt,
-};
+#[cfg(feature = "account_domains")]
+pub use extensions::account_domains::AccountDomainsExt;
+#[cfg(feature = "account_notifications")]
+pub use extensions::account_notifications::AccountNotificationsExt;
+#[cfg(feature = "account_reports")]
+pub use extensions::account_reports::AccountReportsExt;
+#[cfg(feature = "accounts")]
+pub use extensions::accounts::AccountsExt;
+#[cfg(feature = "appointment_groups")]
+pub use extensions::appointment_groups::AppointmentGroupsExt;
+#[cfg(feature = "calendar")]
+pub use extensions::calendar::CalendarExt;
+#[cfg(feature = "calendar_events")]
+pub use extensions::calendar_events::CalendarEventsExt;
@@ -0,0 +1,438 @@
+//! An in-process stand-in for a Canvas instance, for testing code that uses a
+//! [`CanvasClient`](crate::CanvasClient) without network access.
+//!
+//! Requires the `mock` feature.
+//!
+//! # Example
+//! ```rs
+//! let canvas = MockCanvas::start().await;
+//! canvas.register(
+//!     Method::POST,
+//!     "v1/accounts/1/account_notifications",
+//!     MockResponse::ok(json!({ "subject": "Maintenance", ... })),
+//! );
+//!
+//! let client = canvas.client_builder().build()?;
+//! client.create_global_notification(AccountId::new(1), &notification).await?;
+//!
+//! let request = canvas.assert_received(Method::POST, "v1/accounts/1/account_notifications");
+//! assert_eq!(
+//!     request.form_value("account_notification[subject]").as_deref(),
+//!     Some("Maintenance"),
+//! );
+//! ```
+
+use std::{
+    collections::{HashMap, VecDeque},
+    convert::Infallible,
+    net::SocketAddr,
+    sync::{Arc, Mutex},
+};
+
+use hyper::{
+    service::{make_service_fn, service_fn},
+    Body, Request, Response, Server,
+};
+use reqwest::{
+    header::{HeaderMap, HeaderName, HeaderValue, CONTENT_TYPE, LINK},
+    Method, StatusCode,
+};
+use serde_json::{json, Value};
+use tokio::sync::oneshot;
+
+use crate::CanvasClientBuilder;
+
+/// A Canvas stand-in serving registered responses over HTTP on a local port.
+///
+/// The server shuts down when the [`MockCanvas`] is dropped.
+pub struct MockCanvas {
+    /// The address the server is listening on.
+    addr: SocketAddr,
+    /// The registered routes and received requests, shared with the server.
+    state: Arc<Mutex<MockState>>,
+    /// Shuts the server down when dropped.
+    _shutdown: oneshot::Sender<()>,
+}
+
+#[derive(Default)]
+struct MockState {
+    routes: HashMap<(Method, String), Responder>,
+    received: Vec<ReceivedRequest>,
+}
+
+/// How a registered route responds to requests.
+enum Responder {
+    /// Responds with each response in order, repeating the last one.
+    Sequence(VecDeque<MockResponse>),
+    /// Responds with the page selected by the `page` query parameter.
+    Pages(Vec<Value>),
+}
+
+impl MockCanvas {
+    /// Starts a new server on a random local port.
+    ///
+    /// # Panics
+    /// Panics if no local port could be bound.
+    pub async fn start() -> MockCanvas {
+        let state = Arc::new(Mutex::new(MockState::default()));
+
+        let server = Server::try_bind(&SocketAddr::from(([127, 0, 0, 1], 0)))
+            .expect("failed to bind the mock canvas server");
+        let addr = server.local_addr();
+
+        let service_state = Arc::clone(&state);
+        let make_service = make_service_fn(move |_| {
+            let state = Arc::clone(&service_state);
+
+            async move {
+                Ok::<_, Infallible>(service_fn(move |request| {
+                    let state = Arc::clone(&state);
+
+                    async move { Ok::<_, Infallible>(handle(addr, &state, request).await) }
+                }))
+            }
+        });
+
+        let (shutdown, shutdown_signal) = oneshot::channel::<()>();
+        tokio::spawn(server.serve(make_service).with_graceful_shutdown(async {
+            shutdown_signal.await.ok();
+        }));
+
+        MockCanvas {
+            addr,
+            state,
+            _shutdown: shutdown,
+        }
+    }
+
+    /// The API url of the server, to pass to [`CanvasClientBuilder::set_api_url`].
+    pub fn api_url(&self) -> String {
+        format!("http://{}/api", self.addr)
+    }
+
+    /// Creates a [`CanvasClientBuilder`] pointed at the server.
+    pub fn client_builder(&self) -> CanvasClientBuilder {
+        CanvasClientBuilder::new(String::from("mock-canvas-token")).set_api_url(self.api_url())
+    }
+
+    /// Registers a response for requests with the given method and path.
+    ///
+    /// `path` is relative to the API url, e.g. `v1/account_calendars`. Query
+    /// parameters are not considered when matching requests.
+    ///
+    /// Registering several responses for the same route responds with each in
+    /// order, and then repeats the last one. This can be used to simulate a
+    /// request that is throttled and then succeeds once retried.
+    pub fn register(&self, method: Method, path: &str, response: MockResponse) {
+        let mut state = self.state.lock().expect("mock canvas state poisoned");
+
+        let responder = state
+            .routes
+            .entry((method, normalize_path(path)))
+            .or_insert_with(|| Responder::Sequence(VecDeque::new()));
+        match responder {
+            Responder::Sequence(responses) => responses.push_back(response),
+            responder => *responder = Responder::Sequence(VecDeque::from([response])),
+        }
+    }
+
+    /// Registers a paginated `GET` endpoint, responding with one page per
+    /// element of `pages`.
+    ///
+    /// The page is selected by the `page` query parameter, and each response
+    /// carries the `Link` header Canvas uses to point at the other pages.
+    pub fn register_pages(&self, path: &str, pages: Vec<Value>) {
+        let mut state = self.state.lock().expect("mock canvas state poisoned");
+
+        state
+            .routes
+            .insert((Method::GET, normalize_path(path)), Responder::Pages(pages));
+    }
+
+    /// Every request the server has received, in the order they were received.
+    pub fn received_requests(&self) -> Vec<ReceivedRequest> {
+        self.state
+            .lock()
+            .expect("mock canvas state poisoned")
+            .received
+            .clone()
+    }
+
+    /// Returns the last request received with the given method and path.
+    ///
+    /// # Panics
+    /// Panics if no such request was received.
+    pub fn assert_received(&self, method: Method, path: &str) -> ReceivedRequest {
+        let path = normalize_path(path);
+
+        self.received_requests()
+            .into_iter()
+            .rev()
+            .find(|request| request.method == method && request.path == path)
+            .unwrap_or_else(|| panic!("mock canvas did not receive {method} {path}"))
+    }
+}
+
+/// A response served by a [`MockCanvas`].
+#[derive(Debug, Clone)]
+pub struct MockResponse {
+    status: StatusCode,
+    headers: HeaderMap,
+    body: Vec<u8>,
+}
+
+impl MockResponse {
+    /// A `200 OK` response with a JSON body.
+    pub fn ok(body: Value) -> Self {
+        Self::json(StatusCode::OK, body)
+    }
+
+    /// A response with the given status and JSON body.
+    pub fn json(status: StatusCode, body: Value) -> Self {
+        let mut headers = HeaderMap::new();
+        headers.insert(CONTENT_TYPE, HeaderValue::from_static("application/json"));
+
+        Self {
+            status,
+            headers,
+            body: body.to_string().into_bytes(),
+        }
+    }
+
+    /// A `200 OK` response with a raw body, such as the contents of a file
+    /// being downloaded.
+    pub fn bytes(content_type: &str, body: impl Into<Vec<u8>>) -> Self {
+        Self {
+            status: StatusCode::OK,
+            headers: HeaderMap::new(),
+            body: body.into(),
+        }
+        .with_header(CONTENT_TYPE.as_str(), content_type)
+    }
+
+    /// An error response in the format Canvas uses for general errors, i.e.
+    /// `{"errors":[{"message":"..."}]}`.
+    pub fn error(status: StatusCode, message: &str) -> Self {
+        Self::json(status, json!({ "errors": [{ "message": message }] }))
+    }
+
+    /// A `422 Unprocessable Entity` response in the format Canvas uses for
+    /// validation errors, i.e. `{"errors":{"field":[{"message":"..."}]}}`.
+    pub fn unprocessable(field: &str, message: &str) -> Self {
+        Self::json(
+            StatusCode::UNPROCESSABLE_ENTITY,
+            json!({ "errors": { field: [{ "attribute": field, "message": message }] } }),
+        )
+    }
+
+    /// The `403 Forbidden (Rate Limit Exceeded)` response Canvas sends once the
+    /// rate limit quota is exhausted.
+    pub fn throttled() -> Self {
+        Self {
+            status: StatusCode::FORBIDDEN,
+            headers: HeaderMap::new(),
+            body: b"403 Forbidden (Rate Limit Exceeded)\n".to_vec(),
+        }
+        .with_rate_limit(0.0, 0.0)
+    }
+
+    /// Adds a header to the response.
+    ///
+    /// # Panics
+    /// Panics if `name` or `value` is not a valid header.
+    pub fn with_header(mut self, name: &str, value: &str) -> Self {
+        self.headers.append(
+            HeaderName::from_bytes(name.as_bytes()).expect("invalid header name"),
+            HeaderValue::from_str(value).expect("invalid header value"),
+        );
+
+        self
+    }
+
+    /// Adds the `X-Rate-Limit-Remaining` and `X-Request-Cost` headers Canvas
+    /// reports the rate limit quota with.
+    pub fn with_rate_limit(self, remaining: f64, cost: f64) -> Self {
+        self.with_header("X-Rate-Limit-Remaining", &remaining.to_string())
+            .with_header("X-Request-Cost", &cost.to_string())
+    }
+
+    fn into_response(self) -> Response<Body> {
+        let mut response = Response::new(Body::from(self.body));
+        *response.status_mut() = self.status;
+        *response.headers_mut() = self.headers;
+
+        response
+    }
+}
+
+/// A request received by a [`MockCanvas`].
+#[derive(Debug, Clone)]
+pub struct ReceivedRequest {
+    method: Method,
+    /// The path relative to the API url, e.g. `v1/account_calendars`.
+    path: String,
+    query: Vec<(String, String)>,
+    headers: HeaderMap,
+    body: Vec<u8>,
+}
+
+impl ReceivedRequest {
+    /// The method of the request.
+    pub fn method(&self) -> &Method {
+        &self.method
+    }
+
+    /// The path of the request relative to the API url, e.g. `v1/account_calendars`.
+    pub fn path(&self) -> &str {
+        &self.path
+    }
+
+    /// The headers of the request.
+    pub fn headers(&self) -> &HeaderMap {
+        &self.headers
+    }
+
+    /// The raw body of the request.
+    pub fn body(&self) -> &[u8] {
+        &self.body
+    }
+
+    /// The decoded query parameters of the request, in order.
+    pub fn query(&self) -> &[(String, String)] {
+        &self.query
+    }
+
+    /// The value of the first query parameter named `key`.
+    pub fn query_value(&self, key: &str) -> Option<&str> {
+        self.query
+            .iter()
+            .find(|(name, _)| name == key)
+            .map(|(_, value)| value.as_str())
+    }
+
+    /// The decoded fields of a form encoded body, in order.
+    pub fn form(&self) -> Vec<(String, String)> {
+        url::form_urlencoded::parse(&self.body)
+            .into_owned()
+            .collect()
+    }
+
+    /// The value of the first form field named `key`, e.g.
+    /// `account_notification[subject]`.
+    pub fn form_value(&self, key: &str) -> Option<String> {
+        self.form_values(key).into_iter().next()
+    }
+
+    /// The values of every form field named `key`, e.g.
+    /// `account_notification_roles[]`.
+    pub fn form_values(&self, key: &str) -> Vec<String> {
+        self.form()
+            .into_iter()
+            .filter(|(name, _)| name == key)
+            .map(|(_, value)| value)
+            .collect()
+    }
+}
+
+async fn handle(
+    addr: SocketAddr,
+    state: &Mutex<MockState>,
+    request: Request<Body>,
+) -> Response<Body> {
+    let (parts, body) = request.into_parts();
+    let body = hyper::body::to_bytes(body)
+        .await
+        .unwrap_or_default()
+        .to_vec();
+
+    let path = normalize_path(parts.uri.path());
+    let query: Vec<(String, String)> =
+        url::form_urlencoded::parse(parts.uri.query().unwrap_or_default().as_bytes())
+            .into_owned()
+            .collect();
+
+    let mut state = state.lock().expect("mock canvas state poisoned");
+    state.received.push(ReceivedRequest {
+        method: parts.method.clone(),
+        path: path.clone(),
+        query: query.clone(),
+        headers: parts.headers,
+        body,
+    });
+
+    let response = match state.routes.get_mut(&(parts.method, path.clone())) {
+        Some(Responder::Sequence(responses)) => {
+            if responses.len() > 1 {
+                responses.pop_front()
+            } else {
+                responses.front().cloned()
+            }
+        }
+        Some(Responder::Pages(pages)) => Some(page_response(addr, &path, &query, pages)),
+        None => None,
+    };
+
+    response
+        .unwrap_or_else(|| {
+            MockResponse::error(
+                StatusCode::NOT_FOUND,
+                "The specified resource does not exist.",
+            )
+        })
+        .into_response()
+}
+
+/// Responds with the page of `pages` selected by the `page` query parameter,
+/// linking to the other pages.
+fn page_response(
+    addr: SocketAddr,
+    path: &str,
+    query: &[(String, String)],
+    pages: &[Value],
+) -> MockResponse {
+    let page = query
+        .iter()
+        .find(|(key, _)| key == "page")
+        .and_then(|(_, page)| page.parse::<usize>().ok())
+        .unwrap_or(1)
+        .max(1);
+
+    let link = |page: usize| {
+        let mut url = url::Url::parse(&format!("http://{addr}/api/{path}"))
+            .expect("mock canvas url is valid");
+        url.query_pairs_mut()
+            .extend_pairs(query.iter().filter(|(key, _)| key != "page"))
+            .append_pair("page", &page.to_string());
+
+        format!("<{url}>")
+    };
+
+    let last = pages.len().max(1);
+    let mut links = vec![
+        format!("{}; rel=\"current\"", link(page)),
+        format!("{}; rel=\"first\"", link(1)),
+        format!("{}; rel=\"last\"", link(last)),
+    ];
+    if page < last {
+        links.push(format!("{}; rel=\"next\"", link(page + 1)));
+    }
+    if page > 1 {
+        links.push(format!("{}; rel=\"prev\"", link(page - 1)));
+    }
+
+    let body = pages.get(page - 1).cloned().unwrap_or_else(|| json!([]));
+    let mut response = MockResponse::ok(body);
+    response.headers.insert(
+        LINK,
+        HeaderValue::from_str(&links.join(",")).expect("link header is valid"),
+    );
+
+    response
+}
+
+/// Normalizes a request path to be relative to the API url.
+fn normalize_path(path: &str) -> String {
+    let path = path.trim_start_matches('/');
+
+    path.strip_prefix("api/").unwrap_or(path).to_string()
+}
@@ -0,0 +1,505 @@
+use std::{
+    sync::{Arc, Mutex},
+    time::{Duration, SystemTime, UNIX_EPOCH},
+};
+
+use async_trait::async_trait;
+use reqwest::{
+    header::{HeaderValue, AUTHORIZATION},
+    Client, Method,
+};
+use serde::{Deserialize, Serialize};
+use url::Url;
+
+use crate::UserId;
+
+/// How long before an access token expires to refresh it.
+const REFRESH_MARGIN: Duration = Duration::from_secs(60);
+
+/// Performs the Canvas OAuth2 authorization code flow for a developer key.
+///
+/// A [`CanvasClient`](crate::CanvasClient) authenticated through OAuth2 can be
+/// created with [`CanvasClientBuilder::new_oauth`](crate::CanvasClientBuilder::new_oauth),
+/// which transparently refreshes the access token once it expires.
+///
+/// # Example
+/// ```rs
+/// let oauth = OAuthClient::new(
+///     "https://canvas.example.edu",
+///     "CLIENT_ID",
+///     "CLIENT_SECRET",
+///     "https://app.example.edu/oauth/callback",
+/// )?;
+///
+/// // redirect the user to this url
+/// let url = oauth.authorization_url().set_state("state").build()?;
+///
+/// // once the user is redirected back with a code
+/// let token = oauth.exchange_code(&code).await?;
+/// ```
+///
+/// [See docs](https://canvas.instructure.com/doc/api/file.oauth.html).
+#[derive(Debug, Clone)]
+pub struct OAuthClient {
+    /// The HTTP client to make requests with. Unlike the [`CanvasClient`](crate::CanvasClient)
+    /// client, this has no default authorization header.
+    http_client: Client,
+    /// The base url of the Canvas instance, e.g. `https://canvas.instructure.com`.
+    base_url: String,
+    /// The client id of the developer key.
+    client_id: String,
+    /// The client secret of the developer key.
+    client_secret: String,
+    /// The redirect uri registered with the developer key.
+    redirect_uri: String,
+}
+
+impl OAuthClient {
+    /// Creates a new [`OAuthClient`] for the developer key with the given
+    /// credentials.
+    ///
+    /// `base_url` is the url of the Canvas instance without the `/api` suffix,
+    /// e.g. `https://canvas.instructure.com`.
+    pub fn new(
+        base_url: impl Into<String>,
+        client_id: impl Into<String>,
+        client_secret: impl Into<String>,
+        redirect_uri: impl Into<String>,
+    ) -> Result<Self, crate::Error> {
+        Ok(Self {
+            http_client: Client::builder().build()?,
+            base_url: base_url.into().trim_end_matches('/').to_string(),
+            client_id: client_id.into(),
+            client_secret: client_secret.into(),
+            redirect_uri: redirect_uri.into(),
+        })
+    }
+
+    /// Creates an [`AuthorizationUrl`] to configure the url the user should be
+    /// redirected to in order to authorize the application.
+    ///
+    /// [See docs](https://canvas.instructure.com/doc/api/file.oauth_endpoints.html#get-login-oauth2-auth).
+    pub fn authorization_url(&self) -> AuthorizationUrl<'_> {
+        AuthorizationUrl {
+            client: self,
+            state: None,
+            scopes: Vec::new(),
+            purpose: None,
+            force_login: false,
+            unique_id: None,
+            authentication_provider: None,
+        }
+    }
+
+    /// Exchanges the code Canvas redirected the user back with for an access
+    /// token.
+    ///
+    /// [See docs](https://canvas.instructure.com/doc/api/file.oauth_endpoints.html#post-login-oauth2-token).
+    pub async fn exchange_code(&self, code: &str) -> Result<OAuthToken, crate::Error> {
+        let response: TokenResponse = self
+            .request_token(&[
+                ("grant_type", "authorization_code"),
+                ("client_id", &self.client_id),
+                ("client_secret", &self.client_secret),
+                ("redirect_uri", &self.redirect_uri),
+                ("code", code),
+            ])
+            .await?;
+
+        Ok(response.into_token(None))
+    }
+
+    /// Exchanges the refresh token of `token` for a new access token.
+    ///
+    /// Canvas does not issue a new refresh token, so the refresh token of
+    /// `token` is carried over to the returned token.
+    ///
+    /// [See docs](https://canvas.instructure.com/doc/api/file.oauth_endpoints.html#post-login-oauth2-token).
+    pub async fn refresh_token(&self, token: &OAuthToken) -> Result<OAuthToken, crate::Error> {
+        let Some(refresh_token) = token.refresh_token.as_deref() else {
+            return Err(crate::Error::MissingRefreshToken);
+        };
+
+        let response: TokenResponse = self
+            .request_token(&[
+                ("grant_type", "refresh_token"),
+                ("client_id", &self.client_id),
+                ("client_secret", &self.client_secret),
+                ("refresh_token", refresh_token),
+            ])
+            .await?;
+
+        Ok(response.into_token(token.refresh_token.clone()))
+    }
+
+    /// Revokes the access token of `token`, and the refresh token it was issued
+    /// with.
+    ///
+    /// If `expire_sessions` is true, the web sessions of the user are also
+    /// ended.
+    ///
+    /// [See docs](https://canvas.instructure.com/doc/api/file.oauth_endpoints.html#delete-login-oauth2-token).
+    pub async fn revoke_token(
+        &self,
+        token: &OAuthToken,
+        expire_sessions: bool,
+    ) -> Result<(), crate::Error> {
+        let mut request = self
+            .http_client
+            .delete(format!("{}/login/oauth2/token", self.base_url))
+            .header(AUTHORIZATION, token.authorization()?);
+        if expire_sessions {
+            request = request.query(&[("expire_sessions", 1)]);
+        }
+
+        let response = request.send().await?;
+        if !response.status().is_success() {
+            return Err(crate::Error::from_response(Method::DELETE, response).await);
+        }
+
+        Ok(())
+    }
+
+    async fn request_token(&self, form: &[(&str, &str)]) -> Result<TokenResponse, crate::Error> {
+        let response = self
+            .http_client
+            .post(format!("{}/login/oauth2/token", self.base_url))
+            .form(form)
+            .send()
+            .await?;
+        if !response.status().is_success() {
+            return Err(crate::Error::from_response(Method::POST, response).await);
+        }
+
+        Ok(response.json().await?)
+    }
+}
+
+/// Configures the url a user is redirected to in order to authorize an
+/// application.
+pub struct AuthorizationUrl<'a> {
+    client: &'a OAuthClient,
+
+    state: Option<String>,
+    scopes: Vec<String>,
+    purpose: Option<String>,
+    force_login: bool,
+    unique_id: Option<String>,
+    authentication_provider: Option<String>,
+}
+
+impl<'a> AuthorizationUrl<'a> {
+    /// Sets a value Canvas returns unchanged with the redirect, used to
+    /// prevent cross-site request forgery.
+    pub fn set_state(mut self, state: impl Into<String>) -> Self {
+        self.state = Some(state.into());
+
+        self
+    }
+
+    /// Sets the scopes to request, e.g. `url:GET|/api/v1/accounts`.
+    pub fn set_scopes(mut self, scopes: Vec<String>) -> Self {
+        self.scopes = scopes;
+
+        self
+    }
+
+    /// Sets the name of the application shown on the user's profile
+    /// settings page.
+    pub fn set_purpose(mut self, purpose: impl Into<String>) -> Self {
+        self.purpose = Some(purpose.into());
+
+        self
+    }
+
+    /// Sets whether the user must log in, even if they are already logged in.
+    pub fn set_force_login(mut self, force_login: bool) -> Self {
+        self.force_login = force_login;
+
+        self
+    }
+
+    /// Sets the login to prefill the login form with.
+    pub fn set_unique_id(mut self, unique_id: impl Into<String>) -> Self {
+        self.unique_id = Some(unique_id.into());
+
+        self
+    }
+
+    /// Sets the authentication provider the user should log in with.
+    ///
+    /// See [`AccountDomain::authentication_provider`](crate::extensions::account_domains::AccountDomain::authentication_provider).
+    pub fn set_authentication_provider(
+        mut self,
+        authentication_provider: impl Into<String>,
+    ) -> Self {
+        self.authentication_provider = Some(authentication_provider.into());
+
+        self
+    }
+
+    /// Builds the authorization url.
+    pub fn build(self) -> Result<String, crate::Error> {
+        let mut params = vec![
+            ("client_id", self.client.client_id.clone()),
+            ("response_type", "code".to_string()),
+            ("redirect_uri", self.client.redirect_uri.clone()),
+        ];
+        if let Some(state) = self.state {
+            params.push(("state", state));
+        }
+        if !self.scopes.is_empty() {
+            params.push(("scope", self.scopes.join(" ")));
+        }
+        if let Some(purpose) = self.purpose {
+            params.push(("purpose", purpose));
+        }
+        if self.force_login {
+            params.push(("force_login", "1".to_string()));
+        }
+        if let Some(unique_id) = self.unique_id {
+            params.push(("unique_id", unique_id));
+        }
+        if let Some(authentication_provider) = self.authentication_provider {
+            params.push(("authentication_provider", authentication_provider));
+        }
+
+        let url = Url::parse_with_params(
+            &format!("{}/login/oauth2/auth", self.client.base_url),
+            &params,
+        )
+        .map_err(|e| crate::Error::InvalidUrl { source: e })?;
+
+        Ok(url.into())
+    }
+}
+
+/// An OAuth2 access token issued by Canvas.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct OAuthToken {
+    /// The access token used to authorize requests.
+    access_token: String,
+    /// The refresh token used to request a new access token once it expires.
+    refresh_token: Option<String>,
+    /// When the access token expires, in seconds since the unix epoch. `None`
+    /// if it does not expire.
+    expires_at: Option<u64>,
+    /// The user the token was issued for.
+    user: Option<OAuthUser>,
+}
+
+impl OAuthToken {
+    /// Creates an [`OAuthToken`], e.g. from a token persisted elsewhere.
+    pub fn new(
+        access_token: impl Into<String>,
+        refresh_token: Option<String>,
+        expires_at: Option<SystemTime>,
+    ) -> Self {
+        Self {
+            access_token: access_token.into(),
+            refresh_token,
+            expires_at: expires_at
+                .and_then(|expires_at| expires_at.duration_since(UNIX_EPOCH).ok())
+                .map(|expires_at| expires_at.as_secs()),
+            user: None,
+        }
+    }
+
+    /// The access token used to authorize requests.
+    pub fn access_token(&self) -> &str {
+        &self.access_token
+    }
+
+    /// The refresh token used to request a new access token once it expires.
+    pub fn refresh_token(&self) -> Option<&str> {
+        self.refresh_token.as_deref()
+    }
+
+    /// When the access token expires. `None` if it does not expire.
+    pub fn expires_at(&self) -> Option<SystemTime> {
+        self.expires_at
+            .map(|expires_at| UNIX_EPOCH + Duration::from_secs(expires_at))
+    }
+
+    /// The user the token was issued for. Only available on tokens returned
+    /// by Canvas.
+    pub fn user(&self) -> Option<&OAuthUser> {
+        self.user.as_ref()
+    }
+
+    /// Whether the access token has expired, or is about to.
+    pub(crate) fn is_expiring(&self) -> bool {
+        self.expires_at()
+            .is_some_and(|expires_at| expires_at <= SystemTime::now() + REFRESH_MARGIN)
+    }
+
+    /// The value of the `Authorization` header for requests made with this token.
+    pub(crate) fn authorization(&self) -> Result<HeaderValue, crate::Error> {
+        HeaderValue::from_str(&format!("Bearer {}", self.access_token))
+            .map_err(|e| crate::Error::CreatingHeader { header: e })
+    }
+}
+
+/// The user an [`OAuthToken`] was issued for.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct OAuthUser {
+    /// The ID of the user.
+    id: UserId,
+    /// The name of the user.
+    name: String,
+    /// The global ID of the user.
+    global_id: Option<String>,
+}
+
+impl OAuthUser {
+    /// The ID of the user.
+    pub fn id(&self) -> &UserId {
+        &self.id
+    }
+
+    /// The name of the user.
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    /// The global ID of the user.
+    pub fn global_id(&self) -> Option<&str> {
+        self.global_id.as_deref()
+    }
+}
+
+// https://canvas.instructure.com/doc/api/file.oauth_endpoints.html#post-login-oauth2-token
+#[derive(Deserialize)]
+struct TokenResponse {
+    access_token: String,
+    refresh_token: Option<String>,
+    expires_in: Option<u64>,
+    user: Option<OAuthUser>,
+}
+
+impl TokenResponse {
+    fn into_token(self, previous_refresh_token: Option<String>) -> OAuthToken {
+        let expires_at = self.expires_in.map(|expires_in| {
+            SystemTime::now()
+                .duration_since(UNIX_EPOCH)
+                .unwrap_or_default()
+                .as_secs()
+                + expires_in
+        });
+
+        OAuthToken {
+            access_token: self.access_token,
+            refresh_token: self.refresh_token.or(previous_refresh_token),
+            expires_at,
+            user: self.user,
+        }
+    }
+}
+
+/// Persists the [`OAuthToken`] of a [`CanvasClient`](crate::CanvasClient)
+/// between refreshes.
+///
+/// Implement this to store tokens in a database or keychain, keyed by whatever
+/// identifies the user the client acts for.
+#[async_trait]
+pub trait TokenStore: Send + Sync {
+    /// Loads the current token, or `None` if no token has been stored.
+    async fn load(&self) -> Result<Option<OAuthToken>, crate::Error>;
+
+    /// Stores a new token, replacing the current one.
+    async fn store(&self, token: &OAuthToken) -> Result<(), crate::Error>;
+
+    /// Removes the current token.
+    async fn clear(&self) -> Result<(), crate::Error>;
+}
+
+/// A [`TokenStore`] that keeps the token in memory.
+#[derive(Debug, Default)]
+pub struct MemoryTokenStore {
+    token: Mutex<Option<OAuthToken>>,
+}
+
+impl MemoryTokenStore {
+    /// Creates a new [`MemoryTokenStore`] holding `token`.
+    pub fn new(token: OAuthToken) -> Self {
+        Self {
+            token: Mutex::new(Some(token)),
+        }
+    }
+}
+
+#[async_trait]
+impl TokenStore for MemoryTokenStore {
+    async fn load(&self) -> Result<Option<OAuthToken>, crate::Error> {
+        Ok(self.token.lock().expect("token store poisoned").clone())
+    }
+
+    async fn store(&self, token: &OAuthToken) -> Result<(), crate::Error> {
+        *self.token.lock().expect("token store poisoned") = Some(token.clone());
+
+        Ok(())
+    }
+
+    async fn clear(&self) -> Result<(), crate::Error> {
+        *self.token.lock().expect("token store poisoned") = None;
+
+        Ok(())
+    }
+}
+
+/// The OAuth2 credentials of a [`CanvasClient`](crate::CanvasClient).
+pub(crate) struct OAuthSession {
+    client: OAuthClient,
+    store: Arc<dyn TokenStore>,
+    /// Held while refreshing, so that concurrent requests that fail with an
+    /// expired token only refresh it once.
+    refresh_lock: tokio::sync::Mutex<()>,
+}
+
+impl OAuthSession {
+    pub(crate) fn new(client: OAuthClient, store: Arc<dyn TokenStore>) -> Self {
+        Self {
+            client,
+            store,
+            refresh_lock: tokio::sync::Mutex::new(()),
+        }
+    }
+
+    /// The current token, refreshing it first if it is about to expire.
+    pub(crate) async fn token(&self) -> Result<OAuthToken, crate::Error> {
+        let token = self.load().await?;
+
+        if token.is_expiring() && token.refresh_token.is_some() {
+            return self.refresh(&token).await;
+        }
+
+        Ok(token)
+    }
+
+    /// Refreshes `stale`, unless another request already replaced it.
+    pub(crate) async fn refresh(&self, stale: &OAuthToken) -> Result<OAuthToken, crate::Error> {
+        let _guard = self.refresh_lock.lock().await;
+
+        let current = self.load().await?;
+        if current.access_token != stale.access_token {
+            return Ok(current);
+        }
+
+        let token = self.client.refresh_token(&current).await?;
+        self.store.store(&token).await?;
+
+        Ok(token)
+    }
+
+    /// Revokes the current token and removes it from the store.
+    pub(crate) async fn revoke(&self, expire_sessions: bool) -> Result<(), crate::Error> {
+        let token = self.load().await?;
+
+        self.client.revoke_token(&token, expire_sessions).await?;
+        self.store.clear().await
+    }
+
+    async fn load(&self) -> Result<OAuthToken, crate::Error> {
+        self.store.load().await?.ok_or(crate::Error::MissingToken)
+    }
+}
@@ -0,0 +1,395 @@
+use std::{
+    sync::{Arc, Mutex},
+    time::Duration,
+};
+
+use reqwest::{header::HeaderMap, StatusCode};
+use tokio::time::Instant;
+
+/// The header Canvas reports the remaining rate limit quota in.
+const RATE_LIMIT_REMAINING: &str = "X-Rate-Limit-Remaining";
+/// The header Canvas reports the cost of the request in.
+const REQUEST_COST: &str = "X-Request-Cost";
+
+/// Configures how a [`CanvasClient`](crate::CanvasClient) throttles itself to
+/// stay within the Canvas rate limit.
+///
+/// Canvas uses a leaky bucket: each request costs some amount of quota
+/// (reported in the `X-Request-Cost` header), the remaining quota is reported
+/// in the `X-Rate-Limit-Remaining` header, and the quota regenerates over time.
+/// Once the quota is exhausted, Canvas responds with `403 Rate Limit Exceeded`.
+///
+/// The client tracks the remaining quota from every response. Once it drops
+/// below [`RateLimitConfig::set_slow_below`], new requests are delayed by an
+/// amount that grows as the quota shrinks. Once it drops below
+/// [`RateLimitConfig::set_pause_below`], new requests wait until the quota has
+/// regenerated.
+///
+/// [See docs](https://canvas.instructure.com/doc/api/file.throttling.html).
+#[derive(Debug, Clone)]
+pub struct RateLimitConfig {
+    /// The remaining quota below which requests are delayed.
+    slow_below: f64,
+    /// The remaining quota below which requests wait for the quota to regenerate.
+    pause_below: f64,
+    /// The longest delay applied to a request before the quota is paused.
+    max_delay: Duration,
+    /// The quota regenerated per second.
+    leak_rate: f64,
+    /// How long to pause all requests after being throttled.
+    throttled_pause: Duration,
+}
+
+impl Default for RateLimitConfig {
+    fn default() -> Self {
+        Self {
+            slow_below: 300.0,
+            pause_below: 100.0,
+            max_delay: Duration::from_secs(2),
+            leak_rate: 10.0,
+            throttled_pause: Duration::from_secs(10),
+        }
+    }
+}
+
+impl RateLimitConfig {
+    /// Creates a new [`RateLimitConfig`] with the default thresholds.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Sets the remaining quota below which requests are delayed. Defaults to `300`.
+    pub fn set_slow_below(mut self, slow_below: f64) -> Self {
+        self.slow_below = slow_below;
+
+        self
+    }
+
+    /// Sets the remaining quota below which requests wait for the quota to
+    /// regenerate. Defaults to `100`.
+    pub fn set_pause_below(mut self, pause_below: f64) -> Self {
+        self.pause_below = pause_below;
+
+        self
+    }
+
+    /// Sets the longest delay applied to a request while the quota is between
+    /// the slow and pause thresholds. Defaults to 2 seconds.
+    pub fn set_max_delay(mut self, max_delay: Duration) -> Self {
+        self.max_delay = max_delay;
+
+        self
+    }
+
+    /// Sets the quota regenerated per second. Defaults to `10`, the Canvas default.
+    pub fn set_leak_rate(mut self, leak_rate: f64) -> Self {
+        self.leak_rate = leak_rate;
+
+        self
+    }
+
+    /// Sets how long to pause all requests after a request is throttled.
+    /// Defaults to 10 seconds.
+    pub fn set_throttled_pause(mut self, throttled_pause: Duration) -> Self {
+        self.throttled_pause = throttled_pause;
+
+        self
+    }
+}
+
+/// Tracks the remaining rate limit quota across every request made by a client.
+#[derive(Debug)]
+pub(crate) struct RateLimiter {
+    config: RateLimitConfig,
+    state: Arc<Mutex<RateLimitState>>,
+}
+
+#[derive(Debug)]
+struct RateLimitState {
+    /// The remaining quota last reported by Canvas. `None` until the first
+    /// response is received.
+    remaining: Option<f64>,
+    /// When `remaining` was reported.
+    reported_at: Instant,
+    /// The estimated cost of the requests that have been sent but not yet
+    /// responded to.
+    in_flight: f64,
+    /// A running average of the cost of a request.
+    average_cost: f64,
+    /// When set, no requests are sent until this instant.
+    paused_until: Option<Instant>,
+}
+
+/// A reservation of quota for a single request.
+///
+/// The reservation is released once the response is recorded, or when the
+/// permit is dropped if the request never completes.
+pub(crate) struct RateLimitPermit {
+    state: Arc<Mutex<RateLimitState>>,
+    cost: f64,
+}
+
+impl RateLimiter {
+    pub(crate) fn new(config: RateLimitConfig) -> Self {
+        Self {
+            config,
+            state: Arc::new(Mutex::new(RateLimitState {
+                remaining: None,
+                reported_at: Instant::now(),
+                in_flight: 0.0,
+                // Canvas charges a pre-flight penalty of 50 for each request
+                average_cost: 50.0,
+                paused_until: None,
+            })),
+        }
+    }
+
+    /// Waits until there is enough quota to send a request, and reserves the
+    /// estimated cost of the request.
+    pub(crate) async fn acquire(&self) -> RateLimitPermit {
+        loop {
+            match self.try_acquire() {
+                Ok((permit, delay)) => {
+                    if !delay.is_zero() {
+                        tokio::time::sleep(delay).await;
+                    }
+
+                    return permit;
+                }
+                Err(wait) => tokio::time::sleep(wait).await,
+            }
+        }
+    }
+
+    /// Reserves quota for a request if enough is available, returning the
+    /// permit and how long to delay the request for. Otherwise returns how long
+    /// to wait before trying again.
+    fn try_acquire(&self) -> Result<(RateLimitPermit, Duration), Duration> {
+        let mut state = self.state.lock().expect("rate limit state poisoned");
+        let now = Instant::now();
+
+        if let Some(paused_until) = state.paused_until {
+            if paused_until > now {
+                return Err(paused_until - now);
+            }
+            state.paused_until = None;
+        }
+
+        let available = state.available(now, self.config.leak_rate);
+        if let Some(available) = available {
+            if available < self.config.pause_below {
+                let missing = self.config.pause_below - available;
+                return Err(Duration::from_secs_f64(
+                    missing / self.config.leak_rate.max(f64::EPSILON),
+                ));
+            }
+        }
+
+        let cost = state.average_cost;
+        state.in_flight += cost;
+
+        let permit = RateLimitPermit {
+            state: Arc::clone(&self.state),
+            cost,
+        };
+        let delay = available
+            .map(|available| self.delay_for(available))
+            .unwrap_or_default();
+
+        Ok((permit, delay))
+    }
+
+    /// Records the response to a request, updating the remaining quota.
+    pub(crate) fn record(&self, permit: RateLimitPermit, status: StatusCode, headers: &HeaderMap) {
+        let remaining = header_f64(headers, RATE_LIMIT_REMAINING);
+        let cost = header_f64(headers, REQUEST_COST);
+
+        let mut state = self.state.lock().expect("rate limit state poisoned");
+        let now = Instant::now();
+
+        if let Some(remaining) = remaining {
+            state.remaining = Some(remaining);
+            state.reported_at = now;
+        }
+        if let Some(cost) = cost {
+            state.average_cost = state.average_cost * 0.8 + cost * 0.2;
+        }
+
+        let throttled = status == StatusCode::TOO_MANY_REQUESTS
+            || (status == StatusCode::FORBIDDEN
+                && remaining.is_some_and(|remaining| remaining <= 0.0));
+        if throttled {
+            state.remaining = Some(0.0);
+            state.reported_at = now;
+            state.paused_until = Some(now + self.config.throttled_pause);
+        }
+
+        // releases the reservation now that the real cost is known
+        drop(state);
+        drop(permit);
+    }
+
+    /// The delay to apply to a request when `available` quota remains.
+    fn delay_for(&self, available: f64) -> Duration {
+        if available >= self.config.slow_below {
+            return Duration::ZERO;
+        }
+
+        let range = (self.config.slow_below - self.config.pause_below).max(f64::EPSILON);
+        let severity = ((self.config.slow_below - available) / range).clamp(0.0, 1.0);
+
+        self.config.max_delay.mul_f64(severity)
+    }
+}
+
+impl RateLimitState {
+    /// The estimated quota available to new requests, accounting for
+    /// regeneration since the last report and requests still in flight.
+    fn available(&self, now: Instant, leak_rate: f64) -> Option<f64> {
+        let remaining = self.remaining?;
+        let regenerated = now.duration_since(self.reported_at).as_secs_f64() * leak_rate;
+
+        Some(remaining + regenerated - self.in_flight)
+    }
+}
+
+impl Drop for RateLimitPermit {
+    fn drop(&mut self) {
+        if let Ok(mut state) = self.state.lock() {
+            state.in_flight = (state.in_flight - self.cost).max(0.0);
+        }
+    }
+}
+
+fn header_f64(headers: &HeaderMap, name: &str) -> Option<f64> {
+    headers.get(name)?.to_str().ok()?.trim().parse().ok()
+}
+
+#[cfg(test)]
+mod tests {
+    use reqwest::header::HeaderValue;
+
+    use super::*;
+
+    /// The headers of a response that left `remaining` quota.
+    fn quota(remaining: f64) -> HeaderMap {
+        let mut headers = HeaderMap::new();
+        headers.insert(
+            RATE_LIMIT_REMAINING,
+            HeaderValue::from_str(&remaining.to_string()).unwrap(),
+        );
+
+        headers
+    }
+
+    /// A limiter that makes requests wait below 100 quota, without delaying
+    /// them above it.
+    fn pausing_limiter() -> RateLimiter {
+        RateLimiter::new(
+            RateLimitConfig::new()
+                .set_slow_below(100.0)
+                .set_pause_below(100.0),
+        )
+    }
+
+    /// Sends a request through `limiter` that Canvas responds to with `status`
+    /// and `headers`.
+    async fn respond(limiter: &RateLimiter, status: StatusCode, headers: &HeaderMap) {
+        let permit = limiter.acquire().await;
+        limiter.record(permit, status, headers);
+    }
+
+    /// Asserts that a permit is acquired after `expected`, give or take the
+    /// millisecond resolution of the timer.
+    async fn assert_acquired_after(limiter: &RateLimiter, expected: Duration) -> RateLimitPermit {
+        let start = Instant::now();
+        let permit = limiter.acquire().await;
+        let elapsed = start.elapsed();
+
+        assert!(
+            elapsed >= expected && elapsed <= expected + Duration::from_millis(5),
+            "acquired after {elapsed:?}, expected {expected:?}"
+        );
+
+        permit
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn sends_first_request_immediately() {
+        let limiter = RateLimiter::new(RateLimitConfig::default());
+
+        assert_acquired_after(&limiter, Duration::ZERO).await;
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn delays_requests_as_quota_shrinks() {
+        let limiter = RateLimiter::new(RateLimitConfig::default());
+
+        respond(&limiter, StatusCode::OK, &quota(200.0)).await;
+
+        // halfway between the slow and pause thresholds
+        assert_acquired_after(&limiter, Duration::from_secs(1)).await;
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn waits_for_the_bucket_to_refill() {
+        let limiter = pausing_limiter();
+
+        respond(&limiter, StatusCode::OK, &quota(50.0)).await;
+
+        // 50 quota regenerates at 10 per second
+        assert_acquired_after(&limiter, Duration::from_secs(5)).await;
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn refills_the_bucket_while_idle() {
+        let limiter = pausing_limiter();
+
+        respond(&limiter, StatusCode::OK, &quota(50.0)).await;
+        tokio::time::advance(Duration::from_secs(5)).await;
+
+        assert_acquired_after(&limiter, Duration::ZERO).await;
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn reserves_quota_for_requests_in_flight() {
+        let limiter = pausing_limiter();
+        respond(&limiter, StatusCode::OK, &quota(150.0)).await;
+
+        let first = assert_acquired_after(&limiter, Duration::ZERO).await;
+        let second = assert_acquired_after(&limiter, Duration::ZERO).await;
+        // both requests in flight reserve 50 quota each
+        let third = assert_acquired_after(&limiter, Duration::from_secs(5)).await;
+
+        drop((first, second, third));
+        assert_acquired_after(&limiter, Duration::ZERO).await;
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn pauses_after_being_throttled() {
+        let limiter = pausing_limiter();
+
+        respond(&limiter, StatusCode::FORBIDDEN, &quota(0.0)).await;
+
+        // the 100 quota needed regenerates within the 10 second pause
+        assert_acquired_after(&limiter, Duration::from_secs(10)).await;
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn pauses_after_too_many_requests() {
+        let limiter = pausing_limiter();
+
+        respond(&limiter, StatusCode::TOO_MANY_REQUESTS, &HeaderMap::new()).await;
+
+        assert_acquired_after(&limiter, Duration::from_secs(10)).await;
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn does_not_pause_on_other_forbidden_responses() {
+        let limiter = pausing_limiter();
+
+        respond(&limiter, StatusCode::FORBIDDEN, &quota(500.0)).await;
+
+        assert_acquired_after(&limiter, Duration::ZERO).await;
+    }
+}
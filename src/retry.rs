@@ -0,0 +1,177 @@
+use std::time::Duration;
+
+use rand::Rng;
+use reqwest::{Method, StatusCode};
+
+/// Configures how a [`CanvasClient`](crate::CanvasClient) retries requests
+/// that failed with a transient error.
+///
+/// Retries are delayed with exponential backoff: the delay before the `n`th
+/// retry is `base_delay * 2^(n - 1)`, capped at `max_delay`. With jitter
+/// enabled, a random delay between zero and that value is used instead, so
+/// that many clients failing at once do not retry in lockstep.
+///
+/// Requests that are not idempotent (`POST` and `PATCH`) are only retried if
+/// [`RetryPolicy::set_retry_non_idempotent`] is enabled, as Canvas may have
+/// processed the request before it failed.
+#[derive(Debug, Clone)]
+pub struct RetryPolicy {
+    /// The maximum number of attempts, including the first.
+    max_attempts: u32,
+    /// The delay before the first retry.
+    base_delay: Duration,
+    /// The longest delay between attempts.
+    max_delay: Duration,
+    /// Whether to randomize the delay between attempts.
+    jitter: bool,
+    /// The status codes to retry.
+    retry_statuses: Vec<StatusCode>,
+    /// Whether to retry requests that were throttled by the rate limit.
+    retry_throttled: bool,
+    /// Whether to retry requests that failed to connect or were reset.
+    retry_connection_errors: bool,
+    /// Whether to retry requests that timed out.
+    retry_timeouts: bool,
+    /// Whether to retry `POST` and `PATCH` requests.
+    retry_non_idempotent: bool,
+}
+
+impl Default for RetryPolicy {
+    fn default() -> Self {
+        Self {
+            max_attempts: 3,
+            base_delay: Duration::from_millis(500),
+            max_delay: Duration::from_secs(30),
+            jitter: true,
+            retry_statuses: vec![
+                StatusCode::INTERNAL_SERVER_ERROR,
+                StatusCode::BAD_GATEWAY,
+                StatusCode::SERVICE_UNAVAILABLE,
+                StatusCode::GATEWAY_TIMEOUT,
+            ],
+            retry_throttled: true,
+            retry_connection_errors: true,
+            retry_timeouts: true,
+            retry_non_idempotent: false,
+        }
+    }
+}
+
+impl RetryPolicy {
+    /// Creates a new [`RetryPolicy`] that makes up to 3 attempts on transient errors.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Creates a [`RetryPolicy`] that never retries requests.
+    pub fn never() -> Self {
+        Self::default().set_max_attempts(1)
+    }
+
+    /// Sets the maximum number of attempts, including the first. Defaults to `3`.
+    pub fn set_max_attempts(mut self, max_attempts: u32) -> Self {
+        self.max_attempts = max_attempts.max(1);
+
+        self
+    }
+
+    /// Sets the delay before the first retry. Defaults to 500 milliseconds.
+    pub fn set_base_delay(mut self, base_delay: Duration) -> Self {
+        self.base_delay = base_delay;
+
+        self
+    }
+
+    /// Sets the longest delay between attempts. Defaults to 30 seconds.
+    pub fn set_max_delay(mut self, max_delay: Duration) -> Self {
+        self.max_delay = max_delay;
+
+        self
+    }
+
+    /// Sets whether to randomize the delay between attempts. Defaults to `true`.
+    pub fn set_jitter(mut self, jitter: bool) -> Self {
+        self.jitter = jitter;
+
+        self
+    }
+
+    /// Sets the status codes to retry.
+    ///
+    /// Defaults to `500`, `502`, `503` and `504`.
+    pub fn set_retry_statuses(mut self, retry_statuses: Vec<StatusCode>) -> Self {
+        self.retry_statuses = retry_statuses;
+
+        self
+    }
+
+    /// Sets whether to retry requests that were throttled by the Canvas rate
+    /// limit. Defaults to `true`.
+    pub fn set_retry_throttled(mut self, retry_throttled: bool) -> Self {
+        self.retry_throttled = retry_throttled;
+
+        self
+    }
+
+    /// Sets whether to retry requests that failed to connect, or whose
+    /// connection was reset. Defaults to `true`.
+    pub fn set_retry_connection_errors(mut self, retry_connection_errors: bool) -> Self {
+        self.retry_connection_errors = retry_connection_errors;
+
+        self
+    }
+
+    /// Sets whether to retry requests that timed out. Defaults to `true`.
+    pub fn set_retry_timeouts(mut self, retry_timeouts: bool) -> Self {
+        self.retry_timeouts = retry_timeouts;
+
+        self
+    }
+
+    /// Sets whether to retry requests that are not idempotent (`POST` and
+    /// `PATCH`). Defaults to `false`.
+    pub fn set_retry_non_idempotent(mut self, retry_non_idempotent: bool) -> Self {
+        self.retry_non_idempotent = retry_non_idempotent;
+
+        self
+    }
+
+    /// Whether a `method` request that failed with `error` on attempt number
+    /// `attempt` should be retried.
+    pub(crate) fn should_retry(&self, method: &Method, attempt: u32, error: &crate::Error) -> bool {
+        if attempt >= self.max_attempts {
+            return false;
+        }
+
+        let idempotent = !matches!(*method, Method::POST | Method::PATCH);
+        if !idempotent && !self.retry_non_idempotent {
+            return false;
+        }
+
+        match error {
+            crate::Error::Throttled { .. } => self.retry_throttled,
+            crate::Error::Reqwest(e) if e.is_timeout() => self.retry_timeouts,
+            crate::Error::Reqwest(e) if e.is_connect() || e.is_request() => {
+                self.retry_connection_errors
+            }
+            error => error
+                .response()
+                .is_some_and(|response| self.retry_statuses.contains(&response.status())),
+        }
+    }
+
+    /// The delay before retrying after attempt number `attempt` failed.
+    pub(crate) fn delay(&self, attempt: u32) -> Duration {
+        let exponent = attempt.saturating_sub(1).min(31);
+        let delay = self
+            .base_delay
+            .saturating_mul(1 << exponent)
+            .min(self.max_delay);
+
+        if self.jitter {
+            delay.mul_f64(rand::thread_rng().gen_range(0.0..=1.0))
+        } else {
+            delay
+        }
+    }
+}
@@ -0,0 +1,212 @@
+//! Managing account notifications as an admin against a `MockCanvas`.
+
+use canvas_api::{
+    extensions::account_notifications::{AccountNotification, NotificationQuery},
+    mock::{MockCanvas, MockResponse},
+    AccountId, AccountNotificationsExt, CanvasClient, NotificationId, RetryPolicy,
+};
+use reqwest::Method;
+use serde_json::{json, Value};
+
+fn client(canvas: &MockCanvas) -> CanvasClient {
+    canvas
+        .client_builder()
+        .set_retry_policy(RetryPolicy::never())
+        .build()
+        .unwrap()
+}
+
+fn datetime(s: &str) -> canvas_api::DateTime {
+    #[cfg(feature = "chrono")]
+    return chrono::DateTime::parse_from_rfc3339(s).unwrap();
+    #[cfg(not(feature = "chrono"))]
+    return time::OffsetDateTime::parse(s, &time::format_description::well_known::Rfc3339).unwrap();
+}
+
+fn notification(id: u64, start_at: &str, end_at: &str) -> Value {
+    json!({
+        "id": id,
+        "subject": format!("Notification {id}"),
+        "message": "A message.",
+        "start_at": start_at,
+        "end_at": end_at,
+        "icon": "warning",
+    })
+}
+
+#[tokio::test]
+async fn expires_started_notification_by_ending_it() {
+    let canvas = MockCanvas::start().await;
+    let current = notification(5, "2000-01-01T00:00:00Z", "2999-01-01T00:00:00Z");
+    canvas.register(
+        Method::PUT,
+        "v1/accounts/1/account_notifications/5",
+        MockResponse::ok(current.clone()),
+    );
+    let client = client(&canvas);
+    let current: AccountNotification = serde_json::from_value(current).unwrap();
+
+    client
+        .expire_global_notification(AccountId::new(1), &current)
+        .await
+        .unwrap();
+
+    let request = canvas.assert_received(Method::PUT, "v1/accounts/1/account_notifications/5");
+    assert!(request.form_value("account_notification[end_at]").is_some());
+    assert_eq!(request.form_value("account_notification[start_at]"), None);
+}
+
+#[tokio::test]
+async fn expires_scheduled_notification_by_starting_and_ending_it() {
+    let canvas = MockCanvas::start().await;
+    let scheduled = notification(6, "2999-01-01T00:00:00Z", "2999-02-01T00:00:00Z");
+    canvas.register(
+        Method::PUT,
+        "v1/accounts/1/account_notifications/6",
+        MockResponse::ok(scheduled.clone()),
+    );
+    let client = client(&canvas);
+    let scheduled: AccountNotification = serde_json::from_value(scheduled).unwrap();
+
+    client
+        .expire_global_notification(AccountId::new(1), &scheduled)
+        .await
+        .unwrap();
+
+    let request = canvas.assert_received(Method::PUT, "v1/accounts/1/account_notifications/6");
+    let start_at = request.form_value("account_notification[start_at]");
+    assert!(start_at.is_some());
+    assert_eq!(start_at, request.form_value("account_notification[end_at]"));
+}
+
+#[tokio::test]
+async fn does_not_expire_notification_without_id() {
+    let canvas = MockCanvas::start().await;
+    let client = client(&canvas);
+    let mut unsaved = notification(7, "2000-01-01T00:00:00Z", "2999-01-01T00:00:00Z");
+    unsaved.as_object_mut().unwrap().remove("id");
+    let unsaved: AccountNotification = serde_json::from_value(unsaved).unwrap();
+
+    let error = client
+        .expire_global_notification(AccountId::new(1), &unsaved)
+        .await
+        .unwrap_err();
+
+    match error {
+        canvas_api::Error::UnsavedNotification { subject } => {
+            assert_eq!(subject, "Notification 7");
+        }
+        e => panic!("unexpected error {e:?}"),
+    }
+    assert!(canvas.received_requests().is_empty());
+}
+
+/// Builds a notification shown from `start_at` until `end_at`, and creates it
+/// if it is valid.
+async fn create_notification(
+    canvas: &MockCanvas,
+    start_at: &str,
+    end_at: &str,
+) -> Result<(), canvas_api::Error> {
+    let notification = AccountNotification::builder(
+        "Maintenance",
+        "Canvas will be unavailable.",
+        datetime(start_at),
+        datetime(end_at),
+    )
+    .build()?;
+    client(canvas)
+        .create_global_notification(AccountId::new(1), &notification)
+        .await?;
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn rejects_notification_ending_when_it_starts() {
+    let canvas = MockCanvas::start().await;
+
+    let error = create_notification(&canvas, "2999-01-01T00:00:00Z", "2999-01-01T00:00:00Z")
+        .await
+        .unwrap_err();
+
+    match error {
+        canvas_api::Error::InvalidNotificationWindow { start_at, end_at } => {
+            assert_eq!(start_at, datetime("2999-01-01T00:00:00Z"));
+            assert_eq!(end_at, start_at);
+        }
+        e => panic!("unexpected error {e:?}"),
+    }
+    assert!(canvas.received_requests().is_empty());
+}
+
+#[tokio::test]
+async fn rejects_notification_ending_before_it_starts() {
+    let canvas = MockCanvas::start().await;
+
+    let error = create_notification(&canvas, "2999-01-02T00:00:00Z", "2999-01-01T00:00:00Z")
+        .await
+        .unwrap_err();
+
+    match error {
+        canvas_api::Error::InvalidNotificationWindow { start_at, end_at } => {
+            assert_eq!(start_at, datetime("2999-01-02T00:00:00Z"));
+            assert_eq!(end_at, datetime("2999-01-01T00:00:00Z"));
+        }
+        e => panic!("unexpected error {e:?}"),
+    }
+    assert!(canvas.received_requests().is_empty());
+}
+
+#[tokio::test]
+async fn collects_notifications_of_account_tree_once() {
+    let canvas = MockCanvas::start().await;
+    canvas.register_pages(
+        "v1/accounts/1/sub_accounts",
+        vec![json!([{ "id": 2, "name": "Science", "parent_account_id": 1 }])],
+    );
+    let root = notification(10, "2000-01-01T00:00:00Z", "2999-01-01T00:00:00Z");
+    let science = notification(20, "2000-01-01T00:00:00Z", "2999-01-01T00:00:00Z");
+    canvas.register_pages(
+        "v1/accounts/1/account_notifications",
+        vec![json!([root.clone()])],
+    );
+    // sub-accounts also list the notifications of their parents
+    canvas.register_pages(
+        "v1/accounts/2/account_notifications",
+        vec![json!([root, science])],
+    );
+    let client = client(&canvas);
+
+    let tree = client
+        .get_notifications_for_account_tree(
+            AccountId::new(1),
+            &NotificationQuery::new().set_include_past(true),
+        )
+        .await
+        .unwrap();
+
+    let ids: Vec<(AccountId, Vec<NotificationId>)> = tree
+        .iter()
+        .map(|account| {
+            (
+                account.account_id().clone(),
+                account
+                    .notifications()
+                    .iter()
+                    .filter_map(|notification| notification.id().cloned())
+                    .collect(),
+            )
+        })
+        .collect();
+    assert_eq!(
+        ids,
+        [
+            (AccountId::new(1), vec![NotificationId::new(10)]),
+            (AccountId::new(2), vec![NotificationId::new(20)]),
+        ]
+    );
+    let request = canvas.assert_received(Method::GET, "v1/accounts/2/account_notifications");
+    assert_eq!(request.query_value("include_past"), Some("true"));
+    assert_eq!(request.query_value("include_all"), Some("true"));
+}
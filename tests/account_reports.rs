@@ -0,0 +1,720 @@
+//! Polling and downloading account reports from a `MockCanvas`.
+
+use std::{path::PathBuf, time::Duration};
+
+use canvas_api::{
+    extensions::account_reports::{
+        parameters::{
+            GradeExportReport, NoSubmissionsReport, ProvisioningReport, ReportParameterProblem,
+        },
+        retention::{RetentionPolicy, RetentionRule},
+        rows::{ReportRows, TermRow, UserRow},
+        CreateReportForm, Report, ReportParameters, ReportPollConfig, ReportStatus,
+    },
+    mock::{MockCanvas, MockResponse},
+    AccountId, AccountReportsExt, CanvasClient, CourseId, ReportId, RetryPolicy, TermId,
+};
+use reqwest::Method;
+use serde_json::{json, Value};
+
+const REPORT_PATH: &str = "v1/accounts/1/reports/grade_export_csv/7";
+
+fn report(status: &str, parameters: Value) -> Value {
+    json!({
+        "id": 7,
+        "report": "grade_export_csv",
+        "status": status,
+        "created_at": "2024-06-01T00:00:00Z",
+        "parameters": parameters,
+        "progress": if status == "complete" { 100 } else { 50 },
+    })
+}
+
+fn client(canvas: &MockCanvas) -> CanvasClient {
+    canvas
+        .client_builder()
+        .set_retry_policy(RetryPolicy::never())
+        .build()
+        .unwrap()
+}
+
+async fn get_report(client: &CanvasClient) -> Report {
+    client
+        .get_report_by_id(
+            AccountId::new(1),
+            String::from("grade_export_csv"),
+            ReportId::new(7),
+        )
+        .await
+        .unwrap()
+}
+
+fn datetime(s: &str) -> canvas_api::DateTime {
+    #[cfg(feature = "chrono")]
+    return chrono::DateTime::parse_from_rfc3339(s).unwrap();
+    #[cfg(not(feature = "chrono"))]
+    return time::OffsetDateTime::parse(s, &time::format_description::well_known::Rfc3339).unwrap();
+}
+
+/// The time `seconds` ago, as an RFC 3339 timestamp.
+fn ago(seconds: u64) -> String {
+    #[cfg(feature = "chrono")]
+    return chrono::DateTime::<chrono::Utc>::from(
+        std::time::SystemTime::now() - Duration::from_secs(seconds),
+    )
+    .to_rfc3339();
+    #[cfg(not(feature = "chrono"))]
+    return (time::OffsetDateTime::now_utc() - Duration::from_secs(seconds))
+        .format(&time::format_description::well_known::Rfc3339)
+        .unwrap();
+}
+
+fn poll() -> ReportPollConfig {
+    ReportPollConfig::new()
+        .set_interval(Duration::ZERO)
+        .set_timeout(Duration::from_secs(10))
+}
+
+#[tokio::test]
+async fn parses_parameters_as_submitted() {
+    let canvas = MockCanvas::start().await;
+    canvas.register(
+        Method::GET,
+        REPORT_PATH,
+        MockResponse::ok(report(
+            "complete",
+            json!({
+                "enrollment_term_id": "",
+                "include_deleted": "true",
+                "enrollments": "0",
+                "start_at": "",
+                "end_at": "2024-06-14",
+            }),
+        )),
+    );
+    let client = client(&canvas);
+
+    let report = get_report(&client).await;
+
+    let parameters = report.parameters();
+    assert_eq!(parameters.enrollment_term_id(), None);
+    assert_eq!(parameters.include_deleted(), Some(true));
+    assert_eq!(parameters.enrollments(), Some(false));
+    assert_eq!(parameters.start_at(), None);
+    assert_eq!(parameters.end_at(), Some(datetime("2024-06-14T00:00:00Z")));
+}
+
+#[tokio::test]
+async fn waits_for_report_to_complete() {
+    let canvas = MockCanvas::start().await;
+    canvas.register(
+        Method::GET,
+        REPORT_PATH,
+        MockResponse::ok(report("running", json!({}))),
+    );
+    canvas.register(
+        Method::GET,
+        REPORT_PATH,
+        MockResponse::ok(report("complete", json!({}))),
+    );
+    let client = client(&canvas);
+    let created: Report = serde_json::from_value(report("created", json!({}))).unwrap();
+
+    let report = client
+        .wait_for_report(AccountId::new(1), created, &poll())
+        .await
+        .unwrap();
+
+    assert_eq!(report.status(), ReportStatus::Complete);
+    assert_eq!(canvas.received_requests().len(), 2);
+}
+
+#[tokio::test]
+async fn fails_when_report_is_aborted() {
+    let canvas = MockCanvas::start().await;
+    canvas.register(
+        Method::GET,
+        REPORT_PATH,
+        MockResponse::ok(report("aborted", json!({}))),
+    );
+    let client = client(&canvas);
+    let created: Report = serde_json::from_value(report("running", json!({}))).unwrap();
+
+    let error = client
+        .wait_for_report(AccountId::new(1), created, &poll())
+        .await
+        .unwrap_err();
+
+    match error {
+        canvas_api::Error::ReportFailed { report } => {
+            assert_eq!(report.status(), ReportStatus::Aborted);
+            assert!(report.status().is_finished());
+        }
+        e => panic!("unexpected error {e:?}"),
+    }
+    assert_eq!(canvas.received_requests().len(), 1);
+}
+
+#[tokio::test]
+async fn times_out_when_report_does_not_finish() {
+    let canvas = MockCanvas::start().await;
+    canvas.register(
+        Method::GET,
+        REPORT_PATH,
+        MockResponse::ok(report("running", json!({}))),
+    );
+    let client = client(&canvas);
+    let created: Report = serde_json::from_value(report("running", json!({}))).unwrap();
+
+    let error = client
+        .wait_for_report(
+            AccountId::new(1),
+            created,
+            &ReportPollConfig::new()
+                .set_interval(Duration::from_millis(10))
+                .set_timeout(Duration::from_millis(50)),
+        )
+        .await
+        .unwrap_err();
+
+    assert!(matches!(error, canvas_api::Error::ReportTimedOut { .. }));
+}
+
+#[tokio::test]
+async fn downloads_report_file() {
+    let canvas = MockCanvas::start().await;
+    let file_url = canvas.api_url().replace("/api", "/files/3/download");
+    canvas.register(
+        Method::GET,
+        "/files/3/download",
+        MockResponse::bytes("text/csv", "user_id,grade\n1,A\n"),
+    );
+    let client = client(&canvas);
+    let mut completed = report("complete", json!({}));
+    completed["file_url"] = json!(file_url);
+    let completed: Report = serde_json::from_value(completed).unwrap();
+
+    let mut contents = Vec::new();
+    let written = client
+        .download_report(&completed, &mut contents)
+        .await
+        .unwrap();
+
+    assert_eq!(contents, b"user_id,grade\n1,A\n");
+    assert_eq!(written, contents.len() as u64);
+}
+
+#[tokio::test]
+async fn fails_to_download_report_without_file() {
+    let canvas = MockCanvas::start().await;
+    let client = client(&canvas);
+    let running: Report = serde_json::from_value(report("running", json!({}))).unwrap();
+
+    let error = client
+        .download_report(&running, &mut Vec::new())
+        .await
+        .unwrap_err();
+
+    assert!(matches!(error, canvas_api::Error::MissingReportFile { .. }));
+    assert!(canvas.received_requests().is_empty());
+}
+
+/// A completed report whose attachment is the CSV file `filename`, downloaded
+/// from the mock.
+fn register_csv_report(canvas: &MockCanvas, filename: &str, csv: &str) {
+    canvas.register(
+        Method::POST,
+        "v1/accounts/1/reports/provisioning_csv",
+        MockResponse::ok(json!({
+            "id": 8,
+            "report": "provisioning_csv",
+            "status": "complete",
+            "created_at": "2024-06-01T00:00:00Z",
+            "parameters": {},
+            "progress": 100,
+            "attachment": {
+                "id": 3,
+                "display_name": filename,
+                "filename": filename,
+                "content-type": "text/csv",
+                "url": canvas.api_url().replace("/api", "/files/3/download"),
+                "size": csv.len(),
+                "created_at": "2024-06-01T00:00:00Z",
+                "updated_at": "2024-06-01T00:00:00Z",
+            },
+        })),
+    );
+    canvas.register(
+        Method::GET,
+        "/files/3/download",
+        MockResponse::bytes("text/csv", csv.to_string()),
+    );
+}
+
+fn report_dir(name: &str) -> PathBuf {
+    std::env::temp_dir().join(format!("canvas-api-{name}-{}", std::process::id()))
+}
+
+#[tokio::test]
+async fn reads_rows_with_blank_cells() {
+    let canvas = MockCanvas::start().await;
+    register_csv_report(
+        &canvas,
+        "users.csv",
+        "canvas_user_id,user_id,login_id,first_name,last_name,email,status,created_by_sis,pronouns\n\
+         5,S005,ada,Ada,Lovelace,ada@example.com,active,true,she/her\n\
+         6,,grace,,,,active,,\n\
+         7,S007,alan,Alan,Turing,,deleted,0,\n",
+    );
+    let client = client(&canvas);
+    let dir = report_dir("user-rows");
+
+    let rows = client
+        .run_report_rows::<UserRow>(
+            AccountId::new(1),
+            String::from("provisioning_csv"),
+            ReportParameters::new().into(),
+            &poll(),
+            &dir,
+        )
+        .await
+        .unwrap()
+        .collect::<Result<Vec<_>, _>>()
+        .unwrap();
+    std::fs::remove_dir_all(dir).unwrap();
+
+    assert_eq!(rows.len(), 3);
+    assert_eq!(rows[0].canvas_user_id, Some(canvas_api::UserId::new(5)));
+    assert_eq!(rows[0].user_id.as_deref(), Some("S005"));
+    assert_eq!(rows[0].email.as_deref(), Some("ada@example.com"));
+    assert_eq!(rows[0].created_by_sis, Some(true));
+    // columns the report does not include are missing
+    assert_eq!(rows[0].integration_id, None);
+
+    assert_eq!(rows[1].login_id.as_deref(), Some("grace"));
+    assert_eq!(rows[1].user_id, None);
+    assert_eq!(rows[1].first_name, None);
+    assert_eq!(rows[1].email, None);
+    assert_eq!(rows[1].created_by_sis, None);
+
+    assert_eq!(rows[2].status.as_deref(), Some("deleted"));
+    assert_eq!(rows[2].created_by_sis, Some(false));
+}
+
+#[test]
+fn reads_dates_of_rows_leniently() {
+    let csv = "canvas_term_id,term_id,name,status,start_date,end_date,created_by_sis\n\
+               1,T1,Fall,active,2024-08-26T06:00:00Z,2024-12-20,false\n\
+               2,,Default Term,active,,,\n";
+
+    let rows = ReportRows::<TermRow, _>::from_reader(csv.as_bytes())
+        .collect::<Result<Vec<_>, _>>()
+        .unwrap();
+
+    assert_eq!(rows[0].start_date, Some(datetime("2024-08-26T06:00:00Z")));
+    assert_eq!(rows[0].end_date, Some(datetime("2024-12-20T00:00:00Z")));
+    assert_eq!(rows[0].created_by_sis, Some(false));
+    assert_eq!(rows[1].term_id, None);
+    assert_eq!(rows[1].start_date, None);
+    assert_eq!(rows[1].end_date, None);
+}
+
+#[test]
+fn fails_on_malformed_rows() {
+    let csv = "canvas_user_id,login_id,created_by_sis\n\
+               5,ada,true\n\
+               6,grace,maybe\n\
+               7,alan\n\
+               8,barbara,false\n";
+
+    let rows: Vec<_> = ReportRows::<UserRow, _>::from_reader(csv.as_bytes()).collect();
+
+    assert_eq!(rows.len(), 4);
+    assert!(rows[0].is_ok());
+    assert!(matches!(rows[1], Err(canvas_api::Error::Csv { .. })));
+    assert!(matches!(rows[2], Err(canvas_api::Error::Csv { .. })));
+    assert_eq!(
+        rows[3].as_ref().unwrap().login_id.as_deref(),
+        Some("barbara")
+    );
+}
+
+#[tokio::test]
+async fn fails_when_report_lacks_csv_file() {
+    let canvas = MockCanvas::start().await;
+    register_csv_report(&canvas, "accounts.csv", "canvas_account_id,name\n1,Root\n");
+    let client = client(&canvas);
+    let dir = report_dir("missing-rows");
+
+    let error = client
+        .run_report_rows::<UserRow>(
+            AccountId::new(1),
+            String::from("provisioning_csv"),
+            ReportParameters::new().into(),
+            &poll(),
+            &dir,
+        )
+        .await
+        .err()
+        .unwrap();
+    std::fs::remove_dir_all(dir).unwrap();
+
+    match error {
+        canvas_api::Error::MissingReportCsv { file_name } => assert_eq!(file_name, "users.csv"),
+        e => panic!("unexpected error {e:?}"),
+    }
+}
+
+/// Validates `form` against the reports available to the account, returning
+/// the problems found. A rejected report must not be submitted.
+async fn validate(
+    report_type: &str,
+    available: Value,
+    form: CreateReportForm,
+) -> Result<(), Vec<ReportParameterProblem>> {
+    let canvas = MockCanvas::start().await;
+    canvas.register_pages("v1/accounts/1/reports", vec![json!([available])]);
+    let path = format!("v1/accounts/1/reports/{report_type}");
+    canvas.register(
+        Method::POST,
+        &path,
+        MockResponse::ok(json!({
+            "id": 9,
+            "report": report_type,
+            "status": "created",
+            "created_at": "2024-06-01T00:00:00Z",
+            "progress": 0,
+        })),
+    );
+    let client = client(&canvas);
+
+    let result = client
+        .create_validated_report(AccountId::new(1), report_type.to_string(), form)
+        .await;
+
+    let submitted = canvas
+        .received_requests()
+        .iter()
+        .any(|request| request.method() == Method::POST);
+    match result {
+        Ok(_) => {
+            assert!(submitted);
+            Ok(())
+        }
+        Err(canvas_api::Error::InvalidReportParameters {
+            report_type: rejected,
+            problems,
+        }) => {
+            assert_eq!(rejected, report_type);
+            assert!(!submitted, "a rejected report was submitted");
+            Err(problems)
+        }
+        Err(e) => panic!("unexpected error {e:?}"),
+    }
+}
+
+fn grade_export(parameters: Value) -> Value {
+    json!({
+        "report": "grade_export_csv",
+        "title": "Grade Export",
+        "parameters": parameters,
+    })
+}
+
+fn no_submissions() -> Value {
+    json!({
+        "report": "students_with_no_submissions_csv",
+        "title": "Students with no submissions",
+        "parameters": {
+            "enrollment_term_id": { "required": false, "description": "The term" },
+            "course_id": { "required": false, "description": "The course" },
+            "start_at": { "required": true, "description": "The start of the window" },
+            "end_at": { "required": true, "description": "The end of the window" },
+        },
+    })
+}
+
+#[tokio::test]
+async fn rejects_unavailable_reports() {
+    let problems = validate(
+        "sis_export_csv",
+        grade_export(json!({})),
+        GradeExportReport::new().into(),
+    )
+    .await
+    .unwrap_err();
+
+    assert_eq!(problems, [ReportParameterProblem::UnavailableReport]);
+}
+
+#[tokio::test]
+async fn rejects_unknown_parameters() {
+    let form = CreateReportForm {
+        course_id: Some(CourseId::new(5)),
+        skip_message: Some(true),
+        ..GradeExportReport::new().set_term(TermId::new(2)).into()
+    };
+
+    let problems = validate(
+        "grade_export_csv",
+        grade_export(json!({
+            "enrollment_term_id": { "required": false, "description": "The term" },
+        })),
+        form,
+    )
+    .await
+    .unwrap_err();
+
+    // `skip_message` is accepted by every report
+    assert_eq!(
+        problems,
+        [ReportParameterProblem::UnknownParameter {
+            name: String::from("course_id")
+        }]
+    );
+}
+
+#[tokio::test]
+async fn rejects_missing_required_parameters() {
+    let problems = validate(
+        "grade_export_csv",
+        grade_export(json!({
+            "enrollment_term_id": { "required": "true", "description": "The term" },
+            "include_deleted": { "required": false, "description": "Deleted enrollments" },
+        })),
+        GradeExportReport::new().into(),
+    )
+    .await
+    .unwrap_err();
+
+    assert_eq!(
+        problems,
+        [ReportParameterProblem::MissingParameter {
+            name: String::from("enrollment_term_id")
+        }]
+    );
+}
+
+#[tokio::test]
+async fn rejects_provisioning_reports_without_files() {
+    let provisioning = json!({
+        "report": "provisioning_csv",
+        "title": "Provisioning",
+        "parameters": {
+            "users": { "required": false, "description": "Users" },
+            "courses": { "required": false, "description": "Courses" },
+        },
+    });
+
+    let problems = validate(
+        "provisioning_csv",
+        provisioning.clone(),
+        ProvisioningReport::new().set_users(false).into(),
+    )
+    .await
+    .unwrap_err();
+    assert_eq!(problems, [ReportParameterProblem::NoFilesSelected]);
+
+    validate(
+        "provisioning_csv",
+        provisioning,
+        ProvisioningReport::new().set_users(true).into(),
+    )
+    .await
+    .unwrap();
+}
+
+#[tokio::test]
+async fn rejects_windows_that_end_before_they_start() {
+    let start_at = datetime("2024-06-01T00:00:00Z");
+
+    for end_at in [start_at, datetime("2024-05-31T00:00:00Z")] {
+        let problems = validate(
+            "students_with_no_submissions_csv",
+            no_submissions(),
+            NoSubmissionsReport::new(start_at, end_at).into(),
+        )
+        .await
+        .unwrap_err();
+
+        assert_eq!(problems, [ReportParameterProblem::EndBeforeStart]);
+    }
+}
+
+#[tokio::test]
+async fn rejects_windows_longer_than_two_weeks() {
+    let problems = validate(
+        "students_with_no_submissions_csv",
+        no_submissions(),
+        NoSubmissionsReport::new(
+            datetime("2024-06-01T00:00:00Z"),
+            datetime("2024-06-15T00:00:01Z"),
+        )
+        .into(),
+    )
+    .await
+    .unwrap_err();
+
+    assert_eq!(problems, [ReportParameterProblem::WindowTooLong]);
+}
+
+#[tokio::test]
+async fn accepts_windows_of_exactly_two_weeks() {
+    validate(
+        "students_with_no_submissions_csv",
+        no_submissions(),
+        NoSubmissionsReport::new(
+            datetime("2024-06-01T00:00:00Z"),
+            datetime("2024-06-15T00:00:00Z"),
+        )
+        .into(),
+    )
+    .await
+    .unwrap();
+}
+
+const DAY: u64 = 24 * 60 * 60;
+
+fn finished_report(id: u64, status: &str, created_at: Option<String>) -> Value {
+    json!({
+        "id": id,
+        "report": "provisioning_csv",
+        "status": status,
+        "created_at": created_at,
+        "progress": 100,
+    })
+}
+
+/// An account with `provisioning_csv` reports, which are deleted on request.
+async fn retention_canvas(reports: Vec<Value>) -> MockCanvas {
+    let canvas = MockCanvas::start().await;
+    canvas.register_pages(
+        "v1/accounts/1/reports",
+        vec![json!([{ "report": "provisioning_csv", "title": "Provisioning" }])],
+    );
+    for report in &reports {
+        canvas.register(
+            Method::DELETE,
+            &format!("v1/accounts/1/reports/provisioning_csv/{}", report["id"]),
+            MockResponse::ok(report.clone()),
+        );
+    }
+    canvas.register_pages(
+        "v1/accounts/1/reports/provisioning_csv",
+        vec![Value::Array(reports)],
+    );
+
+    canvas
+}
+
+fn deleted_paths(canvas: &MockCanvas) -> Vec<String> {
+    let mut paths: Vec<String> = canvas
+        .received_requests()
+        .iter()
+        .filter(|request| request.method() == Method::DELETE)
+        .map(|request| request.path().to_string())
+        .collect();
+    paths.sort();
+
+    paths
+}
+
+fn report_ids(reports: &[Report]) -> Vec<u64> {
+    let mut ids: Vec<u64> = reports
+        .iter()
+        .map(|report| report.id().as_u64().unwrap())
+        .collect();
+    ids.sort();
+
+    ids
+}
+
+/// Complete reports created 1 to 4 days ago, out of order, along with an
+/// older running report and a report without a creation time.
+fn report_history() -> Vec<Value> {
+    vec![
+        finished_report(3, "complete", Some(ago(3 * DAY))),
+        finished_report(1, "complete", Some(ago(DAY))),
+        finished_report(4, "error", Some(ago(4 * DAY))),
+        finished_report(2, "complete", Some(ago(2 * DAY))),
+        finished_report(5, "running", Some(ago(10 * DAY))),
+        finished_report(6, "complete", None),
+    ]
+}
+
+#[tokio::test]
+async fn keeps_the_newest_reports_of_each_type() {
+    let canvas = retention_canvas(report_history()).await;
+    let client = client(&canvas);
+    let policy = RetentionPolicy::new().set_rule("provisioning_csv", RetentionRule::KeepLast(2));
+
+    let deleted = client
+        .apply_report_retention(AccountId::new(1), &policy)
+        .await
+        .unwrap();
+
+    assert_eq!(report_ids(&deleted), [3, 4]);
+    assert_eq!(
+        deleted_paths(&canvas),
+        [
+            "v1/accounts/1/reports/provisioning_csv/3",
+            "v1/accounts/1/reports/provisioning_csv/4",
+        ]
+    );
+}
+
+#[tokio::test]
+async fn keeps_reports_newer_than_the_cutoff() {
+    let canvas = retention_canvas(vec![
+        finished_report(1, "complete", Some(ago(DAY))),
+        finished_report(2, "complete", Some(ago(7 * DAY - 60 * 60))),
+        finished_report(3, "complete", Some(ago(7 * DAY + 60 * 60))),
+        finished_report(4, "aborted", Some(ago(30 * DAY))),
+        finished_report(5, "created", Some(ago(30 * DAY))),
+        finished_report(6, "complete", None),
+    ])
+    .await;
+    let client = client(&canvas);
+    let policy = RetentionPolicy::new()
+        .set_default_rule(RetentionRule::NewerThan(Duration::from_secs(7 * DAY)));
+
+    let deleted = client
+        .apply_report_retention(AccountId::new(1), &policy)
+        .await
+        .unwrap();
+
+    assert_eq!(report_ids(&deleted), [3, 4]);
+    assert_eq!(deleted_paths(&canvas).len(), 2);
+}
+
+#[tokio::test]
+async fn keeps_every_report_by_default() {
+    let canvas = retention_canvas(report_history()).await;
+    let client = client(&canvas);
+
+    let deleted = client
+        .apply_report_retention(AccountId::new(1), &RetentionPolicy::new())
+        .await
+        .unwrap();
+
+    assert!(deleted.is_empty());
+    assert!(deleted_paths(&canvas).is_empty());
+}
+
+#[tokio::test]
+async fn deletes_nothing_in_a_dry_run() {
+    let canvas = retention_canvas(report_history()).await;
+    let client = client(&canvas);
+    let policy = RetentionPolicy::new()
+        .set_default_rule(RetentionRule::KeepLast(0))
+        .set_dry_run(true);
+
+    let deleted = client
+        .apply_report_retention(AccountId::new(1), &policy)
+        .await
+        .unwrap();
+
+    // running reports and reports without a creation time are kept
+    assert_eq!(report_ids(&deleted), [1, 2, 3, 4]);
+    assert!(deleted_paths(&canvas).is_empty());
+}
@@ -0,0 +1,136 @@
+//! Creating appointment groups with time slots on a `MockCanvas`.
+
+use std::time::Duration;
+
+use canvas_api::{
+    extensions::appointment_groups::{AppointmentGroupForm, TimeSlot},
+    mock::{MockCanvas, MockResponse},
+    AppointmentGroupsExt, Error,
+};
+use reqwest::Method;
+use serde_json::json;
+
+fn datetime(s: &str) -> canvas_api::DateTime {
+    #[cfg(feature = "chrono")]
+    return chrono::DateTime::parse_from_rfc3339(s).unwrap();
+    #[cfg(not(feature = "chrono"))]
+    return time::OffsetDateTime::parse(s, &time::format_description::well_known::Rfc3339).unwrap();
+}
+
+#[test]
+fn splits_windows_into_time_slots() {
+    let slots = TimeSlot::evenly_spaced(
+        datetime("2024-06-03T15:00:00Z"),
+        datetime("2024-06-03T16:10:00Z"),
+        Duration::from_secs(20 * 60),
+    )
+    .unwrap();
+
+    // the last 10 minutes are too short for another slot
+    assert_eq!(
+        slots,
+        [
+            TimeSlot::new(
+                datetime("2024-06-03T15:00:00Z"),
+                datetime("2024-06-03T15:20:00Z")
+            ),
+            TimeSlot::new(
+                datetime("2024-06-03T15:20:00Z"),
+                datetime("2024-06-03T15:40:00Z")
+            ),
+            TimeSlot::new(
+                datetime("2024-06-03T15:40:00Z"),
+                datetime("2024-06-03T16:00:00Z")
+            ),
+        ]
+    );
+}
+
+#[test]
+fn splits_empty_windows_into_no_time_slots() {
+    let start_at = datetime("2024-06-03T15:00:00Z");
+
+    for end_at in [start_at, datetime("2024-06-03T14:00:00Z")] {
+        let slots =
+            TimeSlot::evenly_spaced(start_at, end_at, Duration::from_secs(20 * 60)).unwrap();
+
+        assert!(slots.is_empty());
+    }
+}
+
+#[test]
+fn rejects_time_slot_lengths_that_are_not_whole_seconds() {
+    let start_at = datetime("2024-06-03T15:00:00Z");
+    let end_at = datetime("2024-06-03T16:00:00Z");
+
+    for length in [
+        Duration::ZERO,
+        Duration::from_millis(500),
+        Duration::from_millis(1500),
+    ] {
+        assert!(matches!(
+            TimeSlot::evenly_spaced(start_at, end_at, length),
+            Err(Error::InvalidTimeSlotLength { length: rejected }) if rejected == length
+        ));
+    }
+}
+
+#[tokio::test]
+async fn sends_time_slots_in_index_order() {
+    let canvas = MockCanvas::start().await;
+    canvas.register(
+        Method::POST,
+        "v1/appointment_groups",
+        MockResponse::ok(json!({
+            "id": 5,
+            "title": "Advising",
+            "workflow_state": "pending",
+        })),
+    );
+    let client = canvas.client_builder().build().unwrap();
+    let slots = TimeSlot::evenly_spaced(
+        datetime("2024-06-03T15:00:00Z"),
+        datetime("2024-06-03T16:50:00Z"),
+        Duration::from_secs(10 * 60),
+    )
+    .unwrap();
+
+    client
+        .create_appointment_group(
+            &AppointmentGroupForm::new(vec![String::from("course_123")])
+                .set_title("Advising")
+                .add_time_slots(slots),
+        )
+        .await
+        .unwrap();
+
+    let request = canvas.assert_received(Method::POST, "v1/appointment_groups");
+    let time_slots: Vec<(String, canvas_api::DateTime)> = request
+        .form()
+        .into_iter()
+        .filter(|(name, _)| name.starts_with("appointment_group[new_appointments]"))
+        .map(|(name, value)| (name, datetime(&value)))
+        .collect();
+    let expected: Vec<(String, canvas_api::DateTime)> = (0..11)
+        .flat_map(|index| {
+            let name = format!("appointment_group[new_appointments][{index}][]");
+            let time = |minutes: u32| {
+                datetime(&format!(
+                    "2024-06-03T{:02}:{:02}:00Z",
+                    15 + minutes / 60,
+                    minutes % 60
+                ))
+            };
+
+            [
+                (name.clone(), time(index * 10)),
+                (name, time(index * 10 + 10)),
+            ]
+        })
+        .collect();
+    assert_eq!(time_slots, expected);
+    assert_eq!(
+        request.form_values("appointment_group[context_codes][]"),
+        ["course_123"]
+    );
+}
@@ -0,0 +1,68 @@
+//! Driving the blocking client against a `MockCanvas` from synchronous code.
+
+use canvas_api::{
+    blocking::AccountsExt,
+    mock::{MockCanvas, MockResponse},
+    AccountId, Error, RetryPolicy,
+};
+use reqwest::{Method, StatusCode};
+use serde_json::{json, Value};
+
+fn account(id: u64) -> Value {
+    json!({ "id": id, "name": format!("Account {id}") })
+}
+
+/// Serves the mock from a runtime of its own, as the blocking client must not
+/// be used from within one.
+fn start_mock() -> (tokio::runtime::Runtime, MockCanvas) {
+    let runtime = tokio::runtime::Runtime::new().unwrap();
+    let canvas = runtime.block_on(MockCanvas::start());
+
+    (runtime, canvas)
+}
+
+#[test]
+fn makes_requests_without_a_runtime() {
+    let (_runtime, canvas) = start_mock();
+    canvas.register(Method::GET, "v1/accounts/1", MockResponse::ok(account(1)));
+    canvas.register(
+        Method::GET,
+        "v1/accounts/9",
+        MockResponse::error(
+            StatusCode::NOT_FOUND,
+            "The specified resource does not exist.",
+        ),
+    );
+    let client = canvas
+        .client_builder()
+        .set_retry_policy(RetryPolicy::never())
+        .build_blocking()
+        .unwrap();
+
+    let found = client.get_account(AccountId::new(1)).unwrap();
+    let missing = client.get_account(AccountId::new(9)).unwrap_err();
+
+    assert_eq!(found.id(), &AccountId::new(1));
+    assert!(matches!(missing, Error::NotFound { .. }), "{missing:?}");
+    assert_eq!(canvas.received_requests().len(), 2);
+}
+
+#[test]
+fn iterates_pages_lazily() {
+    let (_runtime, canvas) = start_mock();
+    canvas.register_pages(
+        "v1/accounts/1/sub_accounts",
+        vec![json!([account(2), account(3)]), json!([account(4)])],
+    );
+    let client = canvas.client_builder().build_blocking().unwrap();
+
+    let mut accounts = client.get_sub_accounts_iter(AccountId::new(1), false);
+
+    assert_eq!(accounts.next().unwrap().unwrap().id(), &AccountId::new(2));
+    assert_eq!(canvas.received_requests().len(), 1);
+    let rest: Vec<AccountId> = accounts
+        .map(|account| account.unwrap().id().clone())
+        .collect();
+    assert_eq!(rest, [AccountId::new(3), AccountId::new(4)]);
+    assert_eq!(canvas.received_requests().len(), 2);
+}
@@ -0,0 +1,68 @@
+//! Listing calendar items from a `MockCanvas`.
+
+use canvas_api::{
+    extensions::calendar_events::{CalendarEventQuery, CalendarEventType},
+    mock::MockCanvas,
+    AssignmentId, CalendarEventId, CalendarEventsExt,
+};
+use serde_json::json;
+
+#[tokio::test]
+async fn lists_assignment_calendar_items() {
+    let canvas = MockCanvas::start().await;
+    canvas.register_pages(
+        "v1/calendar_events",
+        vec![json!([{
+            "id": "assignment_987",
+            "title": "Essay",
+            "start_at": "2024-06-07T23:59:00Z",
+            "end_at": "2024-06-07T23:59:00Z",
+            "description": "<p>Write an essay.</p>",
+            "context_code": "course_123",
+            "context_name": "History",
+            "workflow_state": "published",
+            "url": "https://canvas.example.com/api/v1/calendar_events/assignment_987",
+            "html_url": "https://canvas.example.com/courses/123/assignments/987",
+            "all_day": false,
+            "all_day_date": "2024-06-07",
+            "created_at": "2024-05-01T00:00:00Z",
+            "updated_at": "2024-05-02T00:00:00Z",
+            "assignment": { "id": 987, "name": "Essay" },
+        }])],
+    );
+    let client = canvas.client_builder().build().unwrap();
+
+    let items = client
+        .list_calendar_events(
+            &CalendarEventQuery::new()
+                .set_event_type(CalendarEventType::Assignment)
+                .set_context_codes(vec![String::from("course_123")]),
+        )
+        .await
+        .unwrap();
+
+    assert_eq!(items.len(), 1);
+    assert_eq!(items[0].id(), &CalendarEventId::assignment(987));
+    assert_eq!(items[0].id().assignment_id(), Some(AssignmentId::new(987)));
+    assert_eq!(items[0].id().to_string(), "assignment_987");
+    assert_eq!(items[0].title(), "Essay");
+
+    let requests = canvas.received_requests();
+    assert_eq!(requests[0].query_value("type"), Some("assignment"));
+}
+
+#[test]
+fn parses_calendar_event_ids() {
+    assert_eq!(
+        "assignment_987".parse::<CalendarEventId>().unwrap(),
+        CalendarEventId::assignment(987)
+    );
+    assert_eq!(
+        "42".parse::<CalendarEventId>().unwrap(),
+        CalendarEventId::new(42)
+    );
+    assert_eq!(CalendarEventId::new(42).assignment_id(), None);
+    assert!("assignment_".parse::<CalendarEventId>().is_err());
+    // other ID types do not accept assignment calendar items
+    assert!("assignment_987".parse::<AssignmentId>().is_err());
+}
@@ -0,0 +1,111 @@
+//! Recording traffic to a `Cassette` and replaying it without a server.
+
+use std::path::PathBuf;
+
+use canvas_api::{
+    cassette::{Cassette, REDACTED},
+    mock::{MockCanvas, MockResponse},
+    AccountId, AccountsExt, CanvasClient, RetryPolicy,
+};
+use reqwest::Method;
+use serde_json::{json, Value};
+
+fn cassette_path(name: &str) -> PathBuf {
+    std::env::temp_dir().join(format!("canvas-api-{name}-{}.json", std::process::id()))
+}
+
+fn replaying(path: &PathBuf) -> CanvasClient {
+    // nothing listens on this port, so any request that is not replayed fails
+    CanvasClient::builder(String::new())
+        .set_api_url("http://127.0.0.1:9/api")
+        .set_retry_policy(RetryPolicy::never())
+        .set_cassette(Cassette::replay(path).unwrap())
+        .build()
+        .unwrap()
+}
+
+#[tokio::test]
+async fn replays_recorded_interactions() {
+    let path = cassette_path("replay");
+    {
+        let canvas = MockCanvas::start().await;
+        canvas.register(
+            Method::GET,
+            "v1/accounts/1",
+            MockResponse::ok(json!({ "id": 1, "name": "Root", "sis_account_id": "root" })),
+        );
+        canvas.register_pages(
+            "v1/accounts/1/sub_accounts",
+            vec![
+                json!([{ "id": 2, "name": "Science" }]),
+                json!([{ "id": 3, "name": "Arts" }]),
+            ],
+        );
+        let client = canvas
+            .client_builder()
+            .set_cassette(Cassette::record(&path))
+            .build()
+            .unwrap();
+
+        client.get_account(AccountId::new(1)).await.unwrap();
+        client
+            .get_sub_accounts(AccountId::new(1), false)
+            .await
+            .unwrap();
+    }
+
+    let client = replaying(&path);
+    let account = client.get_account(AccountId::new(1)).await.unwrap();
+    let sub_accounts = client
+        .get_sub_accounts(AccountId::new(1), false)
+        .await
+        .unwrap();
+
+    assert_eq!(account.name(), "Root");
+    assert_eq!(account.sis_account_id(), Some("root"));
+    assert_eq!(sub_accounts.len(), 2);
+
+    // every interaction is replayed once
+    let error = client.get_account(AccountId::new(1)).await.unwrap_err();
+    assert!(matches!(error, canvas_api::Error::Cassette { .. }));
+
+    std::fs::remove_file(path).unwrap();
+}
+
+#[tokio::test]
+async fn scrubs_recorded_responses() {
+    let path = cassette_path("scrub");
+    {
+        let canvas = MockCanvas::start().await;
+        canvas.register(
+            Method::GET,
+            "v1/accounts/1",
+            MockResponse::ok(json!({
+                "id": 1,
+                "name": "Root",
+                "admin": { "email": "admin@example.edu", "login_id": null },
+            })),
+        );
+        let client = canvas
+            .client_builder()
+            .set_cassette(Cassette::record(&path))
+            .build()
+            .unwrap();
+
+        client.get_account(AccountId::new(1)).await.unwrap();
+    }
+
+    let contents = std::fs::read_to_string(&path).unwrap();
+    std::fs::remove_file(&path).unwrap();
+    assert!(!contents.contains("mock-canvas-token"));
+
+    let cassette: Value = serde_json::from_str(&contents).unwrap();
+    let body: Value = serde_json::from_str(
+        cassette["interactions"][0]["response"]["body"]
+            .as_str()
+            .unwrap(),
+    )
+    .unwrap();
+    assert_eq!(body["admin"]["email"], REDACTED);
+    assert_eq!(body["admin"]["login_id"], Value::Null);
+}
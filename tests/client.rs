@@ -0,0 +1,497 @@
+//! Request and response handling of `CanvasClient`, exercised against a
+//! `MockCanvas`.
+
+use std::time::{Duration, Instant};
+
+use canvas_api::{
+    mock::{MockCanvas, MockResponse},
+    oauth::{MemoryTokenStore, OAuthClient, OAuthToken},
+    AccountId, AccountNotificationsExt, AccountsExt, CanvasClient, CanvasClientBuilder,
+    RateLimitConfig, RetryPolicy, RoleId, UserId,
+};
+use futures::StreamExt;
+use reqwest::{header::AUTHORIZATION, Method, StatusCode};
+use serde_json::{json, Value};
+
+fn account(id: u64) -> Value {
+    json!({ "id": id, "name": format!("Account {id}") })
+}
+
+/// A client that fails fast, so that error responses are returned as is.
+fn client(canvas: &MockCanvas) -> CanvasClient {
+    canvas
+        .client_builder()
+        .set_retry_policy(RetryPolicy::never())
+        .build()
+        .unwrap()
+}
+
+fn datetime(s: &str) -> canvas_api::DateTime {
+    #[cfg(feature = "chrono")]
+    return chrono::DateTime::parse_from_rfc3339(s).unwrap();
+    #[cfg(not(feature = "chrono"))]
+    return time::OffsetDateTime::parse(s, &time::format_description::well_known::Rfc3339).unwrap();
+}
+
+#[tokio::test]
+async fn paginates_through_link_headers() {
+    let canvas = MockCanvas::start().await;
+    canvas.register_pages(
+        "v1/accounts/1/sub_accounts",
+        vec![json!([account(2), account(3)]), json!([account(4)])],
+    );
+    let client = canvas.client_builder().set_per_page(2).build().unwrap();
+
+    let accounts = client
+        .get_sub_accounts(AccountId::new(1), true)
+        .await
+        .unwrap();
+
+    let ids: Vec<_> = accounts
+        .iter()
+        .map(|account| account.id().clone())
+        .collect();
+    assert_eq!(
+        ids,
+        [AccountId::new(2), AccountId::new(3), AccountId::new(4)]
+    );
+
+    let requests = canvas.received_requests();
+    assert_eq!(requests.len(), 2);
+    assert_eq!(requests[0].query_value("page"), None);
+    assert_eq!(requests[1].query_value("page"), Some("2"));
+    for request in &requests {
+        assert_eq!(request.query_value("per_page"), Some("2"));
+        assert_eq!(request.query_value("recursive"), Some("true"));
+    }
+}
+
+#[tokio::test]
+async fn streams_request_pages_lazily() {
+    let canvas = MockCanvas::start().await;
+    canvas.register_pages(
+        "v1/accounts/1/sub_accounts",
+        vec![json!([account(2)]), json!([account(3)])],
+    );
+    let client = client(&canvas);
+
+    let first = client
+        .get_sub_accounts_stream(AccountId::new(1), false)
+        .next()
+        .await
+        .unwrap()
+        .unwrap();
+
+    assert_eq!(first.id(), &AccountId::new(2));
+    assert_eq!(canvas.received_requests().len(), 1);
+}
+
+#[tokio::test]
+async fn stops_paginating_when_current_page_is_last() {
+    let canvas = MockCanvas::start().await;
+    let url = format!("{}/v1/accounts/1/sub_accounts", canvas.api_url());
+    canvas.register(
+        Method::GET,
+        "v1/accounts/1/sub_accounts",
+        MockResponse::ok(json!([account(2)])).with_header(
+            "Link",
+            &format!(
+                "<{url}?page=1>; rel=\"current\",<{url}?page=2>; rel=\"next\",<{url}?page=1>; rel=\"last\""
+            ),
+        ),
+    );
+    let client = client(&canvas);
+
+    let accounts = client
+        .get_sub_accounts(AccountId::new(1), false)
+        .await
+        .unwrap();
+
+    assert_eq!(accounts.len(), 1);
+    assert_eq!(canvas.received_requests().len(), 1);
+}
+
+#[tokio::test]
+async fn maps_error_statuses_to_errors() {
+    let canvas = MockCanvas::start().await;
+    let responses = [
+        (
+            1,
+            MockResponse::error(StatusCode::UNAUTHORIZED, "Invalid access token."),
+        ),
+        (
+            2,
+            MockResponse::error(StatusCode::FORBIDDEN, "user not authorized"),
+        ),
+        (3, MockResponse::error(StatusCode::NOT_FOUND, "not found")),
+        (4, MockResponse::unprocessable("name", "can't be blank")),
+        (5, MockResponse::throttled()),
+        (
+            6,
+            MockResponse::error(StatusCode::TOO_MANY_REQUESTS, "slow down"),
+        ),
+        (
+            7,
+            MockResponse::error(StatusCode::INTERNAL_SERVER_ERROR, "oops"),
+        ),
+    ];
+    for (id, response) in responses {
+        canvas.register(Method::GET, &format!("v1/accounts/{id}"), response);
+    }
+    let client = canvas
+        .client_builder()
+        .set_retry_policy(RetryPolicy::never())
+        .set_rate_limit(None)
+        .build()
+        .unwrap();
+    let get = |id| client.get_account(AccountId::new(id));
+
+    match get(1).await.unwrap_err() {
+        canvas_api::Error::Unauthorized { response } => {
+            assert_eq!(response.status(), StatusCode::UNAUTHORIZED);
+            assert_eq!(response.messages(), ["Invalid access token."]);
+        }
+        e => panic!("unexpected error {e:?}"),
+    }
+    assert!(matches!(
+        get(2).await.unwrap_err(),
+        canvas_api::Error::Forbidden { .. }
+    ));
+    assert!(matches!(
+        get(3).await.unwrap_err(),
+        canvas_api::Error::NotFound { .. }
+    ));
+    match get(4).await.unwrap_err() {
+        canvas_api::Error::Unprocessable { errors, response } => {
+            assert_eq!(errors["name"], ["can't be blank"]);
+            assert_eq!(response.messages(), ["name: can't be blank"]);
+        }
+        e => panic!("unexpected error {e:?}"),
+    }
+    assert!(matches!(
+        get(5).await.unwrap_err(),
+        canvas_api::Error::Throttled { .. }
+    ));
+    assert!(matches!(
+        get(6).await.unwrap_err(),
+        canvas_api::Error::Throttled { .. }
+    ));
+    let error = get(7).await.unwrap_err();
+    assert!(matches!(error, canvas_api::Error::Api { .. }));
+    let response = error.response().unwrap();
+    assert_eq!(response.method(), Method::GET);
+    assert_eq!(response.path(), "/api/v1/accounts/7");
+}
+
+fn retrying(canvas: &MockCanvas) -> CanvasClientBuilder {
+    canvas
+        .client_builder()
+        .set_rate_limit(None)
+        .set_retry_policy(
+            RetryPolicy::new()
+                .set_base_delay(Duration::ZERO)
+                .set_jitter(false),
+        )
+}
+
+#[tokio::test]
+async fn retries_transient_failures() {
+    let canvas = MockCanvas::start().await;
+    canvas.register(
+        Method::GET,
+        "v1/accounts/1",
+        MockResponse::error(StatusCode::SERVICE_UNAVAILABLE, "unavailable"),
+    );
+    canvas.register(Method::GET, "v1/accounts/1", MockResponse::throttled());
+    canvas.register(Method::GET, "v1/accounts/1", MockResponse::ok(account(1)));
+    let client = retrying(&canvas).build().unwrap();
+
+    let account = client.get_account(AccountId::new(1)).await.unwrap();
+
+    assert_eq!(account.id(), &AccountId::new(1));
+    assert_eq!(canvas.received_requests().len(), 3);
+}
+
+#[tokio::test]
+async fn gives_up_after_max_attempts() {
+    let canvas = MockCanvas::start().await;
+    canvas.register(
+        Method::GET,
+        "v1/accounts/1",
+        MockResponse::error(StatusCode::BAD_GATEWAY, "bad gateway"),
+    );
+    let client = retrying(&canvas).build().unwrap();
+
+    let error = client.get_account(AccountId::new(1)).await.unwrap_err();
+
+    assert_eq!(
+        error.response().map(|response| response.status()),
+        Some(StatusCode::BAD_GATEWAY)
+    );
+    assert_eq!(canvas.received_requests().len(), 3);
+}
+
+#[tokio::test]
+async fn does_not_retry_client_errors_or_posts() {
+    let canvas = MockCanvas::start().await;
+    canvas.register(
+        Method::GET,
+        "v1/accounts/1",
+        MockResponse::error(StatusCode::NOT_FOUND, "not found"),
+    );
+    canvas.register(
+        Method::POST,
+        "v1/accounts/1/account_notifications",
+        MockResponse::error(StatusCode::SERVICE_UNAVAILABLE, "unavailable"),
+    );
+    let client = retrying(&canvas).build().unwrap();
+    let notification = canvas_api::extensions::account_notifications::AccountNotification::builder(
+        "Maintenance",
+        "Canvas is down on Saturday.",
+        datetime("2024-06-01T00:00:00Z"),
+        datetime("2024-06-02T00:00:00Z"),
+    )
+    .build()
+    .unwrap();
+
+    client.get_account(AccountId::new(1)).await.unwrap_err();
+    client
+        .create_global_notification(AccountId::new(1), &notification)
+        .await
+        .unwrap_err();
+
+    assert_eq!(canvas.received_requests().len(), 2);
+}
+
+#[tokio::test]
+async fn pauses_requests_after_being_throttled() {
+    let canvas = MockCanvas::start().await;
+    canvas.register(Method::GET, "v1/accounts/1", MockResponse::throttled());
+    canvas.register(Method::GET, "v1/accounts/1", MockResponse::ok(account(1)));
+    let throttled_pause = Duration::from_millis(200);
+    // only throttled responses hold requests back
+    let rate_limit = RateLimitConfig::new()
+        .set_slow_below(0.0)
+        .set_pause_below(0.0)
+        .set_throttled_pause(throttled_pause);
+    let client = canvas
+        .client_builder()
+        .set_rate_limit(Some(rate_limit))
+        .set_retry_policy(RetryPolicy::never())
+        .build()
+        .unwrap();
+
+    assert!(matches!(
+        client.get_account(AccountId::new(1)).await.unwrap_err(),
+        canvas_api::Error::Throttled { .. }
+    ));
+    let start = Instant::now();
+    client.get_account(AccountId::new(1)).await.unwrap();
+
+    assert!(start.elapsed() >= throttled_pause);
+    assert_eq!(canvas.received_requests().len(), 2);
+}
+
+fn oauth_client(canvas: &MockCanvas, token: OAuthToken) -> CanvasClient {
+    let base_url = canvas.api_url().trim_end_matches("/api").to_string();
+    let oauth = OAuthClient::new(base_url, "client-id", "client-secret", "urn:test").unwrap();
+
+    CanvasClientBuilder::new_oauth(oauth, MemoryTokenStore::new(token))
+        .set_api_url(canvas.api_url())
+        .set_retry_policy(RetryPolicy::never())
+        .build()
+        .unwrap()
+}
+
+fn authorization(request: &canvas_api::mock::ReceivedRequest) -> &str {
+    request.headers()[AUTHORIZATION].to_str().unwrap()
+}
+
+#[tokio::test]
+async fn refreshes_oauth_token_once_on_unauthorized() {
+    let canvas = MockCanvas::start().await;
+    canvas.register(
+        Method::GET,
+        "v1/accounts/1",
+        MockResponse::error(StatusCode::UNAUTHORIZED, "Invalid access token."),
+    );
+    canvas.register(Method::GET, "v1/accounts/1", MockResponse::ok(account(1)));
+    canvas.register(
+        Method::POST,
+        "login/oauth2/token",
+        MockResponse::ok(json!({ "access_token": "fresh", "expires_in": 3600 })),
+    );
+    let client = oauth_client(
+        &canvas,
+        OAuthToken::new("stale", Some(String::from("refresh")), None),
+    );
+
+    client.get_account(AccountId::new(1)).await.unwrap();
+
+    let requests = canvas.received_requests();
+    assert_eq!(requests.len(), 3);
+    assert_eq!(authorization(&requests[0]), "Bearer stale");
+    assert_eq!(requests[1].path(), "login/oauth2/token");
+    assert_eq!(
+        requests[1].form_value("grant_type").as_deref(),
+        Some("refresh_token")
+    );
+    assert_eq!(
+        requests[1].form_value("refresh_token").as_deref(),
+        Some("refresh")
+    );
+    assert_eq!(authorization(&requests[2]), "Bearer fresh");
+}
+
+#[tokio::test]
+async fn returns_unauthorized_if_refreshed_token_is_rejected() {
+    let canvas = MockCanvas::start().await;
+    canvas.register(
+        Method::GET,
+        "v1/accounts/1",
+        MockResponse::error(StatusCode::UNAUTHORIZED, "Invalid access token."),
+    );
+    canvas.register(
+        Method::POST,
+        "login/oauth2/token",
+        MockResponse::ok(json!({ "access_token": "fresh" })),
+    );
+    let client = oauth_client(
+        &canvas,
+        OAuthToken::new("stale", Some(String::from("refresh")), None),
+    );
+
+    let error = client.get_account(AccountId::new(1)).await.unwrap_err();
+
+    assert!(matches!(error, canvas_api::Error::Unauthorized { .. }));
+    let refreshes = canvas
+        .received_requests()
+        .iter()
+        .filter(|request| request.path() == "login/oauth2/token")
+        .count();
+    assert_eq!(refreshes, 1);
+    assert_eq!(canvas.received_requests().len(), 3);
+}
+
+#[tokio::test]
+async fn does_not_refresh_without_refresh_token() {
+    let canvas = MockCanvas::start().await;
+    canvas.register(
+        Method::GET,
+        "v1/accounts/1",
+        MockResponse::error(StatusCode::UNAUTHORIZED, "Invalid access token."),
+    );
+    let client = oauth_client(&canvas, OAuthToken::new("stale", None, None));
+
+    let error = client.get_account(AccountId::new(1)).await.unwrap_err();
+
+    assert!(matches!(error, canvas_api::Error::Unauthorized { .. }));
+    assert_eq!(canvas.received_requests().len(), 1);
+}
+
+#[tokio::test]
+async fn injects_as_user_id_once() {
+    let canvas = MockCanvas::start().await;
+    canvas.register(Method::GET, "v1/accounts/1", MockResponse::ok(account(1)));
+    canvas.register_pages(
+        "v1/accounts/1/sub_accounts",
+        vec![json!([account(2)]), json!([account(3)])],
+    );
+    let client = client(&canvas);
+
+    client.get_account(AccountId::new(1)).await.unwrap();
+    assert_eq!(
+        canvas
+            .assert_received(Method::GET, "v1/accounts/1")
+            .query_value("as_user_id"),
+        None
+    );
+
+    let masquerading = client.as_user(UserId::sis("s123"));
+    masquerading.get_account(AccountId::new(1)).await.unwrap();
+    assert_eq!(
+        canvas
+            .assert_received(Method::GET, "v1/accounts/1")
+            .query_value("as_user_id"),
+        Some("sis_user_id:s123")
+    );
+
+    let masquerading = client.as_user(42);
+    masquerading
+        .get_sub_accounts(AccountId::new(1), false)
+        .await
+        .unwrap();
+    for request in canvas
+        .received_requests()
+        .iter()
+        .filter(|request| request.path() == "v1/accounts/1/sub_accounts")
+    {
+        let as_user_ids: Vec<_> = request
+            .query()
+            .iter()
+            .filter(|(key, _)| key == "as_user_id")
+            .map(|(_, value)| value.as_str())
+            .collect();
+        assert_eq!(as_user_ids, ["42"]);
+    }
+}
+
+#[tokio::test]
+async fn encodes_nested_form_bodies() {
+    let canvas = MockCanvas::start().await;
+    canvas.register(
+        Method::POST,
+        "v1/accounts/1/account_notifications",
+        MockResponse::ok(json!({
+            "id": 5,
+            "subject": "Maintenance",
+            "message": "Canvas is down on Saturday.",
+            "start_at": "2024-06-01T00:00:00Z",
+            "end_at": "2024-06-02T00:00:00Z",
+            "icon": "calendar",
+            "role_ids": [1, 2],
+        })),
+    );
+    let client = client(&canvas);
+    let notification = canvas_api::extensions::account_notifications::AccountNotification::builder(
+        "Maintenance",
+        "Canvas is down on Saturday.",
+        datetime("2024-06-01T00:00:00Z"),
+        datetime("2024-06-02T00:00:00Z"),
+    )
+    .set_icon(canvas_api::extensions::account_notifications::NotificationIcon::Calendar)
+    .set_role_ids(Some(vec![RoleId::new(1), RoleId::new(2)]))
+    .build()
+    .unwrap();
+
+    client
+        .create_global_notification(AccountId::new(1), &notification)
+        .await
+        .unwrap();
+
+    let request = canvas.assert_received(Method::POST, "v1/accounts/1/account_notifications");
+    assert_eq!(
+        request.headers()["content-type"],
+        "application/x-www-form-urlencoded"
+    );
+    assert_eq!(
+        request
+            .form_value("account_notification[subject]")
+            .as_deref(),
+        Some("Maintenance")
+    );
+    assert_eq!(
+        request.form_value("account_notification[icon]").as_deref(),
+        Some("calendar")
+    );
+    assert_eq!(
+        request
+            .form_value("account_notification[start_at]")
+            .map(|start_at| datetime(&start_at)),
+        Some(datetime("2024-06-01T00:00:00Z"))
+    );
+    assert_eq!(
+        request.form_values("account_notification_roles[]"),
+        ["1", "2"]
+    );
+}
@@ -0,0 +1,288 @@
+//! Planning and applying a notification sync against a `MockCanvas`.
+
+use canvas_api::{
+    extensions::account_notifications::sync::{
+        ChangeAction, DesiredState, Matching, NotificationSync,
+    },
+    mock::{MockCanvas, MockResponse},
+    CanvasClient, NotificationId, RetryPolicy,
+};
+use reqwest::Method;
+use serde_json::{json, Value};
+
+const NOTIFICATIONS_PATH: &str = "v1/accounts/1/account_notifications";
+
+fn client(canvas: &MockCanvas) -> CanvasClient {
+    canvas
+        .client_builder()
+        .set_retry_policy(RetryPolicy::never())
+        .build()
+        .unwrap()
+}
+
+fn notification(id: u64, subject: &str, message: &str, end_at: &str) -> Value {
+    json!({
+        "id": id,
+        "subject": subject,
+        "message": message,
+        "start_at": "2024-06-01T00:00:00Z",
+        "end_at": end_at,
+        "icon": "warning",
+    })
+}
+
+fn desired(notifications: &str) -> DesiredState {
+    DesiredState::from_yaml_str(&format!(
+        "accounts:\n  - account_id: 1\n    notifications:\n{notifications}"
+    ))
+    .unwrap()
+}
+
+fn desired_notification(key: &str, message: &str, end_at: &str) -> String {
+    format!(
+        "      - key: {key}\n        subject: Maintenance\n        message: {message}\n        start_at: 2024-06-01T00:00:00Z\n        end_at: {end_at}\n"
+    )
+}
+
+fn actions(
+    plan: &canvas_api::extensions::account_notifications::sync::SyncPlan,
+) -> Vec<(ChangeAction, &str)> {
+    plan.changes()
+        .iter()
+        .map(|change| (change.action(), change.key()))
+        .collect()
+}
+
+#[tokio::test]
+async fn plans_changes_to_tagged_notifications() {
+    let canvas = MockCanvas::start().await;
+    canvas.register_pages(
+        NOTIFICATIONS_PATH,
+        vec![json!([
+            notification(
+                1,
+                "Maintenance [same]",
+                "Unchanged.",
+                "2999-01-01T00:00:00Z"
+            ),
+            notification(
+                2,
+                "Maintenance [changed]",
+                "Before.",
+                "2999-01-01T00:00:00Z"
+            ),
+            notification(
+                3,
+                "Maintenance [removed]",
+                "Removed.",
+                "2999-01-01T00:00:00Z"
+            ),
+            notification(4, "Unmanaged", "Left alone.", "2999-01-01T00:00:00Z"),
+        ])],
+    );
+    let client = client(&canvas);
+    let desired = desired(
+        &[
+            desired_notification("same", "Unchanged.", "2999-01-01T00:00:00Z"),
+            desired_notification("changed", "After.", "2999-01-01T00:00:00Z"),
+            desired_notification("added", "Added.", "2999-01-01T00:00:00Z"),
+        ]
+        .concat(),
+    );
+
+    let plan = NotificationSync::new(&client).plan(&desired).await.unwrap();
+
+    assert_eq!(
+        actions(&plan),
+        [
+            (ChangeAction::Update, "changed"),
+            (ChangeAction::Create, "added"),
+            (ChangeAction::Delete, "removed"),
+        ]
+    );
+    assert_eq!(
+        plan.changes()[0].notification_id(),
+        Some(&NotificationId::new(2))
+    );
+    assert_eq!(plan.changes()[1].subject(), "Maintenance [added]");
+    assert_eq!(
+        plan.changes()[2].notification_id(),
+        Some(&NotificationId::new(3))
+    );
+
+    let request = canvas.assert_received(Method::GET, NOTIFICATIONS_PATH);
+    assert_eq!(request.query_value("include_past"), Some("true"));
+}
+
+#[tokio::test]
+async fn leaves_ended_notifications_alone() {
+    let canvas = MockCanvas::start().await;
+    canvas.register_pages(
+        NOTIFICATIONS_PATH,
+        vec![json!([
+            notification(
+                1,
+                "Maintenance [expired]",
+                "Expired.",
+                "2024-06-02T00:00:00Z"
+            ),
+            notification(2, "Maintenance [ended]", "Ended.", "2024-06-02T00:00:00Z"),
+        ])],
+    );
+    let client = client(&canvas);
+    let desired = desired(
+        &[
+            desired_notification("ended", "Ended.", "2024-06-02T00:00:00Z"),
+            desired_notification("missed", "Missed.", "2024-06-02T00:00:00Z"),
+        ]
+        .concat(),
+    );
+
+    let plan = NotificationSync::new(&client).plan(&desired).await.unwrap();
+
+    assert!(plan.is_empty(), "{:?}", actions(&plan));
+}
+
+#[tokio::test]
+async fn rejects_duplicate_desired_keys() {
+    let canvas = MockCanvas::start().await;
+    let client = client(&canvas);
+    let desired = desired(
+        &[
+            desired_notification("twice", "First.", "2999-01-01T00:00:00Z"),
+            desired_notification("twice", "Second.", "2999-01-01T00:00:00Z"),
+        ]
+        .concat(),
+    );
+
+    let error = NotificationSync::new(&client)
+        .plan(&desired)
+        .await
+        .unwrap_err();
+
+    assert!(matches!(error, canvas_api::Error::NotificationSync { .. }));
+    assert!(canvas.received_requests().is_empty());
+}
+
+#[tokio::test]
+async fn rejects_duplicate_desired_accounts() {
+    let canvas = MockCanvas::start().await;
+    let client = client(&canvas);
+    let desired =
+        DesiredState::from_yaml_str("accounts:\n  - account_id: 1\n  - account_id: 1\n").unwrap();
+
+    let error = NotificationSync::new(&client)
+        .plan(&desired)
+        .await
+        .unwrap_err();
+
+    assert!(matches!(error, canvas_api::Error::NotificationSync { .. }));
+}
+
+#[tokio::test]
+async fn rejects_notifications_sharing_a_subject_tag() {
+    let canvas = MockCanvas::start().await;
+    canvas.register_pages(
+        NOTIFICATIONS_PATH,
+        vec![json!([
+            notification(1, "Maintenance [shared]", "One.", "2999-01-01T00:00:00Z"),
+            notification(2, "Outage [shared]", "Two.", "2999-01-01T00:00:00Z"),
+        ])],
+    );
+    let client = client(&canvas);
+    let desired = desired(&desired_notification(
+        "shared",
+        "One.",
+        "2999-01-01T00:00:00Z",
+    ));
+
+    let error = NotificationSync::new(&client)
+        .plan(&desired)
+        .await
+        .unwrap_err();
+
+    assert!(matches!(error, canvas_api::Error::NotificationSync { .. }));
+}
+
+#[tokio::test]
+async fn applies_plan_and_records_state() {
+    let canvas = MockCanvas::start().await;
+    canvas.register_pages(NOTIFICATIONS_PATH, vec![json!([])]);
+    canvas.register(
+        Method::POST,
+        NOTIFICATIONS_PATH,
+        MockResponse::ok(notification(
+            9,
+            "Maintenance",
+            "Added.",
+            "2999-01-01T00:00:00Z",
+        )),
+    );
+    let client = client(&canvas);
+    let state_path = std::env::temp_dir().join(format!(
+        "canvas-api-notification-sync-{}.json",
+        std::process::id()
+    ));
+    let sync = NotificationSync::new(&client).set_matching(Matching::StateFile(state_path.clone()));
+    let desired = desired(&desired_notification(
+        "added",
+        "Added.",
+        "2999-01-01T00:00:00Z",
+    ));
+
+    let plan = sync.plan(&desired).await.unwrap();
+    let report = sync.apply(plan).await.unwrap();
+
+    assert!(report.is_success());
+    assert_eq!(
+        report.results()[0].result_id(),
+        Some(&NotificationId::new(9))
+    );
+    let request = canvas.assert_received(Method::POST, NOTIFICATIONS_PATH);
+    assert_eq!(
+        request
+            .form_value("account_notification[subject]")
+            .as_deref(),
+        Some("Maintenance")
+    );
+
+    // the created notification is now managed through the state file
+    canvas.register_pages(
+        NOTIFICATIONS_PATH,
+        vec![json!([notification(
+            9,
+            "Maintenance",
+            "Added.",
+            "2999-01-01T00:00:00Z"
+        )])],
+    );
+    let plan = sync.plan(&desired).await.unwrap();
+    std::fs::remove_file(state_path).unwrap();
+    assert!(plan.is_empty(), "{:?}", actions(&plan));
+}
+
+#[tokio::test]
+async fn applies_removals_by_expiring_notifications() {
+    let canvas = MockCanvas::start().await;
+    let removed = notification(
+        3,
+        "Maintenance [removed]",
+        "Removed.",
+        "2999-01-01T00:00:00Z",
+    );
+    canvas.register_pages(NOTIFICATIONS_PATH, vec![json!([removed.clone()])]);
+    canvas.register(
+        Method::PUT,
+        "v1/accounts/1/account_notifications/3",
+        MockResponse::ok(removed),
+    );
+    let client = client(&canvas);
+    let sync = NotificationSync::new(&client);
+
+    let plan = sync.plan(&desired("        []\n")).await.unwrap();
+    let report = sync.apply(plan).await.unwrap();
+
+    assert!(report.is_success());
+    let request = canvas.assert_received(Method::PUT, "v1/accounts/1/account_notifications/3");
+    assert!(request.form_value("account_notification[end_at]").is_some());
+}